@@ -0,0 +1,3 @@
+-- Flags accounts whose stored password hash could not be verified
+ALTER TABLE users
+    ADD COLUMN password_reset_required BOOLEAN NOT NULL DEFAULT FALSE; -- Set when the password must be reset before the next login
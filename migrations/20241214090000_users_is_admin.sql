@@ -0,0 +1,3 @@
+-- Marks users allowed to call administrative endpoints
+ALTER TABLE users
+    ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE; -- Grants the admin role in issued access tokens
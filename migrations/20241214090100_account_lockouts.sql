@@ -0,0 +1,20 @@
+-- Failed login tracking and temporary account lockouts
+CREATE TABLE account_lockouts (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE, -- One row per user with failed logins
+    failed_attempts INTEGER NOT NULL DEFAULT 0,                       -- Failed logins since the last success or lockout
+    lockouts INTEGER NOT NULL DEFAULT 0,                              -- Consecutive lockouts, drives the exponential backoff
+    locked_until TIMESTAMPTZ,                                         -- Login is refused until this time
+    last_failed_at TIMESTAMPTZ                                        -- Time of the most recent failed login
+);
+
+CREATE TABLE lockout_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- Account the event applies to
+    event VARCHAR(20) NOT NULL,                                       -- 'locked' or 'unlocked'
+    ip_address VARCHAR(45),                                           -- Client IP that triggered the event
+    actor_id UUID REFERENCES users (id) ON DELETE SET NULL,           -- Admin who performed an unlock
+    locked_until TIMESTAMPTZ,                                         -- End of the lockout for 'locked' events
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the event happened
+);
+
+CREATE INDEX lockout_events_user_id_idx ON lockout_events (user_id, created_at DESC);
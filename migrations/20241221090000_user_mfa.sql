@@ -0,0 +1,18 @@
+-- TOTP second factor and single-use recovery codes
+CREATE TABLE user_totp (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE, -- One authenticator per user
+    secret_ciphertext BYTEA NOT NULL,                                 -- AES-256-GCM encrypted TOTP secret
+    secret_nonce BYTEA NOT NULL,                                      -- Nonce used to encrypt the secret
+    confirmed_at TIMESTAMPTZ,                                         -- Set once the first code was verified
+    last_used_step BIGINT,                                            -- Last accepted time step, stops code replay
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When enrollment started
+);
+
+CREATE TABLE mfa_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- Owner of the code
+    code_hash VARCHAR(64) NOT NULL,                                   -- SHA-256 hex digest of the code
+    used_at TIMESTAMPTZ,                                              -- Set when the code was redeemed
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,        -- When the code was issued
+    UNIQUE (user_id, code_hash)
+);
@@ -0,0 +1,22 @@
+-- WebAuthn credentials (passkeys and security keys) and pending ceremony challenges
+CREATE TABLE webauthn_credentials (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- Owner of the credential
+    credential_id BYTEA NOT NULL UNIQUE,                              -- Credential ID chosen by the authenticator
+    public_key BYTEA NOT NULL,                                        -- COSE encoded public key
+    sign_count BIGINT NOT NULL DEFAULT 0,                             -- Last signature counter seen
+    name VARCHAR(100) NOT NULL,                                       -- Label chosen by the user
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,        -- When the credential was registered
+    last_used_at TIMESTAMPTZ                                          -- Last successful authentication
+);
+
+CREATE INDEX webauthn_credentials_user_id_idx ON webauthn_credentials (user_id);
+
+CREATE TABLE webauthn_challenges (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,             -- Expected user, NULL for passkey sign-in
+    ceremony VARCHAR(20) NOT NULL,                                    -- 'registration' or 'authentication'
+    challenge BYTEA NOT NULL,                                         -- Random bytes the authenticator signs
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- Challenge is rejected after this time
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the ceremony started
+);
@@ -0,0 +1,14 @@
+-- Short-lived codes and magic links for passwordless login
+CREATE TABLE one_time_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- User the code signs in
+    purpose VARCHAR(20) NOT NULL,                                     -- 'email_code', 'magic_link', ...
+    code_hash VARCHAR(64) NOT NULL,                                   -- Keyed SHA-256 of the code or link secret
+    binding_hash VARCHAR(64) NOT NULL,                                -- Keyed SHA-256 of the browser's nonce
+    attempts INTEGER NOT NULL DEFAULT 0,                              -- Verification attempts so far
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- Code is rejected after this time
+    consumed_at TIMESTAMPTZ,                                          -- Set once the code was used
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the code was issued
+);
+
+CREATE INDEX one_time_codes_user_id_idx ON one_time_codes (user_id, purpose);
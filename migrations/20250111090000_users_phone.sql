@@ -0,0 +1,4 @@
+-- Phone numbers for SMS one-time codes
+ALTER TABLE users
+    ADD COLUMN phone_number VARCHAR(16),                -- E.164 formatted, e.g. +14155550123
+    ADD COLUMN phone_verified_at TIMESTAMPTZ;           -- Set once a code sent to the number was entered
@@ -0,0 +1,16 @@
+-- Server-side sessions for browser clients that authenticate with a cookie
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- User the session belongs to
+    token_hash VARCHAR(64) NOT NULL UNIQUE,                           -- SHA-256 of the cookie value
+    csrf_token VARCHAR(64) NOT NULL,                                  -- Expected in X-CSRF-Token on unsafe requests
+    roles VARCHAR(50)[] NOT NULL DEFAULT '{}',                        -- Roles granted at sign-in
+    auth_time TIMESTAMPTZ NOT NULL,                                   -- When the user authenticated
+    amr VARCHAR(10)[] NOT NULL DEFAULT '{}',                          -- Authentication methods used
+    acr VARCHAR(10) NOT NULL,                                         -- Authentication context class level
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,        -- When the session was created
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,      -- Last request, drives the idle timeout
+    expires_at TIMESTAMPTZ NOT NULL                                   -- Absolute expiry, regardless of activity
+);
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
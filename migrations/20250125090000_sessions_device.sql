@@ -0,0 +1,6 @@
+-- Device details shown to users when they review where they are signed in
+ALTER TABLE sessions
+    ADD COLUMN user_agent TEXT,                                       -- Raw User-Agent header at sign-in
+    ADD COLUMN browser VARCHAR(100),                                  -- Browser name and version parsed from user_agent
+    ADD COLUMN os VARCHAR(100),                                       -- Operating system parsed from user_agent
+    ADD COLUMN ip_address VARCHAR(45);                                -- Client IP at sign-in
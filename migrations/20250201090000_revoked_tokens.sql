@@ -0,0 +1,9 @@
+-- Ids of self-contained tokens revoked before they expire
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY,                                             -- Token id from the jti claim
+    user_id UUID,                                                     -- Subject of the token, when it had one
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- Token expiry; the entry is dropped after it
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the token was revoked
+);
+
+CREATE INDEX revoked_tokens_expires_at_idx ON revoked_tokens (expires_at);
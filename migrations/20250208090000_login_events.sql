@@ -0,0 +1,15 @@
+-- History of sign-in attempts, shown to users as their security event feed
+CREATE TABLE login_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,             -- Account signed in to; NULL for unknown usernames
+    success BOOLEAN NOT NULL,                                         -- Whether the sign-in completed
+    method VARCHAR(10) NOT NULL,                                      -- First factor as an amr value ('pwd', 'hwk', 'email')
+    mfa_method VARCHAR(10),                                           -- Second factor as an amr value, when one was used
+    failure_reason VARCHAR(30),                                       -- Why a failed attempt was refused
+    ip_address VARCHAR(45),                                           -- Client IP
+    user_agent TEXT,                                                  -- Raw User-Agent header
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the attempt happened
+);
+
+CREATE INDEX login_events_user_id_idx ON login_events (user_id, created_at DESC);
+CREATE INDEX login_events_created_at_idx ON login_events (created_at);
@@ -0,0 +1,4 @@
+-- Device and location of each sign-in, compared against earlier ones to spot unusual logins
+ALTER TABLE login_events
+    ADD COLUMN device_hash VARCHAR(64),                               -- SHA-256 hex of the device cookie
+    ADD COLUMN country VARCHAR(2);                                    -- ISO 3166-1 alpha-2 code looked up from ip_address
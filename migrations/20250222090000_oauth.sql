@@ -0,0 +1,24 @@
+-- Applications that send users to the OAuth authorization endpoint
+CREATE TABLE oauth_clients (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, doubles as the client_id
+    name VARCHAR(100) NOT NULL,                                       -- Shown to users on the sign-in page
+    redirect_uris TEXT[] NOT NULL DEFAULT '{}',                       -- Redirect URIs accepted by exact match
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the client was registered
+);
+
+-- Authorization codes waiting to be exchanged at the token endpoint
+CREATE TABLE oauth_authorization_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    code_hash VARCHAR(64) NOT NULL UNIQUE,                            -- SHA-256 of the code handed to the client
+    client_id UUID NOT NULL REFERENCES oauth_clients (id) ON DELETE CASCADE, -- Client the code was issued to
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- User who signed in
+    redirect_uri TEXT NOT NULL,                                       -- Has to be presented again with the code
+    scope TEXT NOT NULL DEFAULT '',                                   -- Space-separated scopes requested
+    code_challenge VARCHAR(128) NOT NULL,                             -- PKCE S256 challenge
+    auth_time TIMESTAMPTZ NOT NULL,                                   -- When the user authenticated
+    amr VARCHAR(10)[] NOT NULL DEFAULT '{}',                          -- Authentication methods used
+    acr VARCHAR(10) NOT NULL,                                         -- Authentication context class level
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- Codes are short-lived
+    consumed_at TIMESTAMPTZ,                                          -- Set once the code has been exchanged
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the code was issued
+);
@@ -0,0 +1,11 @@
+-- Registration details managed through the admin client API
+ALTER TABLE oauth_clients
+    ADD COLUMN client_type VARCHAR(12) NOT NULL DEFAULT 'public',     -- public or confidential
+    ADD COLUMN secret_hash VARCHAR(64),                               -- SHA-256 of the current secret, confidential clients only
+    ADD COLUMN previous_secret_hash VARCHAR(64),                      -- Secret replaced by the last rotation
+    ADD COLUMN previous_secret_expires_at TIMESTAMPTZ,                -- Until when the previous secret is still accepted
+    ADD COLUMN grant_types TEXT[] NOT NULL DEFAULT '{authorization_code}', -- Grant types the client may use
+    ADD COLUMN scopes TEXT[] NOT NULL DEFAULT '{}',                   -- Scopes the client may request
+    ADD COLUMN access_ttl INTEGER,                                    -- Access token lifetime in seconds, NULL for the default
+    ADD COLUMN refresh_ttl INTEGER,                                   -- Refresh token lifetime in seconds, NULL for the default
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP; -- When the registration last changed
@@ -0,0 +1,11 @@
+-- Keys for private_key_jwt client authentication
+ALTER TABLE oauth_clients
+    ADD COLUMN jwks JSONB;                                            -- JWK Set with the public keys client assertions are signed with
+
+-- Client assertions already presented, so none can be used twice
+CREATE TABLE oauth_client_assertions (
+    client_id UUID NOT NULL REFERENCES oauth_clients (id) ON DELETE CASCADE, -- Client that signed the assertion
+    jti VARCHAR(255) NOT NULL,                                        -- Assertion id from the jti claim
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- Assertion expiry; the entry is dropped after it
+    PRIMARY KEY (client_id, jti)
+);
@@ -0,0 +1,18 @@
+-- Device authorization requests (RFC 8628) waiting for the user to approve them
+CREATE TABLE oauth_device_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    device_code_hash VARCHAR(64) NOT NULL UNIQUE,                     -- SHA-256 of the code the device polls with
+    user_code_hash VARCHAR(64) NOT NULL UNIQUE,                       -- SHA-256 of the normalised code the user types in
+    client_id UUID NOT NULL REFERENCES oauth_clients (id) ON DELETE CASCADE, -- Client that started the request
+    scope TEXT NOT NULL DEFAULT '',                                   -- Space-separated scopes requested
+    status VARCHAR(10) NOT NULL DEFAULT 'pending',                    -- pending, approved or denied
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,             -- User who approved or denied the request
+    auth_time TIMESTAMPTZ,                                            -- When that user authenticated
+    amr VARCHAR(10)[] NOT NULL DEFAULT '{}',                          -- Authentication methods used
+    acr VARCHAR(10),                                                  -- Authentication context class level
+    poll_interval INTEGER NOT NULL,                                   -- Seconds the device has to wait between polls
+    last_polled_at TIMESTAMPTZ,                                       -- When the device last polled the token endpoint
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- Device codes are short-lived
+    consumed_at TIMESTAMPTZ,                                          -- Set once a token has been issued
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the request was started
+);
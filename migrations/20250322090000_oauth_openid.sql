@@ -0,0 +1,3 @@
+-- OpenID Connect: the nonce is echoed back in the ID token
+ALTER TABLE oauth_authorization_codes
+    ADD COLUMN nonce TEXT;                                            -- Nonce sent with the authorization request, if any
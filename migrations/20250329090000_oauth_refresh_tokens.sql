@@ -0,0 +1,17 @@
+-- Refresh tokens issued to OAuth clients, rotated on every use
+CREATE TABLE oauth_refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    token_hash VARCHAR(64) NOT NULL UNIQUE,                           -- SHA-256 of the token handed to the client
+    family_id UUID NOT NULL,                                          -- Shared by every token rotated from the same grant
+    client_id UUID NOT NULL REFERENCES oauth_clients (id) ON DELETE CASCADE, -- Client the token was issued to
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- User who granted access
+    scope TEXT NOT NULL DEFAULT '',                                   -- Space-separated scopes granted
+    auth_time TIMESTAMPTZ NOT NULL,                                   -- When the user authenticated
+    amr VARCHAR(10)[] NOT NULL DEFAULT '{}',                          -- Authentication methods used
+    acr VARCHAR(10) NOT NULL,                                         -- Authentication context class level
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- When the token stops working
+    revoked_at TIMESTAMPTZ,                                           -- Set when the token is rotated or revoked
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the token was issued
+);
+
+CREATE INDEX oauth_refresh_tokens_family_id_idx ON oauth_refresh_tokens (family_id);
@@ -0,0 +1,13 @@
+-- Clients run by the service itself are trusted without asking the user
+ALTER TABLE oauth_clients
+    ADD COLUMN first_party BOOLEAN NOT NULL DEFAULT false;            -- Skips the consent screen
+
+-- Scopes each user has agreed to share with each client
+CREATE TABLE oauth_grants (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,    -- User who consented
+    client_id UUID NOT NULL REFERENCES oauth_clients (id) ON DELETE CASCADE, -- Client the consent was given to
+    scopes TEXT[] NOT NULL DEFAULT '{}',                              -- Scopes agreed to so far
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,        -- When the user first consented
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,        -- When the grant last widened
+    PRIMARY KEY (user_id, client_id)
+);
@@ -0,0 +1,3 @@
+-- Resource servers a client may address tokens to through token exchange
+ALTER TABLE oauth_clients
+    ADD COLUMN audiences TEXT[] NOT NULL DEFAULT '{}';                -- Allowed `audience` values
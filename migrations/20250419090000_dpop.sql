@@ -0,0 +1,11 @@
+-- Binds refresh tokens of public clients to the DPoP key they were issued to
+ALTER TABLE oauth_refresh_tokens
+    ADD COLUMN jkt VARCHAR(43);                                       -- JWK SHA-256 thumbprint, NULL if unbound
+
+-- DPoP proofs seen within their acceptance window, to reject replays
+CREATE TABLE dpop_proofs (
+    jkt VARCHAR(43) NOT NULL,                                         -- Thumbprint of the proof key
+    jti VARCHAR(255) NOT NULL,                                        -- Proof identifier
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- When the proof can no longer be replayed
+    PRIMARY KEY (jkt, jti)
+);
@@ -0,0 +1,15 @@
+-- Clients can be held to pushed and signed authorization requests
+ALTER TABLE oauth_clients
+    ADD COLUMN require_pushed_authorization_requests BOOLEAN NOT NULL DEFAULT false, -- Only accepts requests pushed to /oauth/par
+    ADD COLUMN require_signed_request_object BOOLEAN NOT NULL DEFAULT false;         -- Only accepts JWT-secured requests
+
+-- Authorization requests pushed by clients ahead of the browser redirect
+CREATE TABLE oauth_pushed_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),                   -- UUID as primary key, auto-generated
+    request_uri_hash VARCHAR(64) NOT NULL UNIQUE,                     -- SHA-256 of the request_uri handed to the client
+    client_id UUID NOT NULL REFERENCES oauth_clients (id) ON DELETE CASCADE, -- Client that pushed the request
+    parameters JSONB NOT NULL,                                        -- The validated authorization request
+    expires_at TIMESTAMPTZ NOT NULL,                                  -- When the request_uri stops working
+    consumed_at TIMESTAMPTZ,                                          -- Set once a code was issued for it
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP         -- When the request was pushed
+);
@@ -0,0 +1,5 @@
+-- Tokens issued for a code, so a replayed code can revoke them
+ALTER TABLE oauth_authorization_codes
+    ADD COLUMN family_id UUID,                                        -- Refresh token family started by the exchange
+    ADD COLUMN access_jti UUID,                                       -- Id of the access token issued for the code
+    ADD COLUMN access_expires_at TIMESTAMPTZ;                         -- When that access token expires
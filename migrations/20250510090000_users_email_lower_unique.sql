@@ -0,0 +1,2 @@
+-- Emails are looked up case-insensitively, so they have to be unique that way too
+CREATE UNIQUE INDEX users_email_lower_idx ON users (LOWER(email));
@@ -0,0 +1,324 @@
+use actix_web::{
+    http::header::{self, ContentType},
+    web, HttpResponse,
+};
+
+use crate::{
+    api::session::{issued_response, removal_cookie, PostgresSessionUseCase},
+    application::use_cases::{
+        auth::{AuthError, AuthUseCase, LoginOutcome},
+        login_risk::LoginRiskUseCase,
+        revocation::RevocationUseCase,
+    },
+    domain::{
+        client::ClientContext, mfa::MfaCode, principal::Principal, webauthn::WebauthnAssertion,
+    },
+    dto::{
+        auth_dto::{LoginRequest, MfaChallengeResponse, MfaVerifyRequest},
+        error::ErrorResponse,
+        mfa_dto::{MfaEmailRequest, MfaEmailResponse, MfaSmsRequest, MfaSmsResponse},
+        session_dto::{LoginMode, LoginQuery},
+        webauthn_dto::{AssertionRequest, MfaWebauthnOptionsRequest, PasskeyOptionsRequest},
+    },
+    infrastructure::repositories::{
+        postgres_lockout_repo::PostgresLockoutRepository,
+        postgres_login_event_repo::PostgresLoginEventRepository,
+        postgres_mfa_repo::PostgresMfaRepository,
+        postgres_one_time_code_repo::PostgresOneTimeCodeRepository,
+        postgres_revoked_token_repo::PostgresRevokedTokenRepository,
+        postgres_user_repo::PostgresUserRepository,
+        postgres_webauthn_repo::PostgresWebauthnRepository,
+    },
+    util::phone::mask_phone_number,
+};
+
+pub type PostgresAuthUseCase = AuthUseCase<
+    PostgresUserRepository,
+    PostgresLockoutRepository,
+    PostgresMfaRepository,
+    PostgresWebauthnRepository,
+    PostgresOneTimeCodeRepository,
+    PostgresRevokedTokenRepository,
+    PostgresLoginEventRepository,
+>;
+
+pub type PostgresRevocationUseCase = RevocationUseCase<PostgresRevokedTokenRepository>;
+
+pub type PostgresLoginRiskUseCase = LoginRiskUseCase<PostgresLoginEventRepository>;
+
+pub fn auth_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/login").route(web::post().to(login)));
+    cfg.service(web::resource("/logout").route(web::post().to(logout)));
+    cfg.service(web::resource("/mfa").route(web::post().to(verify_mfa)));
+    cfg.service(web::resource("/mfa/sms").route(web::post().to(send_mfa_sms)));
+    cfg.service(web::resource("/mfa/email").route(web::post().to(send_mfa_email)));
+    cfg.service(web::resource("/mfa/webauthn/options").route(web::post().to(mfa_webauthn_options)));
+    cfg.service(web::resource("/webauthn").route(web::post().to(login_with_passkey)));
+    cfg.service(web::resource("/webauthn/options").route(web::post().to(passkey_options)));
+}
+
+fn malformed_assertion() -> HttpResponse {
+    HttpResponse::BadRequest()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Malformed WebAuthn Assertion".to_string(),
+        })
+}
+
+async fn login(
+    use_case: web::Data<PostgresAuthUseCase>,
+    sessions: web::Data<PostgresSessionUseCase>,
+    client: ClientContext,
+    query: web::Query<LoginQuery>,
+    req_body: web::Json<LoginRequest>,
+) -> HttpResponse {
+    let credentials = req_body.into_inner();
+
+    match use_case.get_ref().login(credentials, client.clone()).await {
+        Ok(outcome) => {
+            login_outcome_response(
+                use_case.get_ref(),
+                sessions.get_ref(),
+                query.mode,
+                &client,
+                outcome,
+            )
+            .await
+        }
+        Err(err) => auth_error_response(err),
+    }
+}
+
+/// Signs the caller out. A bearer token is put on the denylist until it
+/// expires; a cookie session is ended and its cookie cleared.
+async fn logout(
+    revocation: web::Data<PostgresRevocationUseCase>,
+    sessions: web::Data<PostgresSessionUseCase>,
+    principal: Principal,
+) -> HttpResponse {
+    let result = if principal.session {
+        sessions
+            .get_ref()
+            .revoke(principal.user_id, principal.token_id)
+            .await
+            .map(|_| ())
+    } else {
+        revocation
+            .get_ref()
+            .revoke(
+                principal.token_id,
+                Some(principal.user_id),
+                principal.expires_at,
+            )
+            .await
+    };
+
+    match result {
+        Ok(()) => {
+            let mut response = HttpResponse::NoContent().finish();
+            if principal.session {
+                let _ = response.add_removal_cookie(&removal_cookie(sessions.get_ref().config()));
+            }
+            response
+        }
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+/// The MFA challenge does not depend on `mode`; the client passes it again
+/// when completing the second factor.
+pub async fn login_outcome_response(
+    use_case: &PostgresAuthUseCase,
+    sessions: &PostgresSessionUseCase,
+    mode: LoginMode,
+    client: &ClientContext,
+    outcome: LoginOutcome,
+) -> HttpResponse {
+    match outcome {
+        LoginOutcome::Authenticated(issued) => {
+            issued_response(sessions, *issued, mode, client).await
+        }
+        LoginOutcome::MfaRequired(mfa_token) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(MfaChallengeResponse {
+                mfa_required: true,
+                mfa_token,
+                expires_in: use_case.mfa_challenge_ttl(),
+                verify_email: false,
+            }),
+        LoginOutcome::VerificationRequired(mfa_token) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(MfaChallengeResponse {
+                mfa_required: true,
+                mfa_token,
+                expires_in: use_case.mfa_challenge_ttl(),
+                verify_email: true,
+            }),
+    }
+}
+
+async fn verify_mfa(
+    use_case: web::Data<PostgresAuthUseCase>,
+    sessions: web::Data<PostgresSessionUseCase>,
+    client: ClientContext,
+    query: web::Query<LoginQuery>,
+    req_body: web::Json<MfaVerifyRequest>,
+) -> HttpResponse {
+    let MfaVerifyRequest {
+        mfa_token,
+        code,
+        recovery_code,
+        webauthn,
+        sms_code,
+        email_code,
+    } = req_body.into_inner();
+    let code = match (code, recovery_code, webauthn, sms_code, email_code) {
+        (Some(code), None, None, None, None) => MfaCode::Totp(code),
+        (None, Some(recovery_code), None, None, None) => MfaCode::Recovery(recovery_code),
+        (None, None, Some(assertion), None, None) => match WebauthnAssertion::try_from(assertion) {
+            Ok(assertion) => MfaCode::WebAuthn(assertion),
+            Err(_) => return malformed_assertion(),
+        },
+        (None, None, None, Some(sms_code), None) => MfaCode::Sms(sms_code),
+        (None, None, None, None, Some(email_code)) => MfaCode::Email(email_code),
+        _ => {
+            return HttpResponse::BadRequest()
+                .content_type(ContentType::json())
+                .json(ErrorResponse {
+                    message: "Provide One Of code, recovery_code, webauthn, sms_code or email_code"
+                        .to_string(),
+                })
+        }
+    };
+
+    match use_case
+        .get_ref()
+        .verify_mfa(&mfa_token, code, client.clone())
+        .await
+    {
+        Ok(issued) => issued_response(sessions.get_ref(), issued, query.mode, &client).await,
+        Err(err) => auth_error_response(err),
+    }
+}
+
+async fn send_mfa_sms(
+    use_case: web::Data<PostgresAuthUseCase>,
+    req_body: web::Json<MfaSmsRequest>,
+) -> HttpResponse {
+    match use_case.get_ref().send_mfa_sms(&req_body.mfa_token).await {
+        Ok(phone_number) => HttpResponse::Accepted()
+            .content_type(ContentType::json())
+            .json(MfaSmsResponse {
+                sent_to: mask_phone_number(&phone_number),
+                expires_in: use_case.get_ref().sms_code_ttl(),
+            }),
+        Err(err) => auth_error_response(err),
+    }
+}
+
+async fn send_mfa_email(
+    use_case: web::Data<PostgresAuthUseCase>,
+    client: ClientContext,
+    req_body: web::Json<MfaEmailRequest>,
+) -> HttpResponse {
+    match use_case
+        .get_ref()
+        .send_mfa_email(&req_body.mfa_token, &client)
+        .await
+    {
+        Ok(()) => HttpResponse::Accepted()
+            .content_type(ContentType::json())
+            .json(MfaEmailResponse {
+                expires_in: use_case.get_ref().email_code_ttl(),
+            }),
+        Err(err) => auth_error_response(err),
+    }
+}
+
+async fn mfa_webauthn_options(
+    use_case: web::Data<PostgresAuthUseCase>,
+    req_body: web::Json<MfaWebauthnOptionsRequest>,
+) -> HttpResponse {
+    match use_case
+        .get_ref()
+        .mfa_webauthn_options(&req_body.mfa_token)
+        .await
+    {
+        Ok(options) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(options),
+        Err(err) => auth_error_response(err),
+    }
+}
+
+async fn passkey_options(
+    use_case: web::Data<PostgresAuthUseCase>,
+    req_body: web::Json<PasskeyOptionsRequest>,
+) -> HttpResponse {
+    match use_case
+        .get_ref()
+        .passkey_options(req_body.username.as_deref())
+        .await
+    {
+        Ok(options) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(options),
+        Err(err) => auth_error_response(err),
+    }
+}
+
+async fn login_with_passkey(
+    use_case: web::Data<PostgresAuthUseCase>,
+    sessions: web::Data<PostgresSessionUseCase>,
+    client: ClientContext,
+    query: web::Query<LoginQuery>,
+    req_body: web::Json<AssertionRequest>,
+) -> HttpResponse {
+    let Ok(assertion) = WebauthnAssertion::try_from(req_body.into_inner()) else {
+        return malformed_assertion();
+    };
+
+    match use_case
+        .get_ref()
+        .login_with_passkey(assertion, client.clone())
+        .await
+    {
+        Ok(issued) => issued_response(sessions.get_ref(), issued, query.mode, &client).await,
+        Err(err) => auth_error_response(err),
+    }
+}
+
+pub fn auth_error_response(err: AuthError) -> HttpResponse {
+    let mut response = match &err {
+        AuthError::InvalidCredentials | AuthError::InvalidMfa | AuthError::InvalidCode => {
+            HttpResponse::Unauthorized()
+        }
+        AuthError::PasswordResetRequired => HttpResponse::Forbidden(),
+        AuthError::Locked(until) => {
+            let retry_after = (*until - chrono::Utc::now()).num_seconds().max(1);
+            let mut response = HttpResponse::Locked();
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+            response
+        }
+        AuthError::Throttled(retry_after) => {
+            let mut response = HttpResponse::TooManyRequests();
+            response.insert_header((
+                header::RETRY_AFTER,
+                retry_after.as_secs().max(1).to_string(),
+            ));
+            response
+        }
+        AuthError::Unavailable(_) => HttpResponse::ServiceUnavailable(),
+        AuthError::Internal(_) => HttpResponse::InternalServerError(),
+    };
+
+    response
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: err.to_string(),
+        })
+}
@@ -0,0 +1,436 @@
+use actix_web::{
+    dev::Payload,
+    http::{
+        header::{self, HeaderValue},
+        Method, StatusCode,
+    },
+    web, FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError,
+};
+use std::{
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    api::{
+        auth::PostgresRevocationUseCase,
+        middleware::device::{is_device_id, NewDeviceId, DEVICE_COOKIE},
+        oauth::PostgresDpopUseCase,
+        session::{PostgresSessionUseCase, CSRF_HEADER},
+    },
+    application::use_cases::dpop::DpopError,
+    domain::{client::ClientContext, principal::Principal},
+    dto::error::ErrorResponse,
+    util::{
+        client_ip::TrustedProxies,
+        dpop::{DPOP_HEADER, DPOP_NONCE_HEADER, DPOP_SCHEME, DPOP_SIGNING_ALGS},
+        geoip::GeoIp,
+        token::TokenService,
+    },
+};
+
+/// Rejection returned by the authentication extractors.
+#[derive(Debug)]
+pub struct AuthRejection {
+    status: StatusCode,
+    message: &'static str,
+    /// Set when the caller has to retry with a DPoP proof: the `error` of
+    /// the DPoP challenge and a nonce to put in the proof.
+    dpop: Option<(&'static str, Option<String>)>,
+}
+
+impl AuthRejection {
+    pub fn unauthorized(message: &'static str) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            message,
+            dpop: None,
+        }
+    }
+
+    pub fn forbidden(message: &'static str) -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            message,
+            dpop: None,
+        }
+    }
+
+    pub fn unavailable(message: &'static str) -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message,
+            dpop: None,
+        }
+    }
+
+    pub fn dpop(error: &'static str, message: &'static str, nonce: Option<String>) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            message,
+            dpop: Some((error, nonce)),
+        }
+    }
+}
+
+impl fmt::Display for AuthRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl ResponseError for AuthRejection {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status);
+        match &self.dpop {
+            Some((error, nonce)) => {
+                response.insert_header((
+                    header::WWW_AUTHENTICATE,
+                    format!(
+                        "{} error=\"{}\", error_description=\"{}\", algs=\"{}\"",
+                        DPOP_SCHEME,
+                        error,
+                        self.message,
+                        DPOP_SIGNING_ALGS.join(" ")
+                    ),
+                ));
+                if let Some(nonce) = nonce {
+                    response.insert_header((DPOP_NONCE_HEADER, nonce.as_str()));
+                }
+            }
+            None if self.status == StatusCode::UNAUTHORIZED => {
+                response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+            }
+            None => {}
+        }
+        response.json(ErrorResponse {
+            message: self.message.to_string(),
+        })
+    }
+}
+
+/// The access token and whether it came with the DPoP scheme.
+fn access_token(req: &HttpRequest) -> Option<(&str, bool)> {
+    let authorization = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    match authorization.strip_prefix("Bearer ") {
+        Some(token) => Some((token.trim(), false)),
+        None => authorization
+            .strip_prefix("DPoP ")
+            .map(|token| (token.trim(), true)),
+    }
+}
+
+/// The DPoP proof sent with a request. A request may carry only one.
+pub fn dpop_proof(req: &HttpRequest) -> Result<Option<&str>, &'static str> {
+    let mut proofs = req.headers().get_all(DPOP_HEADER);
+    let proof = proofs.next();
+    if proofs.next().is_some() {
+        return Err("Only One DPoP Proof Is Allowed");
+    }
+    proof
+        .map(HeaderValue::to_str)
+        .transpose()
+        .map_err(|_| "Invalid DPoP Proof")
+}
+
+/// A token bound to a DPoP key has to come with the DPoP scheme and a
+/// proof signed by that key for this request; an unbound one must not.
+async fn check_proof_of_possession(
+    req: &HttpRequest,
+    token: &str,
+    jkt: Option<&str>,
+    dpop: bool,
+) -> Result<(), AuthRejection> {
+    let jkt = match (jkt, dpop) {
+        (None, false) => return Ok(()),
+        (None, true) => return Err(AuthRejection::unauthorized("Token Is Not DPoP-Bound")),
+        (Some(_), false) => {
+            return Err(AuthRejection::dpop(
+                "invalid_token",
+                "DPoP-Bound Token Requires A DPoP Proof",
+                None,
+            ))
+        }
+        (Some(jkt), true) => jkt,
+    };
+    let use_case = req
+        .app_data::<web::Data<PostgresDpopUseCase>>()
+        .ok_or(AuthRejection::unavailable("Authentication Unavailable"))?;
+    let proof = dpop_proof(req)
+        .map_err(|message| AuthRejection::dpop("invalid_dpop_proof", message, None))?
+        .ok_or(AuthRejection::dpop(
+            "invalid_dpop_proof",
+            "Missing DPoP Proof",
+            None,
+        ))?;
+
+    let url = {
+        let connection = req.connection_info();
+        format!(
+            "{}://{}{}",
+            connection.scheme(),
+            connection.host(),
+            req.path()
+        )
+    };
+    let proven = use_case
+        .verify(proof, req.method().as_str(), &url, Some(token))
+        .await
+        .map_err(|err| match err {
+            DpopError::InvalidProof(message) => {
+                AuthRejection::dpop("invalid_dpop_proof", message, None)
+            }
+            DpopError::UseNonce => AuthRejection::dpop(
+                "use_dpop_nonce",
+                "DPoP Nonce Required",
+                Some(use_case.nonce()),
+            ),
+            DpopError::Internal(err) => {
+                tracing::error!(error = %err, "failed to check DPoP proof");
+                AuthRejection::unavailable("Authentication Unavailable")
+            }
+        })?;
+    if proven != jkt {
+        return Err(AuthRejection::dpop(
+            "invalid_dpop_proof",
+            "DPoP Proof Signed With Another Key",
+            None,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Tokens on the denylist are rejected when revocation is configured.
+async fn authenticate_bearer(
+    req: &HttpRequest,
+    token: &str,
+    dpop: bool,
+) -> Result<Principal, AuthRejection> {
+    let tokens = req
+        .app_data::<web::Data<TokenService>>()
+        .ok_or(AuthRejection::unauthorized("Authentication Unavailable"))?;
+
+    let claims = tokens
+        .verify_access_token(token)
+        .map_err(|_| AuthRejection::unauthorized("Invalid Bearer Token"))?;
+    check_proof_of_possession(req, token, claims.jkt(), dpop).await?;
+    let principal = Principal::from(claims);
+
+    if let Some(revocation) = req.app_data::<web::Data<PostgresRevocationUseCase>>() {
+        let revoked = revocation
+            .is_revoked(principal.token_id, principal.expires_at)
+            .await
+            .map_err(|err| {
+                tracing::error!(error = %err, "failed to check token revocation");
+                AuthRejection::unavailable("Authentication Unavailable")
+            })?;
+        if revoked {
+            return Err(AuthRejection::unauthorized("Token Has Been Revoked"));
+        }
+    }
+
+    Ok(principal)
+}
+
+/// Cookies are sent by the browser on cross-site requests too, so anything
+/// other than a read has to echo the session's CSRF token in a header.
+fn csrf_token_matches(req: &HttpRequest, expected: &str) -> bool {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+
+    req.headers()
+        .get(CSRF_HEADER)
+        .is_some_and(|value| bool::from(value.as_bytes().ct_eq(expected.as_bytes())))
+}
+
+async fn authenticate_session(
+    req: &HttpRequest,
+    sessions: &PostgresSessionUseCase,
+    token: &str,
+) -> Result<Principal, AuthRejection> {
+    let session = sessions
+        .authenticate(token)
+        .await
+        .map_err(|err| {
+            tracing::error!(error = %err, "failed to look up session");
+            AuthRejection::unavailable("Authentication Unavailable")
+        })?
+        .ok_or(AuthRejection::unauthorized("Invalid Session"))?;
+
+    if !csrf_token_matches(req, &session.csrf_token) {
+        return Err(AuthRejection::forbidden("Invalid CSRF Token"));
+    }
+
+    Ok(Principal {
+        user_id: session.user_id,
+        token_id: session.id,
+        expires_at: session.valid_until(sessions.config().idle).timestamp(),
+        auth: session.auth_context(),
+        roles: session.roles,
+        session: true,
+        client_id: None,
+        scope: None,
+    })
+}
+
+/// An access token takes precedence. Without one, the session cookie is
+/// used when sessions are configured. Tokens issued to OAuth clients are
+/// refused: they only reach resources that check their scopes, through
+/// [`OAuthPrincipal`].
+async fn authenticate(req: HttpRequest) -> Result<Principal, AuthRejection> {
+    if let Some((token, dpop)) = access_token(&req) {
+        let principal = authenticate_bearer(&req, token, dpop).await?;
+        if principal.client_id.is_some() {
+            return Err(AuthRejection::forbidden(
+                "OAuth Client Tokens Are Not Accepted Here",
+            ));
+        }
+        return Ok(principal);
+    }
+
+    let sessions = req.app_data::<web::Data<PostgresSessionUseCase>>();
+    let cookie = sessions.and_then(|sessions| req.cookie(&sessions.config().name));
+    match (sessions, cookie) {
+        (Some(sessions), Some(cookie)) => {
+            authenticate_session(&req, sessions.get_ref(), cookie.value()).await
+        }
+        _ => Err(AuthRejection::unauthorized("Missing Bearer Token")),
+    }
+}
+
+impl FromRequest for Principal {
+    type Error = AuthRejection;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        Box::pin(authenticate(req.clone()))
+    }
+}
+
+/// A [`Principal`] that carries the admin role.
+pub struct AdminPrincipal(pub Principal);
+
+impl FromRequest for AdminPrincipal {
+    type Error = AuthRejection;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let principal = authenticate(req.clone());
+        Box::pin(async move {
+            let principal = principal.await?;
+            if principal.is_admin() {
+                Ok(AdminPrincipal(principal))
+            } else {
+                Err(AuthRejection::forbidden("Admin Role Required"))
+            }
+        })
+    }
+}
+
+/// The caller of an OAuth-protected resource: a user acting through a
+/// client that was issued an access token. The handler checks the scope it
+/// needs with [`OAuthPrincipal::scope`].
+pub struct OAuthPrincipal(pub Principal);
+
+impl OAuthPrincipal {
+    /// The granted scopes, when they include the one required.
+    pub fn scope(&self, required: &str) -> Option<&str> {
+        let OAuthPrincipal(principal) = self;
+        principal
+            .scope
+            .as_deref()
+            .filter(|_| principal.has_scope(required))
+    }
+}
+
+impl FromRequest for OAuthPrincipal {
+    type Error = AuthRejection;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            let (token, dpop) =
+                access_token(&req).ok_or(AuthRejection::unauthorized("Missing Bearer Token"))?;
+            let principal = authenticate_bearer(&req, token, dpop).await?;
+            if principal.client_id.is_none() {
+                return Err(AuthRejection::forbidden("OAuth Access Token Required"));
+            }
+            Ok(OAuthPrincipal(principal))
+        })
+    }
+}
+
+impl FromRequest for ClientContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let client_ip = match req.app_data::<web::Data<TrustedProxies>>() {
+            Some(proxies) => proxies.client_ip(req.peer_addr(), req.headers()),
+            None => req.peer_addr().map(|peer| peer.ip()),
+        };
+        // The extensions borrow has to end before `cookie`, which parses the
+        // cookies into the extensions on first use.
+        let new_device_id = req
+            .extensions()
+            .get::<NewDeviceId>()
+            .map(|NewDeviceId(device_id)| device_id.clone());
+        let device_id = new_device_id.or_else(|| {
+            req.cookie(DEVICE_COOKIE)
+                .map(|cookie| cookie.value().to_string())
+                .filter(|device_id| is_device_id(device_id))
+        });
+        // Looked up from the same trusted address the history records, so a
+        // forged header cannot make a sign-in look like it comes from home.
+        let country = req
+            .app_data::<web::Data<GeoIp>>()
+            .zip(client_ip)
+            .and_then(|(geoip, ip)| geoip.country(ip));
+
+        ready(Ok(ClientContext {
+            ip_address: client_ip.map(|ip| ip.to_string()),
+            user_agent,
+            device_id,
+            country,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    async fn client(peer: &str, forwarded_for: &str) -> ClientContext {
+        let req = TestRequest::default()
+            .peer_addr(peer.parse().unwrap())
+            .insert_header(("x-forwarded-for", forwarded_for))
+            .app_data(web::Data::new(TrustedProxies::parse("10.0.0.0/8").unwrap()))
+            .to_http_request();
+        ClientContext::extract(&req).await.unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_client_ip_only_trusts_forwarded_for_from_proxies() {
+        let spoofed = client("203.0.113.7:40000", "198.51.100.1").await;
+        assert_eq!(Some("203.0.113.7"), spoofed.ip_address.as_deref());
+
+        let proxied = client("10.0.0.2:40000", "198.51.100.1").await;
+        assert_eq!(Some("198.51.100.1"), proxied.ip_address.as_deref());
+    }
+}
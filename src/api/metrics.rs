@@ -0,0 +1,13 @@
+use actix_web::{http::header::ContentType, web, HttpResponse};
+
+use crate::util::pwd_pool::PwdPool;
+
+pub fn metrics_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(metrics_handler)));
+}
+
+async fn metrics_handler(pwd_pool: web::Data<PwdPool>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body(pwd_pool.metrics().render())
+}
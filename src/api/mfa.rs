@@ -0,0 +1,207 @@
+use actix_web::{http::header::ContentType, web, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    api::extractors::AdminPrincipal,
+    application::use_cases::mfa::{MfaError, MfaUseCase},
+    domain::principal::Principal,
+    dto::{
+        error::ErrorResponse,
+        mfa_dto::{MfaResetResponse, TotpConfirmRequest, TotpConfirmResponse, TotpEnrollResponse},
+    },
+    infrastructure::repositories::{
+        postgres_mfa_repo::PostgresMfaRepository,
+        postgres_one_time_code_repo::PostgresOneTimeCodeRepository,
+        postgres_user_repo::PostgresUserRepository,
+        postgres_webauthn_repo::PostgresWebauthnRepository,
+    },
+};
+
+pub type PostgresMfaUseCase = MfaUseCase<
+    PostgresUserRepository,
+    PostgresMfaRepository,
+    PostgresWebauthnRepository,
+    PostgresOneTimeCodeRepository,
+>;
+
+pub fn mfa_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/{user_id}/mfa").route(web::delete().to(reset)));
+    cfg.service(web::resource("/{user_id}/mfa/totp").route(web::post().to(enroll)));
+    cfg.service(web::resource("/{user_id}/mfa/totp/confirm").route(web::post().to(confirm)));
+}
+
+fn mfa_error_response(err: MfaError) -> HttpResponse {
+    let mut response = match &err {
+        MfaError::UserNotFound | MfaError::NotEnrolled => HttpResponse::NotFound(),
+        MfaError::AlreadyEnrolled => HttpResponse::Conflict(),
+        MfaError::InvalidCode => HttpResponse::BadRequest(),
+        MfaError::Internal(_) => HttpResponse::InternalServerError(),
+    };
+
+    response
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: err.to_string(),
+        })
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "MFA Can Only Be Enrolled By The Account Owner".to_string(),
+        })
+}
+
+async fn enroll(
+    use_case: web::Data<PostgresMfaUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case.get_ref().enroll(user_id).await {
+        Ok(enrollment) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(TotpEnrollResponse::from(enrollment)),
+        Err(err) => mfa_error_response(err),
+    }
+}
+
+async fn confirm(
+    use_case: web::Data<PostgresMfaUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+    req_body: web::Json<TotpConfirmRequest>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case.get_ref().confirm(user_id, &req_body.code).await {
+        Ok(recovery_codes) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(TotpConfirmResponse { recovery_codes }),
+        Err(err) => mfa_error_response(err),
+    }
+}
+
+async fn reset(
+    use_case: web::Data<PostgresMfaUseCase>,
+    admin: AdminPrincipal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    let AdminPrincipal(admin) = admin;
+
+    match use_case.get_ref().reset(user_id, &admin).await {
+        Ok(true) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(MfaResetResponse {
+                message: "MFA Reset Successfully".to_string(),
+            }),
+        Ok(false) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "MFA Not Enabled".to_string(),
+            }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{
+        application::use_cases::{phone::PhoneUseCase, webauthn::WebauthnUseCase},
+        config::{MfaConfig, SmsConfig, TokenConfig, WebauthnConfig},
+        domain::auth_context::{AuthContext, AMR_PASSWORD},
+        infrastructure::sms_senders::log_sms_sender::LogSmsSender,
+        util::{throttle::Throttle, token::TokenService},
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{call_service, init_service, read_body_json, TestRequest},
+        App,
+    };
+    use sqlx::PgPool;
+
+    #[actix_web::test]
+    async fn test_oauth_client_tokens_are_refused() {
+        let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let pool = PgPool::connect_lazy(&database_url).unwrap();
+        let webauthn = Arc::new(WebauthnUseCase::new(
+            PostgresUserRepository::new(pool.clone()),
+            PostgresWebauthnRepository::new(pool.clone()),
+            WebauthnConfig::default(),
+        ));
+        let phone = Arc::new(PhoneUseCase::new(
+            PostgresUserRepository::new(pool.clone()),
+            PostgresOneTimeCodeRepository::new(pool.clone()),
+            Arc::new(LogSmsSender::default()),
+            Arc::new(Throttle::new(5, Duration::from_secs(3600))),
+            SmsConfig::default(),
+        ));
+        let use_case = web::Data::new(MfaUseCase::new(
+            PostgresUserRepository::new(pool.clone()),
+            PostgresMfaRepository::new(pool),
+            webauthn,
+            phone,
+            MfaConfig::default(),
+        ));
+        let tokens = web::Data::new(TokenService::new(TokenConfig::default()));
+        let app = init_service(
+            App::new()
+                .app_data(tokens.clone())
+                .app_data(use_case)
+                .service(web::scope("/users").configure(mfa_cfg)),
+        )
+        .await;
+
+        let user_id = Uuid::new_v4();
+        let token = tokens
+            .issue_oauth_access_token(
+                user_id,
+                AuthContext::new(&[AMR_PASSWORD]),
+                Uuid::new_v4(),
+                Some("openid".to_string()),
+                300,
+                None,
+            )
+            .unwrap()
+            .token;
+        let bearer = (header::AUTHORIZATION, format!("Bearer {}", token));
+
+        let res = call_service(
+            &app,
+            TestRequest::post()
+                .uri(&format!("/users/{}/mfa/totp", user_id))
+                .insert_header(bearer.clone())
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let body: ErrorResponse = read_body_json(res).await;
+        assert_eq!("OAuth Client Tokens Are Not Accepted Here", body.message);
+
+        let res = call_service(
+            &app,
+            TestRequest::delete()
+                .uri(&format!("/users/{}/mfa", user_id))
+                .insert_header(bearer)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    cookie::{time::Duration, Cookie, SameSite},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    HttpMessage,
+};
+
+use crate::util::otp::generate_secret;
+
+/// Long-lived cookie that recognises a browser across sign-ins.
+pub const DEVICE_COOKIE: &str = "device_id";
+const DEVICE_COOKIE_MAX_AGE: Duration = Duration::days(400);
+
+/// Device id handed out during the current request, before the browser has
+/// the cookie.
+#[derive(Clone)]
+pub struct NewDeviceId(pub String);
+
+/// Accepts only values shaped like the ids handed out here, so arbitrary
+/// cookie contents never reach the login history.
+pub fn is_device_id(value: &str) -> bool {
+    value.len() == 43
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+/// Makes sure every browser that reaches a scope carries a device cookie.
+/// Requests without one get a fresh id, visible to the `ClientContext`
+/// extractor straight away and set as a cookie on the response.
+pub struct DeviceCookie {
+    secure: bool,
+}
+
+impl DeviceCookie {
+    pub fn new(secure: bool) -> Self {
+        Self { secure }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for DeviceCookie
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = DeviceCookieService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(DeviceCookieService {
+            service: Rc::new(service),
+            secure: self.secure,
+        }))
+    }
+}
+
+pub struct DeviceCookieService<S> {
+    service: Rc<S>,
+    secure: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for DeviceCookieService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let secure = self.secure;
+
+        Box::pin(async move {
+            let known = req
+                .cookie(DEVICE_COOKIE)
+                .is_some_and(|cookie| is_device_id(cookie.value()));
+            if known {
+                return service.call(req).await;
+            }
+
+            let device_id = generate_secret();
+            req.extensions_mut().insert(NewDeviceId(device_id.clone()));
+
+            let mut res = service.call(req).await?;
+            let cookie = Cookie::build(DEVICE_COOKIE, device_id)
+                .path("/")
+                .http_only(true)
+                .secure(secure)
+                .same_site(SameSite::Lax)
+                .max_age(DEVICE_COOKIE_MAX_AGE)
+                .finish();
+            res.response_mut().add_cookie(&cookie)?;
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpRequest, HttpResponse,
+    };
+
+    #[actix_web::test]
+    async fn test_hands_out_a_device_cookie_once() {
+        let app = init_service(App::new().wrap(DeviceCookie::new(true)).route(
+            "/",
+            web::get().to(|req: HttpRequest| async move {
+                let new_device = req.extensions().get::<NewDeviceId>().is_some();
+                HttpResponse::Ok().body(new_device.to_string())
+            }),
+        ))
+        .await;
+
+        let res = call_service(&app, TestRequest::get().uri("/").to_request()).await;
+        let cookie = res
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == DEVICE_COOKIE)
+            .unwrap()
+            .into_owned();
+        assert!(is_device_id(cookie.value()));
+        assert!(cookie.http_only().unwrap_or(false));
+
+        let res = call_service(
+            &app,
+            TestRequest::get().uri("/").cookie(cookie).to_request(),
+        )
+        .await;
+        assert_eq!(0, res.response().cookies().count());
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!("false", body);
+    }
+}
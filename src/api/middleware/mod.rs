@@ -0,0 +1,3 @@
+pub mod device;
+pub mod rate_limit;
+pub mod step_up;
@@ -0,0 +1,378 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderMap, HeaderName, HeaderValue},
+        Method,
+    },
+    HttpResponse,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    application::repositories::rate_limit_store::RateLimitStore,
+    config::RateLimitConfig,
+    domain::rate_limit::{RateLimit, RateLimitDecision},
+    dto::error::ErrorResponse,
+    infrastructure::repositories::memory_rate_limit_store::MemoryRateLimitStore,
+    util::{client_ip::TrustedProxies, token::TokenService},
+};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+struct RouteLimit {
+    method: Option<Method>,
+    pattern: String,
+    limit: RateLimit,
+}
+
+impl RouteLimit {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let (route, limit) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("expected METHOD /path=capacity/period, got {:?}", spec))?;
+        let (method, pattern) = route
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("expected METHOD /path, got {:?}", route))?;
+        let method = match method {
+            "*" => None,
+            method => Some(
+                Method::from_bytes(method.as_bytes())
+                    .map_err(|_| format!("invalid method {:?}", method))?,
+            ),
+        };
+
+        Ok(Self {
+            method,
+            pattern: pattern.trim().to_string(),
+            limit: limit.parse()?,
+        })
+    }
+
+    fn matches(&self, method: &Method, pattern: &str) -> bool {
+        self.method.as_ref().is_none_or(|m| m == method) && self.pattern == pattern
+    }
+}
+
+/// Decides which bucket a request draws from and asks the store for a token.
+pub struct RateLimiter {
+    enabled: bool,
+    store: Arc<dyn RateLimitStore + Send + Sync>,
+    default_limit: RateLimit,
+    routes: Vec<RouteLimit>,
+    api_keys: HashSet<String>,
+    tokens: Arc<TokenService>,
+    proxies: Arc<TrustedProxies>,
+}
+
+impl RateLimiter {
+    pub fn new(
+        config: &RateLimitConfig,
+        store: Arc<dyn RateLimitStore + Send + Sync>,
+        tokens: Arc<TokenService>,
+        proxies: Arc<TrustedProxies>,
+    ) -> Result<Self, String> {
+        let routes = config
+            .routes
+            .split(';')
+            .filter(|spec| !spec.trim().is_empty())
+            .map(RouteLimit::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        let api_keys = config
+            .keys
+            .split(',')
+            .map(|key| key.trim().to_lowercase())
+            .filter(|key| !key.is_empty())
+            .collect();
+
+        Ok(Self {
+            enabled: config.enabled,
+            store,
+            default_limit: RateLimit {
+                capacity: config.capacity,
+                period: config.period,
+            },
+            routes,
+            api_keys,
+            tokens,
+            proxies,
+        })
+    }
+
+    /// Builds the limiter with the store selected by `config.backend`.
+    pub async fn from_config(
+        config: &RateLimitConfig,
+        tokens: Arc<TokenService>,
+        proxies: Arc<TrustedProxies>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let store: Arc<dyn RateLimitStore + Send + Sync> = match config.backend.as_str() {
+            "memory" => Arc::new(MemoryRateLimitStore::new()),
+            #[cfg(feature = "redis")]
+            "redis" => Arc::new(
+                crate::infrastructure::repositories::redis_rate_limit_store::RedisRateLimitStore::new(
+                    &config.url,
+                )
+                .await?,
+            ),
+            backend => return Err(format!("unsupported rate limit backend {:?}", backend).into()),
+        };
+
+        Ok(Self::new(config, store, tokens, proxies)?)
+    }
+
+    fn limit_for(&self, req: &ServiceRequest) -> (String, RateLimit) {
+        let pattern = req
+            .match_pattern()
+            .unwrap_or_else(|| req.path().to_string());
+
+        self.routes
+            .iter()
+            .find(|route| route.matches(req.method(), &pattern))
+            .map(|route| {
+                let method = route.method.as_ref().map_or("*", Method::as_str);
+                (format!("{} {}", method, route.pattern), route.limit)
+            })
+            .unwrap_or_else(|| ("default".to_string(), self.default_limit))
+    }
+
+    /// Identifies the caller: a verified access token first, then a known
+    /// API key, then the client IP.
+    fn client_key(&self, req: &ServiceRequest) -> String {
+        let bearer = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                value
+                    .strip_prefix("Bearer ")
+                    .or_else(|| value.strip_prefix("DPoP "))
+            });
+        if let Some(claims) =
+            bearer.and_then(|token| self.tokens.verify_access_token(token.trim()).ok())
+        {
+            return format!("user:{}", claims.sub);
+        }
+
+        if let Some(api_key) = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            let digest = Sha256::digest(api_key.as_bytes())
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            if self.api_keys.contains(&digest) {
+                return format!("key:{}", digest);
+            }
+        }
+
+        match self.proxies.client_ip(req.peer_addr(), req.headers()) {
+            Some(ip_address) => format!("ip:{}", ip_address),
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    async fn check(&self, req: &ServiceRequest) -> Option<RateLimitDecision> {
+        if !self.enabled {
+            return None;
+        }
+
+        let (route, limit) = self.limit_for(req);
+        let key = format!("{}|{}", route, self.client_key(req));
+        match self.store.take(&key, limit).await {
+            Ok(decision) => Some(decision),
+            Err(err) => {
+                tracing::warn!(error = %err, "rate limit store unavailable, allowing request");
+                None
+            }
+        }
+    }
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    let values = [
+        ("ratelimit-limit", decision.limit.capacity.to_string()),
+        ("ratelimit-remaining", decision.remaining.to_string()),
+        ("ratelimit-reset", decision.reset.to_string()),
+        ("ratelimit-policy", decision.limit.to_string()),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+}
+
+/// Token bucket rate limiting for every request that reaches the `App`.
+pub struct RateLimitMiddleware {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitService {
+            service: Rc::new(service),
+            limiter: Arc::clone(&self.limiter),
+        }))
+    }
+}
+
+pub struct RateLimitService<S> {
+    service: Rc<S>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let limiter = Arc::clone(&self.limiter);
+
+        Box::pin(async move {
+            let decision = limiter.check(&req).await;
+
+            if let Some(decision) = decision.filter(|decision| !decision.allowed) {
+                let mut response = HttpResponse::TooManyRequests();
+                response
+                    .insert_header((header::RETRY_AFTER, decision.retry_after.max(1).to_string()));
+                let mut response = response.json(ErrorResponse {
+                    message: "Too Many Requests".to_string(),
+                });
+                insert_headers(response.headers_mut(), &decision);
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            let mut res = service.call(req).await?;
+            if let Some(decision) = decision {
+                insert_headers(res.headers_mut(), &decision);
+            }
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TokenConfig;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    fn limiter(routes: &str) -> Arc<RateLimiter> {
+        let config = RateLimitConfig {
+            capacity: 5,
+            period: 60,
+            routes: routes.to_string(),
+            ..RateLimitConfig::default()
+        };
+        Arc::new(
+            RateLimiter::new(
+                &config,
+                Arc::new(MemoryRateLimitStore::new()),
+                Arc::new(TokenService::new(TokenConfig::default())),
+                Arc::new(TrustedProxies::default()),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[actix_web::test]
+    async fn test_route_limit_returns_429_with_headers() {
+        let app = init_service(
+            App::new()
+                .wrap(RateLimitMiddleware::new(limiter("POST /items/{id}=1/60")))
+                .route("/items/{id}", web::post().to(HttpResponse::Ok))
+                .route("/items/{id}", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::post().uri("/items/1").to_request()).await;
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!("1", res.headers().get("ratelimit-limit").unwrap());
+        assert_eq!("0", res.headers().get("ratelimit-remaining").unwrap());
+
+        let res = call_service(&app, TestRequest::post().uri("/items/2").to_request()).await;
+        assert_eq!(429, res.status().as_u16());
+        assert_eq!("60", res.headers().get(header::RETRY_AFTER).unwrap());
+
+        let res = call_service(&app, TestRequest::get().uri("/items/1").to_request()).await;
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!("5", res.headers().get("ratelimit-limit").unwrap());
+    }
+
+    #[actix_web::test]
+    async fn test_forwarded_for_from_untrusted_peer_shares_the_peer_bucket() {
+        let app = init_service(
+            App::new()
+                .wrap(RateLimitMiddleware::new(limiter("POST /login=1/60")))
+                .route("/login", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+        let request = |forwarded_for: &str| {
+            TestRequest::post()
+                .uri("/login")
+                .peer_addr("203.0.113.7:40000".parse().unwrap())
+                .insert_header(("x-forwarded-for", forwarded_for))
+                .to_request()
+        };
+
+        let res = call_service(&app, request("198.51.100.1")).await;
+        assert_eq!(200, res.status().as_u16());
+
+        let res = call_service(&app, request("198.51.100.2")).await;
+        assert_eq!(429, res.status().as_u16());
+    }
+
+    #[test]
+    fn test_invalid_route_spec_is_rejected() {
+        let config = RateLimitConfig {
+            routes: "POST /login=ten/60".to_string(),
+            ..RateLimitConfig::default()
+        };
+        let result = RateLimiter::new(
+            &config,
+            Arc::new(MemoryRateLimitStore::new()),
+            Arc::new(TokenService::new(TokenConfig::default())),
+            Arc::new(TrustedProxies::default()),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, StatusCode},
+    HttpResponse, ResponseError,
+};
+
+use crate::{
+    domain::{auth_context::StepUpRequirement, principal::Principal},
+    dto::error::StepUpResponse,
+};
+
+/// Error code from the OAuth step-up challenge protocol (RFC 9470).
+pub const INSUFFICIENT_USER_AUTHENTICATION: &str = "insufficient_user_authentication";
+
+/// The caller is signed in, but not strongly or recently enough.
+#[derive(Debug)]
+pub struct StepUpRequired(pub StepUpRequirement);
+
+impl fmt::Display for StepUpRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Step-Up Authentication Required")
+    }
+}
+
+impl ResponseError for StepUpRequired {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let StepUpRequired(requirement) = self;
+        let mut challenge = format!(
+            "Bearer error=\"{}\", error_description=\"{}\", acr_values=\"{}\"",
+            INSUFFICIENT_USER_AUTHENTICATION, self, requirement.acr
+        );
+        if let Some(max_age) = requirement.max_age {
+            challenge.push_str(&format!(", max_age={}", max_age));
+        }
+
+        HttpResponse::Unauthorized()
+            .insert_header((header::WWW_AUTHENTICATE, challenge))
+            .json(StepUpResponse {
+                message: self.to_string(),
+                error: INSUFFICIENT_USER_AUTHENTICATION.to_string(),
+                acr_values: requirement.acr.to_string(),
+                max_age: requirement.max_age,
+            })
+    }
+}
+
+/// Checks the caller's `acr` and `auth_time` against a route's requirement.
+pub fn require_step_up(
+    principal: &Principal,
+    requirement: StepUpRequirement,
+) -> Result<(), StepUpRequired> {
+    if requirement.is_met_by(&principal.auth) {
+        Ok(())
+    } else {
+        Err(StepUpRequired(requirement))
+    }
+}
+
+/// Lets a route or scope declare how strong and how recent the caller's
+/// sign-in has to be. Requests without a valid bearer token are rejected the
+/// same way the [`Principal`] extractor rejects them.
+pub struct StepUp {
+    requirement: StepUpRequirement,
+}
+
+impl StepUp {
+    pub fn new(requirement: StepUpRequirement) -> Self {
+        Self { requirement }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for StepUp
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = StepUpService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(StepUpService {
+            service: Rc::new(service),
+            requirement: self.requirement,
+        }))
+    }
+}
+
+pub struct StepUpService<S> {
+    service: Rc<S>,
+    requirement: StepUpRequirement,
+}
+
+impl<S, B> Service<ServiceRequest> for StepUpService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let requirement = self.requirement;
+
+        Box::pin(async move {
+            let checked = match req.extract::<Principal>().await {
+                Ok(principal) => {
+                    require_step_up(&principal, requirement).map_err(|err| err.error_response())
+                }
+                Err(err) => Err(err.error_response()),
+            };
+            if let Err(response) = checked {
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            Ok(service.call(req).await?.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::TokenConfig,
+        domain::auth_context::{AuthContext, AMR_OTP, AMR_PASSWORD},
+        util::token::TokenService,
+    };
+    use actix_web::{
+        test::{call_service, init_service, read_body_json, TestRequest},
+        web, App,
+    };
+    use uuid::Uuid;
+
+    #[actix_web::test]
+    async fn test_rejects_weak_or_stale_authentication() {
+        let tokens = web::Data::new(TokenService::new(TokenConfig::default()));
+        let app = init_service(
+            App::new().app_data(tokens.clone()).route(
+                "/sensitive",
+                web::post()
+                    .to(HttpResponse::Ok)
+                    .wrap(StepUp::new(StepUpRequirement::multi_factor(Some(300)))),
+            ),
+        )
+        .await;
+        let token = |auth: AuthContext| {
+            tokens
+                .issue_access_token(Uuid::new_v4(), Vec::new(), auth)
+                .unwrap()
+                .token
+        };
+        let request = |token: String| {
+            TestRequest::post()
+                .uri("/sensitive")
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let res = call_service(&app, TestRequest::post().uri("/sensitive").to_request()).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+        assert_eq!(
+            "Bearer",
+            res.headers().get(header::WWW_AUTHENTICATE).unwrap()
+        );
+
+        let res = call_service(&app, request(token(AuthContext::new(&[AMR_PASSWORD])))).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+        assert_eq!(
+            "Bearer error=\"insufficient_user_authentication\", \
+             error_description=\"Step-Up Authentication Required\", acr_values=\"2\", max_age=300",
+            res.headers().get(header::WWW_AUTHENTICATE).unwrap()
+        );
+        let body: StepUpResponse = read_body_json(res).await;
+        assert_eq!(INSUFFICIENT_USER_AUTHENTICATION, body.error);
+        assert_eq!("2", body.acr_values);
+
+        let mut stale = AuthContext::new(&[AMR_PASSWORD, AMR_OTP]);
+        stale.auth_time -= 301;
+        let res = call_service(&app, request(token(stale))).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+
+        let fresh = AuthContext::new(&[AMR_PASSWORD, AMR_OTP]);
+        let res = call_service(&app, request(token(fresh))).await;
+        assert_eq!(StatusCode::OK, res.status());
+    }
+}
@@ -1,22 +1,301 @@
+pub mod auth;
+pub mod extractors;
 pub mod health_check;
+pub mod metrics;
+pub mod mfa;
+pub mod middleware;
+pub mod oauth;
+pub mod oauth_client;
+pub mod oauth_grant;
+pub mod passwordless;
+pub mod phone;
+pub mod security_event;
+pub mod session;
 pub mod user;
+pub mod webauthn;
 
 use crate::api::health_check::health_check_cfg;
+use crate::application::services::mailer::Mailer;
+use crate::application::services::notifier::Notifier;
+use crate::application::services::sms_sender::SmsSender;
+use crate::application::use_cases::auth::AuthUseCase;
+use crate::application::use_cases::dpop::DpopUseCase;
+use crate::application::use_cases::login_event::LoginEventUseCase;
+use crate::application::use_cases::login_risk::LoginRiskUseCase;
+use crate::application::use_cases::mfa::MfaUseCase;
+use crate::application::use_cases::oauth::OAuthUseCase;
+use crate::application::use_cases::oauth_client::OAuthClientUseCase;
+use crate::application::use_cases::oauth_grant::OAuthGrantUseCase;
+use crate::application::use_cases::passwordless::PasswordlessUseCase;
+use crate::application::use_cases::phone::PhoneUseCase;
+use crate::application::use_cases::revocation::RevocationUseCase;
+use crate::application::use_cases::session::SessionUseCase;
 use crate::application::use_cases::user::UserUseCase;
+use crate::application::use_cases::webauthn::WebauthnUseCase;
+use crate::config::AppConfig;
+use crate::infrastructure::mailers::log_mailer::LogMailer;
+use crate::infrastructure::mailers::smtp_mailer::SmtpMailer;
+use crate::infrastructure::notifiers::email_notifier::EmailNotifier;
+use crate::infrastructure::notifiers::log_notifier::LogNotifier;
+use crate::infrastructure::repositories::postgres_lockout_repo::PostgresLockoutRepository;
+use crate::infrastructure::repositories::postgres_authorization_code_repo::PostgresAuthorizationCodeRepository;
+use crate::infrastructure::repositories::postgres_client_assertion_repo::PostgresClientAssertionRepository;
+use crate::infrastructure::repositories::postgres_device_code_repo::PostgresDeviceCodeRepository;
+use crate::infrastructure::repositories::postgres_dpop_proof_repo::PostgresDpopProofRepository;
+use crate::infrastructure::repositories::postgres_login_event_repo::PostgresLoginEventRepository;
+use crate::infrastructure::repositories::postgres_mfa_repo::PostgresMfaRepository;
+use crate::infrastructure::repositories::postgres_oauth_client_repo::PostgresOAuthClientRepository;
+use crate::infrastructure::repositories::postgres_oauth_grant_repo::PostgresOAuthGrantRepository;
+use crate::infrastructure::repositories::postgres_one_time_code_repo::PostgresOneTimeCodeRepository;
+use crate::infrastructure::repositories::postgres_pushed_request_repo::PostgresPushedRequestRepository;
+use crate::infrastructure::repositories::postgres_refresh_token_repo::PostgresRefreshTokenRepository;
+use crate::infrastructure::repositories::postgres_revoked_token_repo::PostgresRevokedTokenRepository;
+use crate::infrastructure::repositories::postgres_session_repo::PostgresSessionRepository;
 use crate::infrastructure::repositories::postgres_user_repo::PostgresUserRepository;
+use crate::infrastructure::repositories::postgres_webauthn_repo::PostgresWebauthnRepository;
+use crate::infrastructure::sms_senders::log_sms_sender::LogSmsSender;
+use crate::infrastructure::sms_senders::webhook_sms_sender::WebhookSmsSender;
+use crate::util::client_ip::TrustedProxies;
+use crate::util::geoip::GeoIp;
+use crate::util::id_token::IdTokenSigner;
+use crate::util::pwd_pool::PwdPool;
+use crate::util::throttle::{LoginThrottle, Throttle};
+use crate::util::token::TokenService;
 use actix_web::web;
 use sqlx::PgPool;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
+use self::auth::{auth_cfg, PostgresLoginRiskUseCase, PostgresRevocationUseCase};
+use self::metrics::metrics_cfg;
+use self::middleware::device::DeviceCookie;
+use self::mfa::mfa_cfg;
+use self::oauth::{oauth_cfg, PostgresDpopUseCase};
+use self::oauth_client::oauth_client_cfg;
+use self::oauth_grant::oauth_grant_cfg;
+use self::passwordless::passwordless_cfg;
+use self::phone::phone_cfg;
+use self::security_event::{security_event_cfg, PostgresLoginEventUseCase};
+use self::session::{session_cfg, user_session_cfg};
 use self::user::user_cfg;
+use self::webauthn::webauthn_cfg;
 
-pub fn api_v1_cfg(cfg: &mut web::ServiceConfig, pool: PgPool) {
+/// Services shared by every actix worker. Built once in `main` so in-memory
+/// state such as hashing slots and login throttles is not split per worker.
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<AppConfig>,
+    pub pool: PgPool,
+    pub pwd_pool: Arc<PwdPool>,
+    pub tokens: Arc<TokenService>,
+    pub id_tokens: Arc<IdTokenSigner>,
+    pub revocation: Arc<PostgresRevocationUseCase>,
+    pub dpop: Arc<PostgresDpopUseCase>,
+    pub login_events: Arc<PostgresLoginEventUseCase>,
+    pub login_risk: Arc<PostgresLoginRiskUseCase>,
+    pub geoip: Option<Arc<GeoIp>>,
+    pub trusted_proxies: Arc<TrustedProxies>,
+    pub login_throttle: Arc<LoginThrottle>,
+    /// SMS sends per user. Shared by all workers, so the limit holds for
+    /// the whole process.
+    pub sms_throttle: Arc<Throttle>,
+    pub mailer: Arc<dyn Mailer + Send + Sync>,
+    pub sms_sender: Arc<dyn SmsSender + Send + Sync>,
+}
+
+impl AppState {
+    pub fn new(config: Arc<AppConfig>, pool: PgPool) -> Result<Self, Box<dyn Error>> {
+        let mailer: Arc<dyn Mailer + Send + Sync> = match config.mail.backend.as_str() {
+            "log" => Arc::new(LogMailer::new()),
+            "smtp" => Arc::new(SmtpMailer::new(&config.mail)?),
+            backend => return Err(format!("unknown mail backend: {}", backend).into()),
+        };
+        let sms_sender: Arc<dyn SmsSender + Send + Sync> = match config.sms.backend.as_str() {
+            "log" => Arc::new(LogSmsSender::new(
+                Some(PathBuf::from(&config.sms.file)).filter(|file| !file.as_os_str().is_empty()),
+            )),
+            "webhook" => Arc::new(WebhookSmsSender::new(&config.sms)?),
+            backend => return Err(format!("unknown SMS backend: {}", backend).into()),
+        };
+        let notifier: Arc<dyn Notifier + Send + Sync> = match config.risk.notifier.as_str() {
+            "log" => Arc::new(LogNotifier::new()),
+            "email" => Arc::new(EmailNotifier::new(Arc::clone(&mailer))),
+            notifier => return Err(format!("unknown notifier: {}", notifier).into()),
+        };
+        let geoip = match config.risk.geoip.as_str() {
+            "" => None,
+            path => Some(Arc::new(GeoIp::open(path)?)),
+        };
+        let id_tokens = match config.oauth.key.as_str() {
+            "" => {
+                tracing::warn!("no ID token signing key configured, generating one");
+                IdTokenSigner::generate()?
+            }
+            path => IdTokenSigner::open(path)?,
+        };
+
+        Ok(Self {
+            pwd_pool: Arc::new(PwdPool::new(config.pwd.clone())),
+            tokens: Arc::new(TokenService::new(config.token.clone())),
+            id_tokens: Arc::new(id_tokens),
+            revocation: Arc::new(RevocationUseCase::new(
+                PostgresRevokedTokenRepository::new(pool.clone()),
+                config.revocation.clone(),
+            )),
+            dpop: Arc::new(DpopUseCase::new(
+                PostgresDpopProofRepository::new(pool.clone()),
+                &config.token.secret,
+                config.dpop.clone(),
+            )),
+            login_events: Arc::new(LoginEventUseCase::new(
+                PostgresLoginEventRepository::new(pool.clone()),
+                config.events.clone(),
+            )),
+            login_risk: Arc::new(LoginRiskUseCase::new(
+                PostgresLoginEventRepository::new(pool.clone()),
+                notifier,
+                config.risk.clone(),
+            )),
+            geoip,
+            trusted_proxies: Arc::new(TrustedProxies::parse(&config.proxy.trusted)?),
+            login_throttle: Arc::new(LoginThrottle::new(&config.throttle)),
+            sms_throttle: Arc::new(Throttle::new(
+                config.sms.sends,
+                Duration::from_secs(config.sms.window),
+            )),
+            mailer,
+            sms_sender,
+            config,
+            pool,
+        })
+    }
+}
+
+pub fn api_v1_cfg(cfg: &mut web::ServiceConfig, state: AppState) {
+    cfg.app_data(web::Data::from(Arc::clone(&state.tokens)));
+    cfg.app_data(web::Data::from(Arc::clone(&state.revocation)));
+    cfg.app_data(web::Data::from(Arc::clone(&state.dpop)));
+    cfg.app_data(web::Data::from(Arc::clone(&state.trusted_proxies)));
+    if let Some(geoip) = &state.geoip {
+        cfg.app_data(web::Data::from(Arc::clone(geoip)));
+    }
+    // Registered for every scope so the Principal extractor can fall back to
+    // the session cookie.
+    cfg.app_data(web::Data::new(SessionUseCase::new(
+        PostgresSessionRepository::new(state.pool.clone()),
+        state.config.session.clone(),
+    )));
     cfg.service(web::scope("/healthz").configure(health_check_cfg));
+    cfg.service(
+        web::scope("/metrics")
+            .app_data(web::Data::from(Arc::clone(&state.pwd_pool)))
+            .configure(metrics_cfg),
+    );
+
+    let webauthn_use_case = web::Data::new(WebauthnUseCase::new(
+        PostgresUserRepository::new(state.pool.clone()),
+        PostgresWebauthnRepository::new(state.pool.clone()),
+        state.config.webauthn.clone(),
+    ));
+    let phone_use_case = web::Data::new(PhoneUseCase::new(
+        PostgresUserRepository::new(state.pool.clone()),
+        PostgresOneTimeCodeRepository::new(state.pool.clone()),
+        Arc::clone(&state.sms_sender),
+        Arc::clone(&state.sms_throttle),
+        state.config.sms.clone(),
+    ));
+    let mfa_use_case = web::Data::new(MfaUseCase::new(
+        PostgresUserRepository::new(state.pool.clone()),
+        PostgresMfaRepository::new(state.pool.clone()),
+        webauthn_use_case.clone().into_inner(),
+        phone_use_case.clone().into_inner(),
+        state.config.mfa.clone(),
+    ));
+    let passwordless_use_case = web::Data::new(PasswordlessUseCase::new(
+        PostgresUserRepository::new(state.pool.clone()),
+        PostgresOneTimeCodeRepository::new(state.pool.clone()),
+        Arc::clone(&state.login_events),
+        Arc::clone(&state.mailer),
+        Arc::clone(&state.login_throttle),
+        state.config.passwordless.clone(),
+    ));
+    let auth_use_case = web::Data::new(AuthUseCase::new(
+        PostgresUserRepository::new(state.pool.clone()),
+        PostgresLockoutRepository::new(state.pool.clone()),
+        mfa_use_case.clone().into_inner(),
+        Arc::clone(&state.pwd_pool),
+        Arc::clone(&state.tokens),
+        Arc::clone(&state.revocation),
+        Arc::clone(&state.login_events),
+        Arc::clone(&state.login_risk),
+        passwordless_use_case.clone().into_inner(),
+        Arc::clone(&state.login_throttle),
+        state.config.lockout.clone(),
+    ));
+    cfg.service(
+        web::scope("/auth")
+            .wrap(DeviceCookie::new(state.config.risk.secure))
+            .app_data(auth_use_case.clone())
+            .app_data(passwordless_use_case)
+            .configure(auth_cfg)
+            .configure(passwordless_cfg)
+            .configure(session_cfg),
+    );
+
+    let oauth_grant_use_case = web::Data::new(OAuthGrantUseCase::new(
+        PostgresOAuthGrantRepository::new(state.pool.clone()),
+        PostgresRefreshTokenRepository::new(state.pool.clone()),
+    ));
+    let oauth_use_case = web::Data::new(OAuthUseCase::new(
+        PostgresOAuthClientRepository::new(state.pool.clone()),
+        PostgresAuthorizationCodeRepository::new(state.pool.clone()),
+        PostgresClientAssertionRepository::new(state.pool.clone()),
+        PostgresDeviceCodeRepository::new(state.pool.clone()),
+        PostgresUserRepository::new(state.pool.clone()),
+        PostgresRefreshTokenRepository::new(state.pool.clone()),
+        Arc::clone(&state.revocation),
+        oauth_grant_use_case.clone().into_inner(),
+        Arc::clone(&state.dpop),
+        PostgresPushedRequestRepository::new(state.pool.clone()),
+        Arc::clone(&state.tokens),
+        Arc::clone(&state.id_tokens),
+        state.config.oauth.clone(),
+    ));
+    cfg.service(
+        web::scope("/oauth")
+            .wrap(DeviceCookie::new(state.config.risk.secure))
+            .app_data(oauth_use_case)
+            .app_data(auth_use_case.clone())
+            .configure(oauth_cfg),
+    );
+    cfg.service(
+        web::scope("/oauth-clients")
+            .app_data(web::Data::new(OAuthClientUseCase::new(
+                PostgresOAuthClientRepository::new(state.pool.clone()),
+                state.config.oauth.clone(),
+            )))
+            .configure(oauth_client_cfg),
+    );
 
-    let user_repository = PostgresUserRepository::new(pool);
-    let user_use_case = UserUseCase::new(user_repository);
+    let user_repository = PostgresUserRepository::new(state.pool);
+    let user_use_case = UserUseCase::new(user_repository, state.pwd_pool);
     cfg.service(
         web::scope("/users")
             .app_data(web::Data::new(user_use_case))
-            .configure(user_cfg),
+            .app_data(auth_use_case)
+            .app_data(mfa_use_case)
+            .app_data(webauthn_use_case)
+            .app_data(phone_use_case)
+            .app_data(oauth_grant_use_case)
+            .app_data(web::Data::from(Arc::clone(&state.login_events)))
+            .configure(user_cfg)
+            .configure(mfa_cfg)
+            .configure(webauthn_cfg)
+            .configure(phone_cfg)
+            .configure(security_event_cfg)
+            .configure(oauth_grant_cfg)
+            .configure(user_session_cfg),
     );
 }
@@ -0,0 +1,848 @@
+use actix_web::{
+    http::{
+        header::{self, ContentType, HeaderName, HeaderValue},
+        StatusCode,
+    },
+    web, HttpRequest, HttpResponse,
+};
+use data_encoding::BASE64;
+use url::{form_urlencoded, Url};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        auth::{auth_error_response, PostgresAuthUseCase},
+        extractors::{dpop_proof, OAuthPrincipal},
+    },
+    application::use_cases::{
+        auth::LoginOutcome,
+        dpop::DpopUseCase,
+        oauth::{ClientAuthentication, OAuthError, OAuthUseCase},
+    },
+    domain::{
+        auth_context::AuthContext,
+        client::ClientContext,
+        oauth::{OAuthClient, SCOPE_BIRTHDATE, SCOPE_EMAIL, SCOPE_OPENID, SCOPE_PROFILE},
+        principal::Principal,
+    },
+    dto::{
+        auth_dto::LoginRequest,
+        error::ErrorResponse,
+        oauth_dto::{
+            AuthorizeForm, AuthorizeRequest, ConsentForm, DeviceAuthorizationRequest, DeviceForm,
+            DeviceQuery, DeviceVerifyRequest, DeviceVerifyResponse, IntrospectionRequest,
+            OAuthErrorResponse, PushedAuthorizationRequest, RevocationRequest, TokenRequest,
+            TokenResponse,
+        },
+    },
+    infrastructure::repositories::{
+        postgres_authorization_code_repo::PostgresAuthorizationCodeRepository,
+        postgres_client_assertion_repo::PostgresClientAssertionRepository,
+        postgres_device_code_repo::PostgresDeviceCodeRepository,
+        postgres_dpop_proof_repo::PostgresDpopProofRepository,
+        postgres_oauth_client_repo::PostgresOAuthClientRepository,
+        postgres_oauth_grant_repo::PostgresOAuthGrantRepository,
+        postgres_pushed_request_repo::PostgresPushedRequestRepository,
+        postgres_refresh_token_repo::PostgresRefreshTokenRepository,
+        postgres_revoked_token_repo::PostgresRevokedTokenRepository,
+        postgres_user_repo::PostgresUserRepository,
+    },
+};
+
+pub type PostgresOAuthUseCase = OAuthUseCase<
+    PostgresOAuthClientRepository,
+    PostgresAuthorizationCodeRepository,
+    PostgresClientAssertionRepository,
+    PostgresDeviceCodeRepository,
+    PostgresUserRepository,
+    PostgresRefreshTokenRepository,
+    PostgresRevokedTokenRepository,
+    PostgresOAuthGrantRepository,
+    PostgresDpopProofRepository,
+    PostgresPushedRequestRepository,
+>;
+
+pub type PostgresDpopUseCase = DpopUseCase<PostgresDpopProofRepository>;
+
+/// Shown when the password was right but the account needs more than a
+/// password to sign in.
+const VERIFICATION_REQUIRED: &str =
+    "Additional Verification Required. Sign In To Your Account, Then Try Again";
+
+pub fn oauth_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/authorize")
+            .route(web::get().to(authorize))
+            .route(web::post().to(authorize_with_password)),
+    );
+    cfg.service(web::resource("/authorize/consent").route(web::post().to(authorize_consent)));
+    cfg.service(web::resource("/par").route(web::post().to(pushed_authorization_request)));
+    cfg.service(web::resource("/token").route(web::post().to(token)));
+    cfg.service(web::resource("/introspect").route(web::post().to(introspect)));
+    cfg.service(web::resource("/revoke").route(web::post().to(revoke)));
+    cfg.service(web::resource("/device_authorization").route(web::post().to(device_authorization)));
+    cfg.service(
+        web::resource("/device")
+            .route(web::get().to(device))
+            .route(web::post().to(device_with_password)),
+    );
+    cfg.service(web::resource("/device/verify").route(web::post().to(device_verify)));
+    cfg.service(
+        web::resource("/userinfo")
+            .route(web::get().to(userinfo))
+            .route(web::post().to(userinfo)),
+    );
+    cfg.service(web::resource("/jwks").route(web::get().to(jwks)));
+    cfg.service(web::resource("/.well-known/openid-configuration").route(web::get().to(discovery)));
+}
+
+/// Starts an authorization. A browser that already has a cookie session
+/// goes on to the consent screen or straight back to the client; anyone
+/// else gets the sign-in form.
+async fn authorize(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    principal: Option<Principal>,
+    query: web::Query<AuthorizeRequest>,
+) -> HttpResponse {
+    let (client, request) = match use_case.get_ref().resolve_request(&query).await {
+        Ok(resolved) => resolved,
+        Err(err) => return oauth_error_response(err),
+    };
+    if let Err(err) = use_case.get_ref().check_request(&client, &request) {
+        return error_redirect(&request, err);
+    }
+
+    match principal.filter(|principal| principal.session) {
+        Some(principal) => {
+            grant_code(
+                use_case.get_ref(),
+                &client,
+                &request,
+                principal.user_id,
+                principal.auth,
+            )
+            .await
+        }
+        None => login_page(&client, &request, StatusCode::OK, None),
+    }
+}
+
+/// The sign-in form posts back here. Accounts that need a second factor or
+/// an extra verification step are asked to sign in to the service first,
+/// so the form never becomes a way around MFA.
+async fn authorize_with_password(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    auth_use_case: web::Data<PostgresAuthUseCase>,
+    client_context: ClientContext,
+    form: web::Form<AuthorizeForm>,
+) -> HttpResponse {
+    let AuthorizeForm {
+        request,
+        username,
+        password,
+    } = form.into_inner();
+    let (client, request) = match use_case.get_ref().resolve_request(&request).await {
+        Ok(resolved) => resolved,
+        Err(err) => return oauth_error_response(err),
+    };
+    if let Err(err) = use_case.get_ref().check_request(&client, &request) {
+        return error_redirect(&request, err);
+    }
+
+    let credentials = LoginRequest { username, password };
+    match auth_use_case
+        .get_ref()
+        .login(credentials, client_context)
+        .await
+    {
+        Ok(LoginOutcome::Authenticated(issued)) => {
+            grant_code(
+                use_case.get_ref(),
+                &client,
+                &request,
+                issued.claims.sub,
+                issued.claims.auth,
+            )
+            .await
+        }
+        Ok(_) => login_page(
+            &client,
+            &request,
+            StatusCode::UNAUTHORIZED,
+            Some(VERIFICATION_REQUIRED),
+        ),
+        Err(err) => {
+            let message = err.to_string();
+            let status = auth_error_response(err).status();
+            login_page(&client, &request, status, Some(&message))
+        }
+    }
+}
+
+/// Sends the signed-in user back to the client with a code, unless they
+/// first have to agree to what the client asks for.
+async fn grant_code(
+    use_case: &PostgresOAuthUseCase,
+    client: &OAuthClient,
+    request: &AuthorizeRequest,
+    user_id: Uuid,
+    auth: AuthContext,
+) -> HttpResponse {
+    let needs_consent = match use_case.needs_consent(client, request, user_id).await {
+        Ok(needs_consent) => needs_consent,
+        Err(err) => return error_redirect(request, err),
+    };
+    if needs_consent {
+        return match use_case.consent_ticket(client, request, user_id, auth) {
+            Ok(ticket) => consent_page(client, request, &ticket),
+            Err(err) => error_redirect(request, err),
+        };
+    }
+
+    match use_case.issue_code(client, request, user_id, auth).await {
+        Ok(code) => redirect(request, &[("code", &code)]),
+        Err(err) => error_redirect(request, err),
+    }
+}
+
+/// The consent screen posts the user's answer here. Either way the browser
+/// goes back to the client.
+async fn authorize_consent(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    form: web::Form<ConsentForm>,
+) -> HttpResponse {
+    let ConsentForm {
+        request,
+        consent_ticket,
+        action,
+    } = form.into_inner();
+    let (client, request) = match use_case.get_ref().resolve_request(&request).await {
+        Ok(resolved) => resolved,
+        Err(err) => return oauth_error_response(err),
+    };
+    if let Err(err) = use_case.get_ref().check_request(&client, &request) {
+        return error_redirect(&request, err);
+    }
+
+    match use_case
+        .get_ref()
+        .consent(&client, &request, &consent_ticket, action == "approve")
+        .await
+    {
+        Ok(code) => redirect(&request, &[("code", &code)]),
+        Err(err) => error_redirect(&request, err),
+    }
+}
+
+/// Takes an authorization request straight from the client, which then
+/// sends the browser to the authorization endpoint with the `request_uri`.
+async fn pushed_authorization_request(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    req: HttpRequest,
+    form: web::Form<PushedAuthorizationRequest>,
+) -> HttpResponse {
+    let request = form.into_inner();
+    let authentication = match basic_credentials(&req)
+        .and_then(|basic| ClientAuthentication::from_request(basic, &request.client()))
+    {
+        Ok(authentication) => authentication,
+        Err(err) => return oauth_error_response(err),
+    };
+
+    match use_case
+        .get_ref()
+        .push_authorization_request(authentication, request.request)
+        .await
+    {
+        Ok(pushed) => HttpResponse::Created()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .content_type(ContentType::json())
+            .json(pushed),
+        Err(err) => oauth_error_response(err),
+    }
+}
+
+async fn token(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    req: HttpRequest,
+    form: web::Form<TokenRequest>,
+) -> HttpResponse {
+    let request = form.into_inner();
+    let authentication = match basic_credentials(&req)
+        .and_then(|basic| ClientAuthentication::from_request(basic, &request.client))
+    {
+        Ok(authentication) => authentication,
+        Err(err) => return oauth_error_response(err),
+    };
+    let proof = match dpop_proof(&req) {
+        Ok(proof) => proof,
+        Err(description) => return oauth_error_response(OAuthError::InvalidDpopProof(description)),
+    };
+
+    let mut response = match use_case
+        .get_ref()
+        .token(authentication, request, proof)
+        .await
+    {
+        Ok(tokens) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .content_type(ContentType::json())
+            .json(TokenResponse {
+                refresh_token: tokens.refresh_token,
+                id_token: tokens.id_token,
+                issued_token_type: tokens.issued_token_type.map(str::to_string),
+                ..TokenResponse::from(tokens.access)
+            }),
+        Err(err) => oauth_error_response(err),
+    };
+    // Handed out with every answer, so the client always has a current one
+    // for its next proof.
+    if let Some(nonce) = use_case
+        .dpop_nonce()
+        .and_then(|nonce| HeaderValue::from_str(&nonce).ok())
+    {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static("dpop-nonce"), nonce);
+    }
+    response
+}
+
+async fn introspect(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    req: HttpRequest,
+    form: web::Form<IntrospectionRequest>,
+) -> HttpResponse {
+    let request = form.into_inner();
+    let authentication = match basic_credentials(&req)
+        .and_then(|basic| ClientAuthentication::from_request(basic, &request.client))
+    {
+        Ok(authentication) => authentication,
+        Err(err) => return oauth_error_response(err),
+    };
+
+    match use_case
+        .get_ref()
+        .introspect(
+            authentication,
+            request.token,
+            request.token_type_hint.as_deref(),
+        )
+        .await
+    {
+        Ok(introspection) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .content_type(ContentType::json())
+            .json(introspection),
+        Err(err) => oauth_error_response(err),
+    }
+}
+
+async fn revoke(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    req: HttpRequest,
+    form: web::Form<RevocationRequest>,
+) -> HttpResponse {
+    let request = form.into_inner();
+    let authentication = match basic_credentials(&req)
+        .and_then(|basic| ClientAuthentication::from_request(basic, &request.client))
+    {
+        Ok(authentication) => authentication,
+        Err(err) => return oauth_error_response(err),
+    };
+
+    match use_case
+        .get_ref()
+        .revoke(
+            authentication,
+            request.token,
+            request.token_type_hint.as_deref(),
+        )
+        .await
+    {
+        Ok(()) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .finish(),
+        Err(err) => oauth_error_response(err),
+    }
+}
+
+/// OpenID Connect userinfo endpoint. Only tokens granted the `openid`
+/// scope may read it, and only the claims their scopes cover come back.
+async fn userinfo(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    principal: OAuthPrincipal,
+) -> HttpResponse {
+    let Some(scope) = principal.scope(SCOPE_OPENID) else {
+        return bearer_error_response(
+            StatusCode::FORBIDDEN,
+            "insufficient_scope",
+            "The openid Scope Is Required",
+        );
+    };
+
+    match use_case
+        .get_ref()
+        .userinfo(principal.0.user_id, scope)
+        .await
+    {
+        Ok(Some(info)) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .content_type(ContentType::json())
+            .json(info),
+        Ok(None) => {
+            bearer_error_response(StatusCode::UNAUTHORIZED, "invalid_token", "User Not Found")
+        }
+        Err(err) => oauth_error_response(err),
+    }
+}
+
+async fn jwks(use_case: web::Data<PostgresOAuthUseCase>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .json(use_case.get_ref().jwks())
+}
+
+async fn discovery(use_case: web::Data<PostgresOAuthUseCase>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .json(use_case.get_ref().discovery())
+}
+
+/// Starts a device authorization. The device shows the user code and
+/// polls the token endpoint until the user has answered.
+async fn device_authorization(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    req: HttpRequest,
+    form: web::Form<DeviceAuthorizationRequest>,
+) -> HttpResponse {
+    let request = form.into_inner();
+    let authentication = match basic_credentials(&req)
+        .and_then(|basic| ClientAuthentication::from_request(basic, &request.client))
+    {
+        Ok(authentication) => authentication,
+        Err(err) => return oauth_error_response(err),
+    };
+
+    match use_case
+        .get_ref()
+        .authorize_device(authentication, request.scope)
+        .await
+    {
+        Ok(authorization) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .content_type(ContentType::json())
+            .json(authorization),
+        Err(err) => oauth_error_response(err),
+    }
+}
+
+/// The verification page. Following `verification_uri_complete` fills in
+/// the user code and names the client asking for access.
+async fn device(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    query: web::Query<DeviceQuery>,
+) -> HttpResponse {
+    let Some(user_code) = query.into_inner().user_code else {
+        return device_page(StatusCode::OK, None, None, None);
+    };
+
+    match use_case.get_ref().find_device_request(&user_code).await {
+        Ok((_, client)) => device_page(StatusCode::OK, Some(&user_code), Some(&client), None),
+        Err(err) => device_error_page(err, &user_code),
+    }
+}
+
+/// The user answers a device request by signing in with their password.
+/// As with the authorization form, accounts that need more than a password
+/// are turned away.
+async fn device_with_password(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    auth_use_case: web::Data<PostgresAuthUseCase>,
+    client_context: ClientContext,
+    form: web::Form<DeviceForm>,
+) -> HttpResponse {
+    let DeviceForm {
+        user_code,
+        username,
+        password,
+        action,
+    } = form.into_inner();
+    let approve = action.as_deref() != Some("deny");
+    if let Err(err) = use_case.get_ref().find_device_request(&user_code).await {
+        return device_error_page(err, &user_code);
+    }
+
+    let credentials = LoginRequest { username, password };
+    let issued = match auth_use_case
+        .get_ref()
+        .login(credentials, client_context)
+        .await
+    {
+        Ok(LoginOutcome::Authenticated(issued)) => issued,
+        Ok(_) => {
+            return device_page(
+                StatusCode::UNAUTHORIZED,
+                Some(&user_code),
+                None,
+                Some(VERIFICATION_REQUIRED),
+            )
+        }
+        Err(err) => {
+            let message = err.to_string();
+            let status = auth_error_response(err).status();
+            return device_page(status, Some(&user_code), None, Some(&message));
+        }
+    };
+
+    match use_case
+        .get_ref()
+        .decide_device_request(&user_code, issued.claims.sub, &issued.claims.auth, approve)
+        .await
+    {
+        Ok(()) => html_response(
+            StatusCode::OK,
+            format!(
+                "<h1>{}</h1>\n<p>You can close this page and return to your device.</p>\n",
+                if approve {
+                    "Device Connected"
+                } else {
+                    "Request Denied"
+                }
+            ),
+        ),
+        Err(err) => device_error_page(err, &user_code),
+    }
+}
+
+/// Lets a first-party app answer a device request for a user who is
+/// already signed in to it.
+async fn device_verify(
+    use_case: web::Data<PostgresOAuthUseCase>,
+    principal: Principal,
+    req_body: web::Json<DeviceVerifyRequest>,
+) -> HttpResponse {
+    let request = req_body.into_inner();
+
+    match use_case
+        .get_ref()
+        .decide_device_request(
+            &request.user_code,
+            principal.user_id,
+            &principal.auth,
+            request.approve,
+        )
+        .await
+    {
+        Ok(()) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(DeviceVerifyResponse {
+                message: if request.approve {
+                    "Device Approved".to_string()
+                } else {
+                    "Device Request Denied".to_string()
+                },
+            }),
+        Err(OAuthError::Internal(err)) => {
+            tracing::error!(error = %err, "device verification failed");
+            HttpResponse::InternalServerError()
+                .content_type(ContentType::json())
+                .json(ErrorResponse {
+                    message: err.to_string(),
+                })
+        }
+        Err(err) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+/// `client_secret_basic` credentials. Both parts are form-encoded before
+/// they are joined (RFC 6749, section 2.3.1).
+fn basic_credentials(req: &HttpRequest) -> Result<Option<(String, String)>, OAuthError> {
+    let Some(value) = req.headers().get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+    let credentials = value
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| BASE64.decode(encoded.trim().as_bytes()).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or(OAuthError::InvalidClient)?;
+    let (client_id, client_secret) = credentials
+        .split_once(':')
+        .ok_or(OAuthError::InvalidClient)?;
+
+    Ok(Some((form_decode(client_id), form_decode(client_secret))))
+}
+
+fn form_decode(value: &str) -> String {
+    form_urlencoded::parse(value.as_bytes())
+        .map(|(decoded, _)| decoded.into_owned())
+        .next()
+        .unwrap_or_default()
+}
+
+pub fn oauth_error_response(err: OAuthError) -> HttpResponse {
+    let mut response = match &err {
+        OAuthError::InvalidClient => {
+            let mut response = HttpResponse::Unauthorized();
+            response.insert_header((header::WWW_AUTHENTICATE, "Basic realm=\"oauth\""));
+            response
+        }
+        OAuthError::Internal(err) => {
+            tracing::error!(error = %err, "OAuth request failed");
+            HttpResponse::InternalServerError()
+        }
+        _ => HttpResponse::BadRequest(),
+    };
+
+    response
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .content_type(ContentType::json())
+        .json(OAuthErrorResponse {
+            error: err.code().to_string(),
+            error_description: err.to_string(),
+        })
+}
+
+/// Error of a protected resource, as RFC 6750 describes it.
+fn bearer_error_response(status: StatusCode, error: &str, description: &str) -> HttpResponse {
+    HttpResponse::build(status)
+        .insert_header((
+            header::WWW_AUTHENTICATE,
+            format!(
+                "Bearer error=\"{}\", error_description=\"{}\"",
+                error, description
+            ),
+        ))
+        .content_type(ContentType::json())
+        .json(OAuthErrorResponse {
+            error: error.to_string(),
+            error_description: description.to_string(),
+        })
+}
+
+/// Sends the browser back to the client's redirect URI, which has already
+/// been checked against the registration, with `state` echoed unchanged.
+fn redirect(request: &AuthorizeRequest, params: &[(&str, &str)]) -> HttpResponse {
+    let Some(mut url) = request
+        .redirect_uri
+        .as_deref()
+        .and_then(|uri| Url::parse(uri).ok())
+    else {
+        return oauth_error_response(OAuthError::InvalidRequest("Invalid redirect_uri"));
+    };
+
+    {
+        let mut query = url.query_pairs_mut();
+        for (name, value) in params {
+            query.append_pair(name, value);
+        }
+        if let Some(state) = &request.state {
+            query.append_pair("state", state);
+        }
+    }
+
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, url.to_string()))
+        .finish()
+}
+
+fn error_redirect(request: &AuthorizeRequest, err: OAuthError) -> HttpResponse {
+    if let OAuthError::Internal(err) = &err {
+        tracing::error!(error = %err, "authorization request failed");
+    }
+    let description = err.to_string();
+    redirect(
+        request,
+        &[("error", err.code()), ("error_description", &description)],
+    )
+}
+
+fn device_error_page(err: OAuthError, user_code: &str) -> HttpResponse {
+    match err {
+        OAuthError::Internal(err) => {
+            tracing::error!(error = %err, "device verification failed");
+            device_page(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Some(user_code),
+                None,
+                Some("Something Went Wrong. Please Try Again"),
+            )
+        }
+        err => device_page(StatusCode::NOT_FOUND, None, None, Some(&err.to_string())),
+    }
+}
+
+fn device_page(
+    status: StatusCode,
+    user_code: Option<&str>,
+    client: Option<&OAuthClient>,
+    message: Option<&str>,
+) -> HttpResponse {
+    let intro = client
+        .map(|client| {
+            format!(
+                "<p>{} is asking to access your account.</p>\n",
+                escape_html(&client.name)
+            )
+        })
+        .unwrap_or_default();
+    let message = message
+        .map(|message| format!("<p role=\"alert\">{}</p>\n", escape_html(message)))
+        .unwrap_or_default();
+
+    html_response(
+        status,
+        format!(
+            "<h1>Connect a device</h1>\n{}{}\
+             <form method=\"post\" action=\"device\">\n\
+             <label>Code shown on your device <input name=\"user_code\" value=\"{}\" autocomplete=\"off\" required></label>\n\
+             <label>Username <input name=\"username\" autocomplete=\"username\" required></label>\n\
+             <label>Password <input name=\"password\" type=\"password\" autocomplete=\"current-password\" required></label>\n\
+             <button type=\"submit\" name=\"action\" value=\"approve\">Allow</button>\n\
+             <button type=\"submit\" name=\"action\" value=\"deny\">Deny</button>\n</form>\n",
+            intro,
+            message,
+            escape_html(user_code.unwrap_or_default())
+        ),
+    )
+}
+
+fn login_page(
+    client: &OAuthClient,
+    request: &AuthorizeRequest,
+    status: StatusCode,
+    message: Option<&str>,
+) -> HttpResponse {
+    let hidden = hidden_fields(request);
+    let message = message
+        .map(|message| format!("<p role=\"alert\">{}</p>\n", escape_html(message)))
+        .unwrap_or_default();
+
+    html_response(
+        status,
+        format!(
+            "<h1>Sign in to continue to {}</h1>\n{}\
+             <form method=\"post\" action=\"authorize\">\n{}\
+             <label>Username <input name=\"username\" autocomplete=\"username\" required></label>\n\
+             <label>Password <input name=\"password\" type=\"password\" autocomplete=\"current-password\" required></label>\n\
+             <button type=\"submit\">Sign in</button>\n</form>\n",
+            escape_html(&client.name),
+            message,
+            hidden
+        ),
+    )
+}
+
+/// Asks the user whether the client may have the scopes it asked for.
+fn consent_page(client: &OAuthClient, request: &AuthorizeRequest, ticket: &str) -> HttpResponse {
+    let mut scopes: Vec<String> = request
+        .scope
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|scope| format!("<li>{}</li>\n", describe_scope(scope)))
+        .collect();
+    if scopes.is_empty() {
+        scopes.push(format!("<li>{}</li>\n", describe_scope(SCOPE_OPENID)));
+    }
+
+    html_response(
+        StatusCode::OK,
+        format!(
+            "<h1>{} wants to access your account</h1>\n<p>It will be able to:</p>\n<ul>\n{}</ul>\n\
+             <form method=\"post\" action=\"authorize/consent\">\n{}\
+             <input type=\"hidden\" name=\"consent_ticket\" value=\"{}\">\n\
+             <button type=\"submit\" name=\"action\" value=\"approve\">Allow</button>\n\
+             <button type=\"submit\" name=\"action\" value=\"deny\">Deny</button>\n</form>\n",
+            escape_html(&client.name),
+            scopes.concat(),
+            hidden_fields(request),
+            escape_html(ticket)
+        ),
+    )
+}
+
+fn describe_scope(scope: &str) -> String {
+    match scope {
+        SCOPE_OPENID => "Confirm who you are".to_string(),
+        SCOPE_PROFILE => "See your name and username".to_string(),
+        SCOPE_EMAIL => "See your email address".to_string(),
+        SCOPE_BIRTHDATE => "See your date of birth".to_string(),
+        scope => format!("Use the <code>{}</code> permission", escape_html(scope)),
+    }
+}
+
+/// The authorization request, carried through the sign-in and consent
+/// forms. A pushed or signed request travels as it came, so its
+/// parameters cannot be changed on the way.
+fn hidden_fields(request: &AuthorizeRequest) -> String {
+    let pushed_or_signed = [
+        ("client_id", &request.client_id),
+        ("request_uri", &request.request_uri),
+        ("request", &request.request),
+    ];
+    let plain = [
+        ("response_type", &request.response_type),
+        ("client_id", &request.client_id),
+        ("redirect_uri", &request.redirect_uri),
+        ("scope", &request.scope),
+        ("state", &request.state),
+        ("code_challenge", &request.code_challenge),
+        ("code_challenge_method", &request.code_challenge_method),
+        ("nonce", &request.nonce),
+        ("prompt", &request.prompt),
+    ];
+    let fields: &[_] = if request.request_uri.is_some() || request.request.is_some() {
+        &pushed_or_signed
+    } else {
+        &plain
+    };
+    fields
+        .iter()
+        .filter_map(|(name, value)| {
+            value.as_deref().map(|value| {
+                format!(
+                    "<input type=\"hidden\" name=\"{}\" value=\"{}\">\n",
+                    name,
+                    escape_html(value)
+                )
+            })
+        })
+        .collect()
+}
+
+/// Wraps a page body. The pages take passwords, so they are never cached
+/// and never framed.
+fn html_response(status: StatusCode, body: String) -> HttpResponse {
+    let body = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Sign in</title></head>\n<body>\n{}</body>\n</html>\n",
+        body
+    );
+
+    HttpResponse::build(status)
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .insert_header(("X-Frame-Options", "DENY"))
+        .content_type(ContentType::html())
+        .body(body)
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,156 @@
+use actix_web::{http::header::ContentType, web, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    api::extractors::AdminPrincipal,
+    application::use_cases::oauth_client::{OAuthClientError, OAuthClientUseCase},
+    dto::{
+        error::ErrorResponse,
+        oauth_client_dto::{
+            ClientListResponse, ClientResponse, ClientSecretResponse, CreateClientRequest,
+            DeleteClientResponse, UpdateClientRequest,
+        },
+    },
+    infrastructure::repositories::postgres_oauth_client_repo::PostgresOAuthClientRepository,
+};
+
+pub type PostgresOAuthClientUseCase = OAuthClientUseCase<PostgresOAuthClientRepository>;
+
+pub fn oauth_client_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(find_all))
+            .route(web::post().to(create)),
+    );
+    cfg.service(
+        web::resource("/{client_id}")
+            .route(web::get().to(find_by_id))
+            .route(web::put().to(update))
+            .route(web::delete().to(delete)),
+    );
+    cfg.service(web::resource("/{client_id}/secret").route(web::post().to(rotate_secret)));
+}
+
+fn client_error_response(err: OAuthClientError) -> HttpResponse {
+    let mut response = match &err {
+        OAuthClientError::Invalid(_) | OAuthClientError::PublicClient => HttpResponse::BadRequest(),
+        OAuthClientError::NotFound => HttpResponse::NotFound(),
+        OAuthClientError::Internal(_) => HttpResponse::InternalServerError(),
+    };
+
+    response
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: err.to_string(),
+        })
+}
+
+async fn find_all(
+    use_case: web::Data<PostgresOAuthClientUseCase>,
+    _admin: AdminPrincipal,
+) -> HttpResponse {
+    match use_case.get_ref().find_all().await {
+        Ok(clients) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(ClientListResponse { data: clients }),
+        Err(err) => client_error_response(err),
+    }
+}
+
+async fn create(
+    use_case: web::Data<PostgresOAuthClientUseCase>,
+    admin: AdminPrincipal,
+    req_body: web::Json<CreateClientRequest>,
+) -> HttpResponse {
+    let AdminPrincipal(admin) = admin;
+
+    match use_case
+        .get_ref()
+        .create(req_body.into_inner(), &admin)
+        .await
+    {
+        Ok((client, client_secret)) => HttpResponse::Created()
+            .content_type(ContentType::json())
+            .json(ClientSecretResponse {
+                data: client,
+                client_secret,
+            }),
+        Err(err) => client_error_response(err),
+    }
+}
+
+async fn find_by_id(
+    use_case: web::Data<PostgresOAuthClientUseCase>,
+    _admin: AdminPrincipal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    match use_case.get_ref().find_by_id(path.into_inner()).await {
+        Ok(client) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(ClientResponse { data: client }),
+        Err(err) => client_error_response(err),
+    }
+}
+
+async fn update(
+    use_case: web::Data<PostgresOAuthClientUseCase>,
+    admin: AdminPrincipal,
+    path: web::Path<Uuid>,
+    req_body: web::Json<UpdateClientRequest>,
+) -> HttpResponse {
+    let AdminPrincipal(admin) = admin;
+
+    match use_case
+        .get_ref()
+        .update(path.into_inner(), req_body.into_inner(), &admin)
+        .await
+    {
+        Ok(client) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(ClientResponse { data: client }),
+        Err(err) => client_error_response(err),
+    }
+}
+
+async fn delete(
+    use_case: web::Data<PostgresOAuthClientUseCase>,
+    admin: AdminPrincipal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let AdminPrincipal(admin) = admin;
+
+    match use_case.get_ref().delete(path.into_inner(), &admin).await {
+        Ok(()) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(DeleteClientResponse {
+                message: "Client Deleted Successfully".to_string(),
+            }),
+        Err(err) => client_error_response(err),
+    }
+}
+
+/// Issues a new secret for a confidential client. The previous one stays
+/// valid for the configured overlap.
+async fn rotate_secret(
+    use_case: web::Data<PostgresOAuthClientUseCase>,
+    admin: AdminPrincipal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let AdminPrincipal(admin) = admin;
+
+    match use_case
+        .get_ref()
+        .rotate_secret(path.into_inner(), &admin)
+        .await
+    {
+        Ok((client, client_secret)) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(ClientSecretResponse {
+                    data: client,
+                    client_secret: Some(client_secret),
+                })
+        }
+        Err(err) => client_error_response(err),
+    }
+}
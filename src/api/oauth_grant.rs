@@ -0,0 +1,78 @@
+use actix_web::{http::header::ContentType, web, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    application::use_cases::oauth_grant::OAuthGrantUseCase,
+    domain::principal::Principal,
+    dto::{error::ErrorResponse, oauth_grant_dto::GrantListResponse},
+    infrastructure::repositories::{
+        postgres_oauth_grant_repo::PostgresOAuthGrantRepository,
+        postgres_refresh_token_repo::PostgresRefreshTokenRepository,
+    },
+};
+
+pub type PostgresOAuthGrantUseCase =
+    OAuthGrantUseCase<PostgresOAuthGrantRepository, PostgresRefreshTokenRepository>;
+
+/// Applications a user has granted access to, under `/users`.
+pub fn oauth_grant_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/{user_id}/grants").route(web::get().to(list)));
+    cfg.service(web::resource("/{user_id}/grants/{client_id}").route(web::delete().to(revoke)));
+}
+
+async fn list(
+    use_case: web::Data<PostgresOAuthGrantUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match use_case.get_ref().list(user_id).await {
+        Ok(data) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(GrantListResponse { data }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+/// Takes access away from an application. Its refresh tokens stop working
+/// at once and the next authorization asks the user again.
+async fn revoke(
+    use_case: web::Data<PostgresOAuthGrantUseCase>,
+    principal: Principal,
+    path: web::Path<(Uuid, Uuid)>,
+) -> HttpResponse {
+    let (user_id, client_id) = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match use_case.get_ref().revoke(user_id, client_id).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Grant Not Found".to_string(),
+            }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Grants Can Only Be Managed By The Account Owner Or An Admin".to_string(),
+        })
+}
@@ -0,0 +1,157 @@
+use actix_web::{
+    cookie::{time::Duration, Cookie, SameSite},
+    http::header::ContentType,
+    web, HttpRequest, HttpResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        auth::{auth_error_response, login_outcome_response, PostgresAuthUseCase},
+        session::PostgresSessionUseCase,
+    },
+    application::use_cases::{auth::AuthError, passwordless::PasswordlessUseCase},
+    domain::{auth_context::AMR_EMAIL, client::ClientContext},
+    dto::{
+        auth_dto::{
+            PasswordlessLinkQuery, PasswordlessStartRequest, PasswordlessStartResponse,
+            PasswordlessVerifyRequest,
+        },
+        session_dto::{LoginMode, LoginQuery},
+    },
+    infrastructure::repositories::{
+        postgres_login_event_repo::PostgresLoginEventRepository,
+        postgres_one_time_code_repo::PostgresOneTimeCodeRepository,
+        postgres_user_repo::PostgresUserRepository,
+    },
+};
+
+pub type PostgresPasswordlessUseCase = PasswordlessUseCase<
+    PostgresUserRepository,
+    PostgresOneTimeCodeRepository,
+    PostgresLoginEventRepository,
+>;
+
+/// Ties a code or link to the browser that asked for it.
+pub const BINDING_COOKIE: &str = "passwordless_binding";
+
+pub fn passwordless_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/passwordless").route(web::post().to(start)));
+    cfg.service(web::resource("/passwordless/verify").route(web::post().to(verify_code)));
+    cfg.service(web::resource("/passwordless/link").route(web::get().to(verify_link)));
+}
+
+fn binding_cookie(value: String, max_age: i64, secure: bool) -> Cookie<'static> {
+    Cookie::build(BINDING_COOKIE, value)
+        .path("/")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Lax)
+        .max_age(Duration::seconds(max_age))
+        .finish()
+}
+
+async fn start(
+    use_case: web::Data<PostgresPasswordlessUseCase>,
+    client: ClientContext,
+    req_body: web::Json<PasswordlessStartRequest>,
+) -> HttpResponse {
+    let PasswordlessStartRequest { email, method } = req_body.into_inner();
+
+    match use_case.get_ref().start(&email, method, &client).await {
+        Ok(challenge) => HttpResponse::Accepted()
+            .cookie(binding_cookie(
+                challenge.binding,
+                challenge.expires_in,
+                use_case.get_ref().secure_cookie(),
+            ))
+            .content_type(ContentType::json())
+            .json(PasswordlessStartResponse {
+                flow_id: challenge.flow_id,
+                expires_in: challenge.expires_in,
+            }),
+        Err(err) => auth_error_response(err),
+    }
+}
+
+async fn verify_code(
+    use_case: web::Data<PostgresPasswordlessUseCase>,
+    auth_use_case: web::Data<PostgresAuthUseCase>,
+    sessions: web::Data<PostgresSessionUseCase>,
+    client: ClientContext,
+    req: HttpRequest,
+    mode: web::Query<LoginQuery>,
+    req_body: web::Json<PasswordlessVerifyRequest>,
+) -> HttpResponse {
+    let binding = req.cookie(BINDING_COOKIE);
+    let binding = binding.as_ref().map(Cookie::value).unwrap_or_default();
+
+    let result = use_case
+        .get_ref()
+        .verify_code(req_body.flow_id, &req_body.code, binding, &client)
+        .await;
+    complete(
+        use_case.get_ref(),
+        auth_use_case.get_ref(),
+        sessions.get_ref(),
+        mode.mode,
+        &client,
+        result,
+    )
+    .await
+}
+
+async fn verify_link(
+    use_case: web::Data<PostgresPasswordlessUseCase>,
+    auth_use_case: web::Data<PostgresAuthUseCase>,
+    sessions: web::Data<PostgresSessionUseCase>,
+    client: ClientContext,
+    req: HttpRequest,
+    mode: web::Query<LoginQuery>,
+    query: web::Query<PasswordlessLinkQuery>,
+) -> HttpResponse {
+    let binding = req.cookie(BINDING_COOKIE);
+    let binding = binding.as_ref().map(Cookie::value).unwrap_or_default();
+
+    let result = use_case
+        .get_ref()
+        .verify_link(&query.token, binding, &client)
+        .await;
+    complete(
+        use_case.get_ref(),
+        auth_use_case.get_ref(),
+        sessions.get_ref(),
+        mode.mode,
+        &client,
+        result,
+    )
+    .await
+}
+
+/// Turns a redeemed code into a login. The binding cookie is kept after a
+/// wrong code so the user can try again, and dropped once the code is used.
+async fn complete(
+    use_case: &PostgresPasswordlessUseCase,
+    auth_use_case: &PostgresAuthUseCase,
+    sessions: &PostgresSessionUseCase,
+    mode: LoginMode,
+    client: &ClientContext,
+    result: Result<Uuid, AuthError>,
+) -> HttpResponse {
+    let user_id = match result {
+        Ok(user_id) => user_id,
+        Err(err) => return auth_error_response(err),
+    };
+
+    let mut response = match auth_use_case
+        .complete_login(user_id, AMR_EMAIL, client)
+        .await
+    {
+        Ok(outcome) => login_outcome_response(auth_use_case, sessions, mode, client, outcome).await,
+        Err(err) => auth_error_response(err),
+    };
+    let _ =
+        response.add_removal_cookie(&binding_cookie(String::new(), 0, use_case.secure_cookie()));
+
+    response
+}
@@ -0,0 +1,152 @@
+use actix_web::{
+    http::header::{self, ContentType},
+    web, HttpResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    application::use_cases::phone::{PhoneError, PhoneUseCase},
+    domain::principal::Principal,
+    dto::{
+        error::ErrorResponse,
+        user_dto::{PhoneNumberRequest, PhoneNumberResponse, PhoneResponse, PhoneVerifyRequest},
+    },
+    infrastructure::repositories::{
+        postgres_one_time_code_repo::PostgresOneTimeCodeRepository,
+        postgres_user_repo::PostgresUserRepository,
+    },
+};
+
+pub type PostgresPhoneUseCase = PhoneUseCase<PostgresUserRepository, PostgresOneTimeCodeRepository>;
+
+pub fn phone_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/{user_id}/phone")
+            .route(web::put().to(set_phone_number))
+            .route(web::delete().to(remove_phone_number)),
+    );
+    cfg.service(web::resource("/{user_id}/phone/resend").route(web::post().to(resend)));
+    cfg.service(web::resource("/{user_id}/phone/verify").route(web::post().to(verify)));
+}
+
+fn phone_error_response(err: PhoneError) -> HttpResponse {
+    let mut response = match &err {
+        PhoneError::InvalidPhoneNumber | PhoneError::InvalidCode => HttpResponse::BadRequest(),
+        PhoneError::UserNotFound | PhoneError::NoPhoneNumber => HttpResponse::NotFound(),
+        PhoneError::Throttled(retry_after) => {
+            let mut response = HttpResponse::TooManyRequests();
+            response.insert_header((
+                header::RETRY_AFTER,
+                retry_after.as_secs().max(1).to_string(),
+            ));
+            response
+        }
+        PhoneError::Internal(_) => HttpResponse::InternalServerError(),
+    };
+
+    response
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: err.to_string(),
+        })
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Phone Number Can Only Be Changed By The Account Owner".to_string(),
+        })
+}
+
+async fn set_phone_number(
+    use_case: web::Data<PostgresPhoneUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+    req_body: web::Json<PhoneNumberRequest>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case
+        .get_ref()
+        .set_phone_number(user_id, &req_body.phone_number)
+        .await
+    {
+        Ok(phone_number) => HttpResponse::Accepted()
+            .content_type(ContentType::json())
+            .json(PhoneNumberResponse {
+                phone_number,
+                expires_in: use_case.get_ref().code_ttl(),
+            }),
+        Err(err) => phone_error_response(err),
+    }
+}
+
+async fn resend(
+    use_case: web::Data<PostgresPhoneUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case.get_ref().resend_verification(user_id).await {
+        Ok(()) => HttpResponse::Accepted()
+            .content_type(ContentType::json())
+            .json(PhoneResponse {
+                message: "Verification Code Sent".to_string(),
+            }),
+        Err(err) => phone_error_response(err),
+    }
+}
+
+async fn verify(
+    use_case: web::Data<PostgresPhoneUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+    req_body: web::Json<PhoneVerifyRequest>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case
+        .get_ref()
+        .verify_phone_number(user_id, &req_body.code)
+        .await
+    {
+        Ok(()) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(PhoneResponse {
+                message: "Phone Number Verified".to_string(),
+            }),
+        Err(err) => phone_error_response(err),
+    }
+}
+
+async fn remove_phone_number(
+    use_case: web::Data<PostgresPhoneUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case.get_ref().remove_phone_number(user_id).await {
+        Ok(true) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(PhoneResponse {
+                message: "Phone Number Removed".to_string(),
+            }),
+        Ok(false) => phone_error_response(PhoneError::NoPhoneNumber),
+        Err(err) => phone_error_response(PhoneError::Internal(err)),
+    }
+}
@@ -0,0 +1,55 @@
+use actix_web::{http::header::ContentType, web, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    application::use_cases::login_event::{LoginEventUseCase, MAX_PAGE_SIZE},
+    domain::principal::Principal,
+    dto::{
+        error::ErrorResponse,
+        login_event_dto::{SecurityEventsQuery, SecurityEventsResponse},
+    },
+    infrastructure::repositories::postgres_login_event_repo::PostgresLoginEventRepository,
+};
+
+pub type PostgresLoginEventUseCase = LoginEventUseCase<PostgresLoginEventRepository>;
+
+pub fn security_event_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/{user_id}/security-events").route(web::get().to(list)));
+}
+
+async fn list(
+    use_case: web::Data<PostgresLoginEventUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+    query: web::Query<SecurityEventsQuery>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return HttpResponse::Forbidden()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Security Events Can Only Be Viewed By The Account Owner Or An Admin"
+                    .to_string(),
+            });
+    }
+
+    let page = query.page.max(1);
+    let per_page = query.per_page.clamp(1, MAX_PAGE_SIZE);
+    match use_case.get_ref().list(user_id, page, per_page).await {
+        Ok((data, total)) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(SecurityEventsResponse {
+                    data,
+                    page,
+                    per_page,
+                    total,
+                })
+        }
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
@@ -0,0 +1,252 @@
+use actix_web::{
+    cookie::{time::Duration, Cookie, SameSite},
+    http::header::ContentType,
+    web, HttpRequest, HttpResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    application::use_cases::session::SessionUseCase,
+    config::SessionConfig,
+    domain::{client::ClientContext, principal::Principal, session::Session},
+    dto::{
+        auth_dto::LoginResponse,
+        error::ErrorResponse,
+        session_dto::{
+            LoginMode, RevokeSessionsResponse, SessionListResponse, SessionResponse, SessionSummary,
+        },
+    },
+    infrastructure::repositories::postgres_session_repo::PostgresSessionRepository,
+    util::token::IssuedToken,
+};
+
+pub type PostgresSessionUseCase = SessionUseCase<PostgresSessionRepository>;
+
+/// Header carrying the session's CSRF token on state-changing requests.
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+
+pub fn session_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/session")
+            .route(web::get().to(current))
+            .route(web::delete().to(logout)),
+    );
+}
+
+/// Session management on behalf of a user, mounted under `/users`.
+pub fn user_session_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/{user_id}/sessions")
+            .route(web::get().to(list))
+            .route(web::delete().to(revoke_all)),
+    );
+    cfg.service(web::resource("/{user_id}/sessions/{session_id}").route(web::delete().to(revoke)));
+}
+
+fn session_cookie(config: &SessionConfig, value: String, max_age: i64) -> Cookie<'static> {
+    let same_site = match config.samesite.to_ascii_lowercase().as_str() {
+        "strict" => SameSite::Strict,
+        "none" => SameSite::None,
+        _ => SameSite::Lax,
+    };
+    let mut cookie = Cookie::build(config.name.clone(), value)
+        .path("/")
+        .http_only(true)
+        .secure(config.secure)
+        .same_site(same_site)
+        .max_age(Duration::seconds(max_age))
+        .finish();
+    if !config.domain.is_empty() {
+        cookie.set_domain(config.domain.clone());
+    }
+    cookie
+}
+
+/// Tells the browser to drop the session cookie.
+pub fn removal_cookie(config: &SessionConfig) -> Cookie<'static> {
+    session_cookie(config, String::new(), 0)
+}
+
+fn session_response(session: &Session, idle: i64) -> SessionResponse {
+    SessionResponse {
+        user_id: session.user_id,
+        csrf_token: session.csrf_token.clone(),
+        expires_at: session.valid_until(idle),
+    }
+}
+
+fn session_unavailable() -> HttpResponse {
+    HttpResponse::InternalServerError()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Session Could Not Be Started".to_string(),
+        })
+}
+
+/// Answers a successful sign-in with either the bearer token or, in session
+/// mode, a session cookie carrying the same user, roles and auth context.
+pub async fn issued_response(
+    sessions: &PostgresSessionUseCase,
+    issued: IssuedToken,
+    mode: LoginMode,
+    client: &ClientContext,
+) -> HttpResponse {
+    if mode == LoginMode::Token {
+        return HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(LoginResponse::from(issued));
+    }
+
+    let claims = issued.claims;
+    match sessions
+        .create(claims.sub, &claims.roles, &claims.auth, client)
+        .await
+    {
+        Ok((token, session)) => {
+            let config = sessions.config();
+            HttpResponse::Ok()
+                .cookie(session_cookie(config, token, config.ttl))
+                .content_type(ContentType::json())
+                .json(session_response(&session, config.idle))
+        }
+        Err(err) => {
+            tracing::error!(user_id = %claims.sub, error = %err, "failed to start session");
+            session_unavailable()
+        }
+    }
+}
+
+async fn current(sessions: web::Data<PostgresSessionUseCase>, req: HttpRequest) -> HttpResponse {
+    let config = sessions.get_ref().config();
+    let session = match req.cookie(&config.name) {
+        Some(cookie) => sessions.get_ref().authenticate(cookie.value()).await,
+        None => Ok(None),
+    };
+
+    match session {
+        Ok(Some(session)) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(session_response(&session, config.idle)),
+        Ok(None) => HttpResponse::Unauthorized()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "No Active Session".to_string(),
+            }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+/// Ends the cookie session. Going through [`Principal`] means the CSRF
+/// token is checked first.
+async fn logout(
+    sessions: web::Data<PostgresSessionUseCase>,
+    _principal: Principal,
+    req: HttpRequest,
+) -> HttpResponse {
+    let config = sessions.get_ref().config();
+    if let Some(cookie) = req.cookie(&config.name) {
+        if let Err(err) = sessions.get_ref().destroy(cookie.value()).await {
+            return HttpResponse::InternalServerError()
+                .content_type(ContentType::json())
+                .json(ErrorResponse {
+                    message: err.to_string(),
+                });
+        }
+    }
+
+    let mut response = HttpResponse::NoContent().finish();
+    let _ = response.add_removal_cookie(&removal_cookie(config));
+    response
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Sessions Can Only Be Managed By The Account Owner Or An Admin".to_string(),
+        })
+}
+
+async fn list(
+    sessions: web::Data<PostgresSessionUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match sessions.get_ref().list(user_id).await {
+        Ok(found) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(SessionListResponse {
+                    data: found
+                        .into_iter()
+                        .map(|session| {
+                            let current = session.id == principal.token_id;
+                            SessionSummary::new(session, current)
+                        })
+                        .collect(),
+                })
+        }
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+async fn revoke(
+    sessions: web::Data<PostgresSessionUseCase>,
+    principal: Principal,
+    path: web::Path<(Uuid, Uuid)>,
+) -> HttpResponse {
+    let (user_id, session_id) = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match sessions.get_ref().revoke(user_id, session_id).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Session Not Found".to_string(),
+            }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+/// Signs the user out everywhere, including the session making the request.
+async fn revoke_all(
+    sessions: web::Data<PostgresSessionUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match sessions.get_ref().revoke_all(user_id).await {
+        Ok(revoked) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(RevokeSessionsResponse { revoked }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
@@ -1,20 +1,29 @@
-use actix_web::{http::header::ContentType, web, HttpResponse};
+use actix_web::{http::header::ContentType, web, HttpResponse, ResponseError};
+use std::error::Error;
 use uuid::Uuid;
 
 use crate::{
+    api::{
+        auth::PostgresAuthUseCase,
+        extractors::AdminPrincipal,
+        middleware::step_up::{require_step_up, StepUp},
+    },
     application::use_cases::user::UserUseCase,
-    config::AppConfig,
+    domain::{auth_context::StepUpRequirement, principal::Principal},
     dto::{
         error::ErrorResponse,
         user_dto::{
             CreateRequest, CreateResponse, DeleteResponse, FindAllResponse, FindByIdResponse,
-            UpdateRequest,
+            ImportRequest, UnlockResponse, UpdateRequest,
         },
     },
     infrastructure::repositories::postgres_user_repo::PostgresUserRepository,
+    util::{pwd::HashScheme, pwd_pool::PwdPoolError},
 };
 
-use std::sync::Arc;
+/// Deleting an account or changing its email needs a sign-in from the last
+/// five minutes.
+const SENSITIVE_CHANGE: StepUpRequirement = StepUpRequirement::recent(300);
 
 pub fn user_cfg(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -22,12 +31,14 @@ pub fn user_cfg(cfg: &mut web::ServiceConfig) {
             .route(web::get().to(find_all_user))
             .route(web::post().to(create_new_user)),
     );
+    cfg.service(web::resource("/import").route(web::post().to(import_user)));
     cfg.service(
         web::resource("/{user_id}")
             .route(web::get().to(find_by_id))
             .route(web::put().to(update))
-            .route(web::delete().to(delete)),
+            .route(web::delete().to(delete).wrap(StepUp::new(SENSITIVE_CHANGE))),
     );
+    cfg.service(web::resource("/{user_id}/unlock").route(web::post().to(unlock)));
 }
 
 async fn find_all_user(use_case: web::Data<UserUseCase<PostgresUserRepository>>) -> HttpResponse {
@@ -43,13 +54,58 @@ async fn find_all_user(use_case: web::Data<UserUseCase<PostgresUserRepository>>)
 
 async fn create_new_user(
     use_case: web::Data<UserUseCase<PostgresUserRepository>>,
-    cfg: web::Data<Arc<AppConfig>>,
     req_body: web::Json<CreateRequest>,
 ) -> HttpResponse {
-    let pwd_cfg = cfg.pwd.clone();
     let new_user = req_body.into_inner();
 
-    match use_case.get_ref().create(new_user, pwd_cfg).await {
+    match use_case.get_ref().create(new_user).await {
+        Ok(user_id) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(CreateResponse { data: user_id }),
+        Err(err) if is_pwd_pool_busy(err.as_ref()) => HttpResponse::ServiceUnavailable()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Only The Account Owner Or An Admin Can Change This User".to_string(),
+        })
+}
+
+fn is_pwd_pool_busy(err: &(dyn Error + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<PwdPoolError>(),
+        Some(PwdPoolError::QueueTimeout)
+    )
+}
+
+async fn import_user(
+    use_case: web::Data<UserUseCase<PostgresUserRepository>>,
+    _admin: AdminPrincipal,
+    req_body: web::Json<ImportRequest>,
+) -> HttpResponse {
+    let imported_user = req_body.into_inner();
+
+    if HashScheme::identify(&imported_user.password_hash).is_none() {
+        return HttpResponse::BadRequest()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Unsupported Password Hash Format".to_string(),
+            });
+    }
+
+    match use_case.get_ref().import(imported_user).await {
         Ok(user_id) => HttpResponse::Ok()
             .content_type(ContentType::json())
             .json(CreateResponse { data: user_id }),
@@ -85,12 +141,32 @@ async fn find_by_id(
 
 async fn update(
     use_case: web::Data<UserUseCase<PostgresUserRepository>>,
+    principal: Principal,
     path: web::Path<Uuid>,
     req_body: web::Json<UpdateRequest>,
 ) -> HttpResponse {
     let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
     let update_data = req_body.into_inner();
 
+    match use_case.get_ref().find_by_id(user_id).await {
+        Ok(Some(user)) if !user.email.eq_ignore_ascii_case(&update_data.email) => {
+            if let Err(err) = require_step_up(&principal, SENSITIVE_CHANGE) {
+                return err.error_response();
+            }
+        }
+        Ok(_) => {}
+        Err(err) => {
+            return HttpResponse::InternalServerError()
+                .content_type(ContentType::json())
+                .json(ErrorResponse {
+                    message: err.to_string(),
+                })
+        }
+    }
+
     match use_case.get_ref().update(user_id, update_data).await {
         Ok(Some(user)) => HttpResponse::Ok()
             .content_type(ContentType::json())
@@ -110,9 +186,13 @@ async fn update(
 
 async fn delete(
     use_case: web::Data<UserUseCase<PostgresUserRepository>>,
+    principal: Principal,
     path: web::Path<Uuid>,
 ) -> HttpResponse {
     let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
 
     match use_case.get_ref().delete(user_id).await {
         Ok(1_u64..) => HttpResponse::Ok()
@@ -132,3 +212,253 @@ async fn delete(
             }),
     }
 }
+
+async fn unlock(
+    auth_use_case: web::Data<PostgresAuthUseCase>,
+    admin: AdminPrincipal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    let AdminPrincipal(admin) = admin;
+
+    match auth_use_case.get_ref().unlock(user_id, &admin).await {
+        Ok(true) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(UnlockResponse {
+                message: "User Unlocked Successfully".to_string(),
+            }),
+        Ok(false) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "User Not Locked".to_string(),
+            }),
+        Err(err) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: err.to_string(),
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        application::repositories::user_repository::UserRepository,
+        config::{PwdConfig, TokenConfig},
+        domain::auth_context::{AuthContext, AMR_PASSWORD},
+        infrastructure::repositories::test_support::{create_user, setup_database},
+        util::{pwd_pool::PwdPool, token::TokenService},
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{call_service, init_service, read_body, TestRequest},
+        App,
+    };
+
+    #[actix_web::test]
+    async fn test_users_cannot_change_other_accounts() {
+        let pool = setup_database().await;
+        sqlx::query("DELETE FROM users WHERE username IN ('apiowner', 'apiother')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let owner = create_user(&pool, "apiowner").await;
+        let other = create_user(&pool, "apiother").await;
+
+        let tokens = web::Data::new(TokenService::new(TokenConfig::default()));
+        let app = init_service(
+            App::new()
+                .app_data(tokens.clone())
+                .app_data(web::Data::new(UserUseCase::new(
+                    PostgresUserRepository::new(pool.clone()),
+                    Arc::new(PwdPool::new(PwdConfig::default())),
+                )))
+                .service(web::scope("/users").configure(user_cfg)),
+        )
+        .await;
+        let bearer = |user_id: Uuid| {
+            let token = tokens
+                .issue_access_token(user_id, Vec::new(), AuthContext::new(&[AMR_PASSWORD]))
+                .unwrap()
+                .token;
+            (header::AUTHORIZATION, format!("Bearer {}", token))
+        };
+        let update = |username: &str| UpdateRequest {
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            first_name: Some("Changed".to_string()),
+            last_name: None,
+            date_of_birth: None,
+        };
+
+        let res = call_service(
+            &app,
+            TestRequest::put()
+                .uri(&format!("/users/{}", owner))
+                .set_json(update("apiowner"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+
+        let res = call_service(
+            &app,
+            TestRequest::put()
+                .uri(&format!("/users/{}", owner))
+                .insert_header(bearer(other))
+                .set_json(update("apiowner"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = call_service(
+            &app,
+            TestRequest::delete()
+                .uri(&format!("/users/{}", owner))
+                .insert_header(bearer(other))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = call_service(
+            &app,
+            TestRequest::put()
+                .uri(&format!("/users/{}", owner))
+                .insert_header(bearer(owner))
+                .set_json(update("apiowner"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, res.status());
+
+        let res = call_service(
+            &app,
+            TestRequest::delete()
+                .uri(&format!("/users/{}", owner))
+                .insert_header(bearer(owner))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, res.status());
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(other)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_import_requires_admin() {
+        let pool = setup_database().await;
+        sqlx::query("DELETE FROM users WHERE username = 'apiimported'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let tokens = web::Data::new(TokenService::new(TokenConfig::default()));
+        let app = init_service(
+            App::new()
+                .app_data(tokens.clone())
+                .app_data(web::Data::new(UserUseCase::new(
+                    PostgresUserRepository::new(pool.clone()),
+                    Arc::new(PwdPool::new(PwdConfig::default())),
+                )))
+                .service(web::scope("/users").configure(user_cfg)),
+        )
+        .await;
+        let bearer = |roles: Vec<String>| {
+            let token = tokens
+                .issue_access_token(Uuid::new_v4(), roles, AuthContext::new(&[AMR_PASSWORD]))
+                .unwrap()
+                .token;
+            (header::AUTHORIZATION, format!("Bearer {}", token))
+        };
+        let import = || {
+            TestRequest::post()
+                .uri("/users/import")
+                .set_json(ImportRequest {
+                    username: "apiimported".to_string(),
+                    email: "apiimported@example.com".to_string(),
+                    password_hash: bcrypt::hash("SuperDuperSecretPassword", 4).unwrap(),
+                    first_name: None,
+                    last_name: None,
+                    date_of_birth: None,
+                })
+        };
+
+        let res = call_service(&app, import().to_request()).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+
+        let res = call_service(
+            &app,
+            import().insert_header(bearer(Vec::new())).to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = call_service(
+            &app,
+            import()
+                .insert_header(bearer(vec!["admin".to_string()]))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, res.status());
+
+        sqlx::query("DELETE FROM users WHERE username = 'apiimported'")
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_user_reads_leave_out_the_phone_number() {
+        let pool = setup_database().await;
+        sqlx::query("DELETE FROM users WHERE username = 'apiphone'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let user_id = create_user(&pool, "apiphone").await;
+        let repository = PostgresUserRepository::new(pool.clone());
+        repository
+            .update_phone_number(user_id, Some("+14155550123"))
+            .await
+            .unwrap();
+        repository
+            .mark_phone_verified(user_id, "+14155550123")
+            .await
+            .unwrap();
+
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(UserUseCase::new(
+                    PostgresUserRepository::new(pool.clone()),
+                    Arc::new(PwdPool::new(PwdConfig::default())),
+                )))
+                .service(web::scope("/users").configure(user_cfg)),
+        )
+        .await;
+
+        for uri in ["/users".to_string(), format!("/users/{}", user_id)] {
+            let res = call_service(&app, TestRequest::get().uri(&uri).to_request()).await;
+            assert_eq!(StatusCode::OK, res.status());
+            let body = read_body(res).await;
+            let body = std::str::from_utf8(&body).unwrap();
+            assert!(body.contains("apiphone"));
+            assert!(!body.contains("phone_number"));
+            assert!(!body.contains("+14155550123"));
+        }
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+use actix_web::{http::header::ContentType, web, HttpResponse};
+use std::error::Error;
+use uuid::Uuid;
+
+use crate::{
+    application::use_cases::webauthn::WebauthnUseCase,
+    domain::principal::Principal,
+    dto::{
+        error::ErrorResponse,
+        webauthn_dto::{
+            CredentialResponse, DeleteCredentialResponse, FindCredentialResponse,
+            FindCredentialsResponse, RegisterCredentialRequest, RenameCredentialRequest,
+        },
+    },
+    infrastructure::repositories::{
+        postgres_user_repo::PostgresUserRepository,
+        postgres_webauthn_repo::PostgresWebauthnRepository,
+    },
+    util::webauthn::WebauthnError,
+};
+
+pub type PostgresWebauthnUseCase =
+    WebauthnUseCase<PostgresUserRepository, PostgresWebauthnRepository>;
+
+pub fn webauthn_cfg(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/{user_id}/webauthn/register/options")
+            .route(web::post().to(registration_options)),
+    );
+    cfg.service(web::resource("/{user_id}/webauthn/register").route(web::post().to(register)));
+    cfg.service(
+        web::resource("/{user_id}/webauthn/credentials").route(web::get().to(find_credentials)),
+    );
+    cfg.service(
+        web::resource("/{user_id}/webauthn/credentials/{credential_id}")
+            .route(web::patch().to(rename_credential))
+            .route(web::delete().to(delete_credential)),
+    );
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: "Forbidden".to_string(),
+        })
+}
+
+fn internal_error(err: Box<dyn Error>) -> HttpResponse {
+    HttpResponse::InternalServerError()
+        .content_type(ContentType::json())
+        .json(ErrorResponse {
+            message: err.to_string(),
+        })
+}
+
+async fn registration_options(
+    use_case: web::Data<PostgresWebauthnUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case.get_ref().registration_options(user_id).await {
+        Ok(Some(options)) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .json(options),
+        Ok(None) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "User Not Found".to_string(),
+            }),
+        Err(err) => internal_error(err),
+    }
+}
+
+async fn register(
+    use_case: web::Data<PostgresWebauthnUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+    req_body: web::Json<RegisterCredentialRequest>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if principal.user_id != user_id {
+        return forbidden();
+    }
+
+    match use_case
+        .get_ref()
+        .register(user_id, req_body.into_inner())
+        .await
+    {
+        Ok(credential) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(FindCredentialResponse {
+                    data: CredentialResponse::from(credential),
+                })
+        }
+        Err(err) => match err.downcast_ref::<WebauthnError>() {
+            Some(WebauthnError::AlreadyRegistered) => HttpResponse::Conflict()
+                .content_type(ContentType::json())
+                .json(ErrorResponse {
+                    message: err.to_string(),
+                }),
+            Some(_) => HttpResponse::BadRequest()
+                .content_type(ContentType::json())
+                .json(ErrorResponse {
+                    message: err.to_string(),
+                }),
+            None => internal_error(err),
+        },
+    }
+}
+
+async fn find_credentials(
+    use_case: web::Data<PostgresWebauthnUseCase>,
+    principal: Principal,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match use_case.get_ref().find_credentials(user_id).await {
+        Ok(credentials) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(FindCredentialsResponse {
+                    data: credentials
+                        .into_iter()
+                        .map(CredentialResponse::from)
+                        .collect(),
+                })
+        }
+        Err(err) => internal_error(err),
+    }
+}
+
+async fn rename_credential(
+    use_case: web::Data<PostgresWebauthnUseCase>,
+    principal: Principal,
+    path: web::Path<(Uuid, Uuid)>,
+    req_body: web::Json<RenameCredentialRequest>,
+) -> HttpResponse {
+    let (user_id, credential_id) = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+    if req_body.name.trim().is_empty() || req_body.name.len() > 100 {
+        return HttpResponse::BadRequest()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Name Must Be 1 To 100 Characters".to_string(),
+            });
+    }
+
+    match use_case
+        .get_ref()
+        .rename(user_id, credential_id, &req_body.name)
+        .await
+    {
+        Ok(Some(credential)) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(FindCredentialResponse {
+                    data: CredentialResponse::from(credential),
+                })
+        }
+        Ok(None) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Credential Not Found".to_string(),
+            }),
+        Err(err) => internal_error(err),
+    }
+}
+
+async fn delete_credential(
+    use_case: web::Data<PostgresWebauthnUseCase>,
+    principal: Principal,
+    path: web::Path<(Uuid, Uuid)>,
+) -> HttpResponse {
+    let (user_id, credential_id) = path.into_inner();
+    if !principal.can_access_user(user_id) {
+        return forbidden();
+    }
+
+    match use_case.get_ref().delete(user_id, credential_id).await {
+        Ok(1_u64..) => {
+            HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .json(DeleteCredentialResponse {
+                    message: "Credential Deleted Successfully".to_string(),
+                })
+        }
+        Ok(0) => HttpResponse::NotFound()
+            .content_type(ContentType::json())
+            .json(ErrorResponse {
+                message: "Credential Not Found".to_string(),
+            }),
+        Err(err) => internal_error(err),
+    }
+}
@@ -1,2 +1,3 @@
 pub mod repositories;
+pub mod services;
 pub mod use_cases;
@@ -0,0 +1,26 @@
+use std::error::Error;
+
+use crate::domain::oauth::{AuthorizationCode, NewAuthorizationCode};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait AuthorizationCodeRepository {
+    async fn create(&self, code: &NewAuthorizationCode) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn find_by_code_hash(
+        &self,
+        code_hash: &str,
+    ) -> Result<Option<AuthorizationCode>, Box<dyn Error>>;
+    /// Marks the code as exchanged and records the refresh token family the
+    /// exchange starts. Affects no rows when it already was.
+    async fn consume(&self, id: Uuid, family_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Records the access token issued for an exchanged code.
+    async fn record_access_token(
+        &self,
+        id: Uuid,
+        jti: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+}
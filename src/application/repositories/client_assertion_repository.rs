@@ -0,0 +1,18 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait ClientAssertionRepository {
+    /// Remembers a client assertion until it expires and drops the ones
+    /// that have. An assertion id the client already used affects no rows.
+    async fn record(
+        &self,
+        client_id: Uuid,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+}
@@ -0,0 +1,40 @@
+use std::error::Error;
+
+use crate::domain::{
+    auth_context::AuthContext,
+    oauth::{DeviceCode, NewDeviceCode},
+};
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait DeviceCodeRepository {
+    async fn create(&self, code: &NewDeviceCode) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn find_by_device_code_hash(
+        &self,
+        device_code_hash: &str,
+    ) -> Result<Option<DeviceCode>, Box<dyn Error>>;
+    async fn find_by_user_code_hash(
+        &self,
+        user_code_hash: &str,
+    ) -> Result<Option<DeviceCode>, Box<dyn Error>>;
+    /// Records the user's decision. Affects no rows unless the request is
+    /// still pending and unexpired.
+    async fn approve(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        auth: &AuthContext,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn deny(&self, id: Uuid, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Stores when the device polled and the interval it has to keep to.
+    async fn record_poll(
+        &self,
+        id: Uuid,
+        poll_interval: i32,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Marks an approved request as redeemed. Affects no rows the second
+    /// time.
+    async fn consume(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+}
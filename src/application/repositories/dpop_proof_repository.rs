@@ -0,0 +1,18 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+
+#[async_trait]
+pub trait DpopProofRepository {
+    /// Remembers a DPoP proof until it can no longer be accepted and drops
+    /// the ones that can't. A proof id already used with the same key
+    /// affects no rows.
+    async fn record(
+        &self,
+        jkt: &str,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+}
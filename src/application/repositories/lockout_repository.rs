@@ -0,0 +1,34 @@
+use std::error::Error;
+
+use crate::domain::lockout::{AccountLockout, LockoutEvent};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait LockoutRepository {
+    async fn find_by_user_id(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<AccountLockout>, Box<dyn Error>>;
+    async fn increment_failed_attempts(
+        &self,
+        user_id: Uuid,
+    ) -> Result<AccountLockout, Box<dyn Error>>;
+    async fn lock(
+        &self,
+        user_id: Uuid,
+        locked_until: DateTime<Utc>,
+    ) -> Result<AccountLockout, Box<dyn Error>>;
+    async fn reset(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn record_event(
+        &self,
+        user_id: Uuid,
+        event: &str,
+        ip_address: Option<&str>,
+        actor_id: Option<Uuid>,
+        locked_until: Option<DateTime<Utc>>,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn find_events(&self, user_id: Uuid) -> Result<Vec<LockoutEvent>, Box<dyn Error>>;
+}
@@ -0,0 +1,34 @@
+use std::error::Error;
+
+use crate::domain::{
+    login_event::{LoginEvent, NewLoginEvent},
+    login_risk::LoginHistory,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait LoginEventRepository {
+    async fn record(&self, event: &NewLoginEvent) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// A page of the user's events, newest first.
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<LoginEvent>, Box<dyn Error>>;
+    async fn count_by_user(&self, user_id: Uuid) -> Result<i64, Box<dyn Error>>;
+    /// Whether the user has signed in successfully from `device_hash` and
+    /// `country` before, and how many attempts failed since `since`.
+    async fn find_history(
+        &self,
+        user_id: Uuid,
+        device_hash: Option<&str>,
+        country: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<LoginHistory, Box<dyn Error>>;
+    /// Drops events recorded before `cutoff`.
+    async fn delete_before(&self, cutoff: DateTime<Utc>) -> Result<PgQueryResult, Box<dyn Error>>;
+}
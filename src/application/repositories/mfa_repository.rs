@@ -0,0 +1,39 @@
+use std::error::Error;
+
+use crate::domain::mfa::UserTotp;
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait MfaRepository {
+    async fn find_totp(&self, user_id: Uuid) -> Result<Option<UserTotp>, Box<dyn Error>>;
+    /// Stores an unconfirmed secret. Affects no rows when the user already
+    /// has a confirmed authenticator.
+    async fn save_pending_totp(
+        &self,
+        user_id: Uuid,
+        secret_ciphertext: &[u8],
+        secret_nonce: &[u8],
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Confirms the pending secret and replaces the user's recovery codes.
+    async fn confirm_totp(
+        &self,
+        user_id: Uuid,
+        step: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Records an accepted time step. Affects no rows when the step is not
+    /// newer than the last one, so a code cannot be replayed.
+    async fn record_totp_step(
+        &self,
+        user_id: Uuid,
+        step: i64,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn redeem_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn delete(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+}
@@ -1 +1,17 @@
+pub mod authorization_code_repository;
+pub mod client_assertion_repository;
+pub mod device_code_repository;
+pub mod dpop_proof_repository;
+pub mod lockout_repository;
+pub mod login_event_repository;
+pub mod mfa_repository;
+pub mod oauth_client_repository;
+pub mod oauth_grant_repository;
+pub mod one_time_code_repository;
+pub mod pushed_request_repository;
+pub mod rate_limit_store;
+pub mod refresh_token_repository;
+pub mod revoked_token_repository;
+pub mod session_repository;
 pub mod user_repository;
+pub mod webauthn_repository;
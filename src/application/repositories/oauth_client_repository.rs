@@ -0,0 +1,29 @@
+use std::error::Error;
+
+use crate::domain::oauth::{NewOAuthClient, OAuthClient};
+use crate::dto::oauth_client_dto::UpdateClientRequest;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait OAuthClientRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<OAuthClient>, Box<dyn Error>>;
+    async fn find_all(&self) -> Result<Vec<OAuthClient>, Box<dyn Error>>;
+    async fn create(&self, client: &NewOAuthClient) -> Result<OAuthClient, Box<dyn Error>>;
+    async fn update(
+        &self,
+        id: Uuid,
+        data: &UpdateClientRequest,
+    ) -> Result<Option<OAuthClient>, Box<dyn Error>>;
+    /// Replaces the secret, keeping the current one as the previous secret
+    /// until `previous_expires_at`.
+    async fn rotate_secret(
+        &self,
+        id: Uuid,
+        secret_hash: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> Result<Option<OAuthClient>, Box<dyn Error>>;
+    async fn delete(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+}
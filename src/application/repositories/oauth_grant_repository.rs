@@ -0,0 +1,26 @@
+use std::error::Error;
+
+use crate::domain::oauth::OAuthGrant;
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait OAuthGrantRepository {
+    async fn find(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<Option<OAuthGrant>, Box<dyn Error>>;
+    async fn find_by_user(&self, user_id: Uuid) -> Result<Vec<OAuthGrant>, Box<dyn Error>>;
+    /// Adds scopes to the user's grant to the client, creating it if needed.
+    /// Scopes agreed to earlier are kept.
+    async fn save(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+        scopes: &[String],
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn delete(&self, user_id: Uuid, client_id: Uuid)
+        -> Result<PgQueryResult, Box<dyn Error>>;
+}
@@ -0,0 +1,34 @@
+use std::error::Error;
+
+use crate::domain::one_time_code::OneTimeCode;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait OneTimeCodeRepository {
+    /// Stores a new code and drops the user's unused codes for the same
+    /// purpose, so only the latest one can be redeemed.
+    async fn create(
+        &self,
+        user_id: Uuid,
+        purpose: &str,
+        code_hash: &str,
+        binding_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid, Box<dyn Error>>;
+    async fn find(&self, id: Uuid) -> Result<Option<OneTimeCode>, Box<dyn Error>>;
+    /// The user's most recent unused code for `purpose`.
+    async fn find_latest(
+        &self,
+        user_id: Uuid,
+        purpose: &str,
+    ) -> Result<Option<OneTimeCode>, Box<dyn Error>>;
+    /// Counts a verification attempt and returns the new total.
+    async fn record_attempt(&self, id: Uuid) -> Result<i32, Box<dyn Error>>;
+    /// Marks the code as used. Affects no rows when it already was.
+    async fn consume(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Drops every expired code.
+    async fn purge_expired(&self) -> Result<PgQueryResult, Box<dyn Error>>;
+}
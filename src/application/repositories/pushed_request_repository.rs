@@ -0,0 +1,17 @@
+use std::error::Error;
+
+use crate::domain::oauth::{NewPushedRequest, PushedRequest};
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+
+#[async_trait]
+pub trait PushedRequestRepository {
+    async fn create(&self, request: &NewPushedRequest) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn find_by_request_uri_hash(
+        &self,
+        request_uri_hash: &str,
+    ) -> Result<Option<PushedRequest>, Box<dyn Error>>;
+    /// Marks the request as used for a code. Affects no rows when it
+    /// already was or has expired.
+    async fn consume(&self, request_uri_hash: &str) -> Result<PgQueryResult, Box<dyn Error>>;
+}
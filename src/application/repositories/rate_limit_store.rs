@@ -0,0 +1,10 @@
+use std::error::Error;
+
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision};
+use async_trait::async_trait;
+
+/// Backing storage for token buckets.
+#[async_trait]
+pub trait RateLimitStore {
+    async fn take(&self, key: &str, limit: RateLimit) -> Result<RateLimitDecision, Box<dyn Error>>;
+}
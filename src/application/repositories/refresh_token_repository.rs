@@ -0,0 +1,26 @@
+use std::error::Error;
+
+use crate::domain::oauth::{NewRefreshToken, RefreshToken};
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait RefreshTokenRepository {
+    async fn create(&self, token: &NewRefreshToken) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn find_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, Box<dyn Error>>;
+    /// Revokes a single token. Affects no rows when it already was, so two
+    /// concurrent rotations cannot both succeed.
+    async fn revoke(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Revokes every token rotated from the same grant.
+    async fn revoke_family(&self, family_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Revokes every token a user's grant to a client produced.
+    async fn revoke_for_client(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+}
@@ -0,0 +1,19 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait RevokedTokenRepository {
+    /// Adds a token id to the denylist and drops entries whose tokens have
+    /// expired. Revoking an already revoked token affects no rows.
+    async fn revoke(
+        &self,
+        jti: Uuid,
+        user_id: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn is_revoked(&self, jti: Uuid) -> Result<bool, Box<dyn Error>>;
+}
@@ -0,0 +1,25 @@
+use std::error::Error;
+
+use crate::domain::session::{NewSession, Session};
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait SessionRepository {
+    async fn create(&self, session: &NewSession) -> Result<Session, Box<dyn Error>>;
+    async fn find_by_token_hash(&self, token_hash: &str)
+        -> Result<Option<Session>, Box<dyn Error>>;
+    /// Sessions of the user that have not reached their absolute expiry,
+    /// most recently used first.
+    async fn find_by_user(&self, user_id: Uuid) -> Result<Vec<Session>, Box<dyn Error>>;
+    /// Records activity, pushing back the idle timeout.
+    async fn touch(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn delete(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn delete_for_user(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn delete_all_for_user(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
+}
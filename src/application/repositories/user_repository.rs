@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use crate::domain::user::{User, UserId};
+use crate::domain::user::{User, UserId, UserWithPassword};
 use crate::dto::user_dto::{CreateRequest, UpdateRequest};
 use async_trait::async_trait;
 use sqlx::postgres::PgQueryResult;
@@ -9,6 +9,16 @@ use uuid::Uuid;
 #[async_trait]
 pub trait UserRepository {
     async fn find_by_id(&self, user_id: Uuid) -> Result<Option<User>, Box<dyn Error>>;
+    async fn find_by_id_with_password(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<UserWithPassword>, Box<dyn Error>>;
+    async fn find_by_username_with_password(
+        &self,
+        username: &str,
+    ) -> Result<Option<UserWithPassword>, Box<dyn Error>>;
+    /// Matches case-insensitively, which is also how emails are unique.
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>>;
     async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>>;
     async fn create(&self, user: &CreateRequest) -> Result<UserId, Box<dyn Error>>;
     async fn update(
@@ -16,5 +26,24 @@ pub trait UserRepository {
         user_id: Uuid,
         data: &UpdateRequest,
     ) -> Result<Option<User>, Box<dyn Error>>;
+    async fn update_password_hash(
+        &self,
+        user_id: Uuid,
+        password_hash: &str,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Replaces the phone number and clears its verification.
+    async fn update_phone_number(
+        &self,
+        user_id: Uuid,
+        phone_number: Option<&str>,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    /// Marks the phone number as verified. Affects no rows when the number
+    /// has changed in the meantime.
+    async fn mark_phone_verified(
+        &self,
+        user_id: Uuid,
+        phone_number: &str,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn flag_password_reset(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
     async fn delete(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>>;
 }
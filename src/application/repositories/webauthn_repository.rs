@@ -0,0 +1,56 @@
+use std::error::Error;
+
+use crate::domain::webauthn::{WebauthnChallenge, WebauthnCredential};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait WebauthnRepository {
+    async fn create_challenge(
+        &self,
+        user_id: Option<Uuid>,
+        ceremony: &str,
+        challenge: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid, Box<dyn Error>>;
+    /// Removes and returns a challenge so it can only be answered once.
+    async fn take_challenge(
+        &self,
+        challenge_id: Uuid,
+        ceremony: &str,
+    ) -> Result<Option<WebauthnChallenge>, Box<dyn Error>>;
+    async fn find_credentials(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<WebauthnCredential>, Box<dyn Error>>;
+    async fn find_credential(
+        &self,
+        credential_id: &[u8],
+    ) -> Result<Option<WebauthnCredential>, Box<dyn Error>>;
+    async fn create_credential(
+        &self,
+        user_id: Uuid,
+        credential_id: &[u8],
+        public_key: &[u8],
+        sign_count: i64,
+        name: &str,
+    ) -> Result<WebauthnCredential, Box<dyn Error>>;
+    async fn update_sign_count(
+        &self,
+        id: Uuid,
+        sign_count: i64,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+    async fn rename_credential(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        name: &str,
+    ) -> Result<Option<WebauthnCredential>, Box<dyn Error>>;
+    async fn delete_credential(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>>;
+}
@@ -0,0 +1,14 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer {
+    async fn send(&self, message: EmailMessage) -> Result<(), Box<dyn Error>>;
+}
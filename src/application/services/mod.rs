@@ -0,0 +1,3 @@
+pub mod mailer;
+pub mod notifier;
+pub mod sms_sender;
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::login_risk::LoginRisk;
+
+/// A sign-in from a device or place the account has not used before.
+pub struct LoginNotification {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub risk: LoginRisk,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait Notifier {
+    async fn notify(&self, notification: LoginNotification) -> Result<(), Box<dyn Error>>;
+}
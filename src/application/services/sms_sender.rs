@@ -0,0 +1,16 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct SmsMessage {
+    /// E.164 formatted recipient.
+    pub to: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait SmsSender {
+    async fn send(&self, message: SmsMessage) -> Result<(), Box<dyn Error>>;
+}
@@ -0,0 +1,655 @@
+use std::{error::Error, fmt, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repositories::{
+            lockout_repository::LockoutRepository, login_event_repository::LoginEventRepository,
+            mfa_repository::MfaRepository, one_time_code_repository::OneTimeCodeRepository,
+            revoked_token_repository::RevokedTokenRepository, user_repository::UserRepository,
+            webauthn_repository::WebauthnRepository,
+        },
+        use_cases::{
+            login_event::LoginEventUseCase, login_risk::LoginRiskUseCase, mfa::MfaUseCase,
+            passwordless::PasswordlessUseCase, phone::PhoneError, revocation::RevocationUseCase,
+        },
+    },
+    config::LockoutConfig,
+    domain::{
+        auth_context::{AuthContext, AMR_EMAIL, AMR_HARDWARE_KEY, AMR_MULTI_FACTOR, AMR_PASSWORD},
+        client::ClientContext,
+        login_event::{
+            LoginAttempt, NewLoginEvent, ACCOUNT_LOCKED, INVALID_CODE, INVALID_CREDENTIALS,
+            INVALID_MFA, PASSWORD_RESET_REQUIRED, UNKNOWN_USER,
+        },
+        mfa::MfaCode,
+        principal::Principal,
+        user::UserWithPassword,
+        webauthn::WebauthnAssertion,
+    },
+    dto::{auth_dto::LoginRequest, webauthn_dto::RequestOptionsResponse},
+    util::{
+        pwd::PwdError,
+        pwd_pool::{PwdPool, PwdPoolError},
+        throttle::LoginThrottle,
+        token::{IssuedToken, MfaChallengeClaims, TokenService},
+        webauthn::encode_base64url,
+    },
+};
+
+pub const LOCKED_EVENT: &str = "locked";
+pub const UNLOCKED_EVENT: &str = "unlocked";
+
+#[derive(Debug)]
+pub enum AuthError {
+    InvalidCredentials,
+    InvalidMfa,
+    InvalidCode,
+    PasswordResetRequired,
+    Locked(DateTime<Utc>),
+    Throttled(Duration),
+    Unavailable(PwdPoolError),
+    Internal(Box<dyn Error>),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::InvalidCredentials => write!(f, "Invalid Username or Password"),
+            AuthError::InvalidMfa => write!(f, "Invalid MFA Token or Code"),
+            AuthError::InvalidCode => write!(f, "Invalid or Expired Code"),
+            AuthError::PasswordResetRequired => write!(f, "Password Reset Required"),
+            AuthError::Locked(until) => write!(f, "Account Locked Until {}", until.to_rfc3339()),
+            AuthError::Throttled(_) => write!(f, "Too Many Login Attempts"),
+            AuthError::Unavailable(err) => err.fmt(f),
+            AuthError::Internal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for AuthError {}
+
+impl AuthError {
+    /// The `failure_reason` recorded for a sign-in attempt that ended with
+    /// this error. Throttling and internal errors are not recorded.
+    pub fn failure_reason(&self, attempt: &LoginAttempt) -> Option<&'static str> {
+        match self {
+            AuthError::InvalidCredentials if attempt.user_id.is_none() => Some(UNKNOWN_USER),
+            AuthError::InvalidCredentials => Some(INVALID_CREDENTIALS),
+            AuthError::InvalidMfa => Some(INVALID_MFA),
+            AuthError::InvalidCode => Some(INVALID_CODE),
+            AuthError::PasswordResetRequired => Some(PASSWORD_RESET_REQUIRED),
+            AuthError::Locked(_) => Some(ACCOUNT_LOCKED),
+            AuthError::Throttled(_) | AuthError::Unavailable(_) | AuthError::Internal(_) => None,
+        }
+    }
+}
+
+impl From<Box<dyn Error>> for AuthError {
+    fn from(err: Box<dyn Error>) -> Self {
+        AuthError::Internal(err)
+    }
+}
+
+impl From<PwdPoolError> for AuthError {
+    fn from(err: PwdPoolError) -> Self {
+        match err {
+            PwdPoolError::Pwd(err) => AuthError::Internal(Box::new(err)),
+            PwdPoolError::Panicked => AuthError::Internal(Box::new(err)),
+            err => AuthError::Unavailable(err),
+        }
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for AuthError {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        AuthError::Internal(Box::new(err))
+    }
+}
+
+pub enum LoginOutcome {
+    Authenticated(Box<IssuedToken>),
+    /// The password was correct but a second factor is still needed. Carries
+    /// the challenge token to present alongside the code.
+    MfaRequired(String),
+    /// The sign-in looked unusual for an account without MFA. Carries a
+    /// challenge token to present alongside a code sent by email.
+    VerificationRequired(String),
+}
+
+impl LoginOutcome {
+    pub fn is_complete(&self) -> bool {
+        matches!(self, LoginOutcome::Authenticated(_))
+    }
+}
+
+pub struct AuthUseCase<
+    R: UserRepository,
+    L: LockoutRepository,
+    M: MfaRepository,
+    W: WebauthnRepository,
+    O: OneTimeCodeRepository,
+    D: RevokedTokenRepository,
+    E: LoginEventRepository,
+> {
+    repository: R,
+    lockout_repository: L,
+    mfa: Arc<MfaUseCase<R, M, W, O>>,
+    pwd_pool: Arc<PwdPool>,
+    tokens: Arc<TokenService>,
+    revocation: Arc<RevocationUseCase<D>>,
+    events: Arc<LoginEventUseCase<E>>,
+    risk: Arc<LoginRiskUseCase<E>>,
+    passwordless: Arc<PasswordlessUseCase<R, O, E>>,
+    throttle: Arc<LoginThrottle>,
+    lockout_cfg: LockoutConfig,
+}
+
+impl<
+        R: UserRepository,
+        L: LockoutRepository,
+        M: MfaRepository,
+        W: WebauthnRepository,
+        O: OneTimeCodeRepository,
+        D: RevokedTokenRepository,
+        E: LoginEventRepository,
+    > AuthUseCase<R, L, M, W, O, D, E>
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repository: R,
+        lockout_repository: L,
+        mfa: Arc<MfaUseCase<R, M, W, O>>,
+        pwd_pool: Arc<PwdPool>,
+        tokens: Arc<TokenService>,
+        revocation: Arc<RevocationUseCase<D>>,
+        events: Arc<LoginEventUseCase<E>>,
+        risk: Arc<LoginRiskUseCase<E>>,
+        passwordless: Arc<PasswordlessUseCase<R, O, E>>,
+        throttle: Arc<LoginThrottle>,
+        lockout_cfg: LockoutConfig,
+    ) -> Self {
+        Self {
+            repository,
+            lockout_repository,
+            mfa,
+            pwd_pool,
+            tokens,
+            revocation,
+            events,
+            risk,
+            passwordless,
+            throttle,
+            lockout_cfg,
+        }
+    }
+
+    pub fn mfa_challenge_ttl(&self) -> i64 {
+        self.mfa.challenge_ttl()
+    }
+
+    pub fn sms_code_ttl(&self) -> i64 {
+        self.mfa.phone().code_ttl()
+    }
+
+    pub fn email_code_ttl(&self) -> i64 {
+        self.passwordless.code_ttl()
+    }
+
+    /// Checks the credentials and issues an access token when they match.
+    ///
+    /// Passwords stored with a legacy scheme are rehashed with Argon2id once
+    /// they have been verified. A stored hash that cannot be parsed flags the
+    /// account for a password reset and fails like a wrong password; the
+    /// reset is only reported once a password has been verified. Repeated
+    /// failures lock the account for an exponentially growing period.
+    /// Accounts with MFA get a challenge token instead of an access token,
+    /// as do accounts without it when the sign-in looks unusual.
+    pub async fn login(
+        &self,
+        credentials: LoginRequest,
+        client: ClientContext,
+    ) -> Result<LoginOutcome, AuthError> {
+        let mut attempt = LoginAttempt::new(AMR_PASSWORD);
+        let result = self
+            .login_with_password(credentials, &client, &mut attempt)
+            .await;
+        self.record_attempt(
+            attempt,
+            &client,
+            result.as_ref().map(LoginOutcome::is_complete),
+        )
+        .await;
+        result
+    }
+
+    async fn login_with_password(
+        &self,
+        credentials: LoginRequest,
+        client: &ClientContext,
+        attempt: &mut LoginAttempt,
+    ) -> Result<LoginOutcome, AuthError> {
+        self.throttle
+            .attempt(client.ip_address.as_deref(), &credentials.username)
+            .map_err(AuthError::Throttled)?;
+
+        let Some(user) = self
+            .repository
+            .find_by_username_with_password(&credentials.username)
+            .await?
+        else {
+            // Same Argon2 work as a wrong password, so response times do
+            // not tell which usernames exist.
+            self.pwd_pool
+                .verify_dummy_password(&credentials.password)
+                .await?;
+            return Err(AuthError::InvalidCredentials);
+        };
+        attempt.user_id = Some(user.id);
+
+        self.ensure_not_locked(user.id).await?;
+
+        let verified = match self
+            .pwd_pool
+            .verify_password_hash(&credentials.password, &user.password_hash)
+            .await
+        {
+            Ok(verified) => verified,
+            Err(PwdPoolError::Pwd(PwdError::CorruptHash(reason))) => {
+                tracing::error!(user_id = %user.id, reason, "corrupt password hash, flagging account for reset");
+                self.repository.flag_password_reset(user.id).await?;
+                return Err(AuthError::InvalidCredentials);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if !verified {
+            return Err(self.record_failed_login(user.id, client).await?);
+        }
+
+        self.lockout_repository.reset(user.id).await?;
+
+        if user.password_reset_required {
+            return Err(AuthError::PasswordResetRequired);
+        }
+
+        if self.pwd_pool.needs_rehash(&user.password_hash) {
+            let password_hash = self
+                .pwd_pool
+                .generate_password_hash(&credentials.password)
+                .await?;
+            self.repository
+                .update_password_hash(user.id, &password_hash)
+                .await?;
+        }
+
+        self.issue_login_outcome(&user, AMR_PASSWORD, client).await
+    }
+
+    /// Finishes a login for a user who was identified some other way, such
+    /// as an emailed code. The lockout and MFA still apply. `method` is the
+    /// `amr` value recorded for that first factor.
+    pub async fn complete_login(
+        &self,
+        user_id: Uuid,
+        method: &str,
+        client: &ClientContext,
+    ) -> Result<LoginOutcome, AuthError> {
+        let mut attempt = LoginAttempt::new(method);
+        attempt.user_id = Some(user_id);
+
+        let result = async {
+            self.ensure_not_locked(user_id).await?;
+
+            let user = self
+                .repository
+                .find_by_id_with_password(user_id)
+                .await?
+                .ok_or(AuthError::InvalidCredentials)?;
+
+            self.issue_login_outcome(&user, method, client).await
+        }
+        .await;
+        self.record_attempt(
+            attempt,
+            client,
+            result.as_ref().map(LoginOutcome::is_complete),
+        )
+        .await;
+        result
+    }
+
+    /// Completes a login that was answered with an MFA challenge. A wrong
+    /// code counts towards the account lockout like a wrong password. The
+    /// challenge token is revoked once it has been used.
+    pub async fn verify_mfa(
+        &self,
+        mfa_token: &str,
+        code: MfaCode,
+        client: ClientContext,
+    ) -> Result<IssuedToken, AuthError> {
+        let claims = self.verify_mfa_challenge(mfa_token).await?;
+        let attempt = LoginAttempt {
+            user_id: Some(claims.sub),
+            method: claims
+                .amr
+                .first()
+                .cloned()
+                .unwrap_or_else(|| AMR_PASSWORD.to_string()),
+            mfa_method: Some(code.amr().to_string()),
+        };
+
+        let result = self.complete_mfa(claims, code, &client).await;
+        self.record_attempt(attempt, &client, result.as_ref().map(|_| true))
+            .await;
+        result
+    }
+
+    async fn complete_mfa(
+        &self,
+        claims: MfaChallengeClaims,
+        code: MfaCode,
+        client: &ClientContext,
+    ) -> Result<IssuedToken, AuthError> {
+        self.throttle
+            .attempt(client.ip_address.as_deref(), &claims.sub.to_string())
+            .map_err(AuthError::Throttled)?;
+        self.ensure_not_locked(claims.sub).await?;
+
+        let verified = match &code {
+            MfaCode::Email(code) if claims.verify_email => {
+                self.passwordless
+                    .verify_verification_code(claims.sub, code)
+                    .await?
+            }
+            MfaCode::Email(_) => false,
+            code => self.mfa.verify(claims.sub, code).await?,
+        };
+        if !verified {
+            return match self.record_failed_login(claims.sub, client).await? {
+                AuthError::InvalidCredentials => Err(AuthError::InvalidMfa),
+                err => Err(err),
+            };
+        }
+
+        self.lockout_repository.reset(claims.sub).await?;
+        self.revocation
+            .revoke(claims.jti, Some(claims.sub), claims.exp)
+            .await?;
+
+        let mut methods: Vec<&str> = claims.amr.iter().map(String::as_str).collect();
+        methods.push(code.amr());
+        Ok(self
+            .tokens
+            .issue_access_token(claims.sub, claims.roles, AuthContext::new(&methods))?)
+    }
+
+    /// Starts a WebAuthn ceremony for the second factor of a login that was
+    /// answered with an MFA challenge.
+    pub async fn mfa_webauthn_options(
+        &self,
+        mfa_token: &str,
+    ) -> Result<RequestOptionsResponse, AuthError> {
+        let claims = self.verify_mfa_challenge(mfa_token).await?;
+
+        Ok(self
+            .mfa
+            .webauthn()
+            .authentication_options(Some(claims.sub), false)
+            .await?)
+    }
+
+    /// Texts a code to the verified phone number for the second factor of a
+    /// login that was answered with an MFA challenge. Returns the number the
+    /// code went to.
+    pub async fn send_mfa_sms(&self, mfa_token: &str) -> Result<String, AuthError> {
+        let claims = self.verify_mfa_challenge(mfa_token).await?;
+
+        match self.mfa.phone().send_mfa_code(claims.sub).await {
+            Ok(phone_number) => Ok(phone_number),
+            Err(PhoneError::Throttled(retry_after)) => Err(AuthError::Throttled(retry_after)),
+            Err(PhoneError::Internal(err)) => Err(AuthError::Internal(err)),
+            Err(_) => Err(AuthError::InvalidMfa),
+        }
+    }
+
+    /// Emails a code for a login that the risk checks answered with a
+    /// verification challenge.
+    pub async fn send_mfa_email(
+        &self,
+        mfa_token: &str,
+        client: &ClientContext,
+    ) -> Result<(), AuthError> {
+        let claims = self.verify_mfa_challenge(mfa_token).await?;
+        if !claims.verify_email {
+            return Err(AuthError::InvalidMfa);
+        }
+
+        self.passwordless
+            .send_verification_code(claims.sub, client)
+            .await
+    }
+
+    /// Starts a passwordless sign-in. A known username narrows the allowed
+    /// credentials; otherwise any discoverable passkey may answer.
+    pub async fn passkey_options(
+        &self,
+        username: Option<&str>,
+    ) -> Result<RequestOptionsResponse, AuthError> {
+        let user_id = match username {
+            Some(username) => self
+                .repository
+                .find_by_username_with_password(username)
+                .await?
+                .map(|user| user.id),
+            None => None,
+        };
+        let mut options = self
+            .mfa
+            .webauthn()
+            .authentication_options(None, true)
+            .await?;
+        if let Some(user_id) = user_id {
+            options.public_key.allow_credentials = self
+                .mfa
+                .webauthn()
+                .find_credentials(user_id)
+                .await?
+                .iter()
+                .map(Into::into)
+                .collect();
+        }
+
+        Ok(options)
+    }
+
+    /// Signs in with a passkey alone. The authenticator must have verified
+    /// the user, which stands in for both the password and a second factor.
+    pub async fn login_with_passkey(
+        &self,
+        assertion: WebauthnAssertion,
+        client: ClientContext,
+    ) -> Result<IssuedToken, AuthError> {
+        let mut attempt = LoginAttempt::new(AMR_HARDWARE_KEY);
+        let result = self.passkey_login(assertion, &client, &mut attempt).await;
+        self.record_attempt(attempt, &client, result.as_ref().map(|_| true))
+            .await;
+        result
+    }
+
+    async fn passkey_login(
+        &self,
+        assertion: WebauthnAssertion,
+        client: &ClientContext,
+        attempt: &mut LoginAttempt,
+    ) -> Result<IssuedToken, AuthError> {
+        let throttle_key = format!("webauthn:{}", encode_base64url(&assertion.credential_id));
+        self.throttle
+            .attempt(client.ip_address.as_deref(), &throttle_key)
+            .map_err(AuthError::Throttled)?;
+
+        let user_id = self
+            .mfa
+            .webauthn()
+            .authenticate(&assertion, None, true)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        attempt.user_id = Some(user_id);
+        self.ensure_not_locked(user_id).await?;
+
+        let user = self
+            .repository
+            .find_by_id_with_password(user_id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        self.risk.assess(&user, client).await;
+
+        // The authenticator verified the user, so the passkey counts as two
+        // factors: possession of the key and a PIN or biometric.
+        Ok(self.tokens.issue_access_token(
+            user.id,
+            user.roles(),
+            AuthContext::new(&[AMR_HARDWARE_KEY, AMR_MULTI_FACTOR]),
+        )?)
+    }
+
+    /// Clears a lockout on behalf of an admin. Returns `false` when the
+    /// account was not locked.
+    pub async fn unlock(&self, user_id: Uuid, admin: &Principal) -> Result<bool, Box<dyn Error>> {
+        let result = self.lockout_repository.reset(user_id).await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        self.lockout_repository
+            .record_event(user_id, UNLOCKED_EVENT, None, Some(admin.user_id), None)
+            .await?;
+        tracing::info!(user_id = %user_id, admin_id = %admin.user_id, "account unlocked");
+
+        Ok(true)
+    }
+
+    /// A risky sign-in to an account with MFA needs nothing beyond the
+    /// usual second factor. One that started from an emailed code has
+    /// already proven access to the mailbox, so it is not asked again.
+    async fn issue_login_outcome(
+        &self,
+        user: &UserWithPassword,
+        method: &str,
+        client: &ClientContext,
+    ) -> Result<LoginOutcome, AuthError> {
+        let risk = self.risk.assess(user, client).await;
+
+        if self.mfa.is_enabled(user.id).await? {
+            let challenge = self.tokens.issue_mfa_challenge(
+                user.id,
+                user.roles(),
+                vec![method.to_string()],
+                false,
+                self.mfa.challenge_ttl(),
+            )?;
+            return Ok(LoginOutcome::MfaRequired(challenge));
+        }
+
+        if method != AMR_EMAIL && self.risk.requires_verification(&risk) {
+            let challenge = self.tokens.issue_mfa_challenge(
+                user.id,
+                user.roles(),
+                vec![method.to_string()],
+                true,
+                self.mfa.challenge_ttl(),
+            )?;
+            return Ok(LoginOutcome::VerificationRequired(challenge));
+        }
+
+        Ok(LoginOutcome::Authenticated(Box::new(
+            self.tokens
+                .issue_access_token(user.id, user.roles(), AuthContext::new(&[method]))?,
+        )))
+    }
+
+    /// Records how a sign-in attempt ended. `Ok(false)` means it is still
+    /// waiting for a second factor, which records its own event.
+    async fn record_attempt(
+        &self,
+        attempt: LoginAttempt,
+        client: &ClientContext,
+        outcome: Result<bool, &AuthError>,
+    ) {
+        let failure_reason = match outcome {
+            Ok(true) => None,
+            Ok(false) => return,
+            Err(err) => match err.failure_reason(&attempt) {
+                Some(reason) => Some(reason),
+                None => return,
+            },
+        };
+
+        self.events
+            .record(NewLoginEvent::new(attempt, failure_reason, client))
+            .await;
+    }
+
+    async fn verify_mfa_challenge(&self, token: &str) -> Result<MfaChallengeClaims, AuthError> {
+        let claims = self
+            .tokens
+            .verify_mfa_challenge(token)
+            .map_err(|_| AuthError::InvalidMfa)?;
+        if self.revocation.is_revoked(claims.jti, claims.exp).await? {
+            return Err(AuthError::InvalidMfa);
+        }
+
+        Ok(claims)
+    }
+
+    async fn ensure_not_locked(&self, user_id: Uuid) -> Result<(), AuthError> {
+        if let Some(lockout) = self.lockout_repository.find_by_user_id(user_id).await? {
+            if let Some(locked_until) = lockout.locked_until.filter(|until| *until > Utc::now()) {
+                return Err(AuthError::Locked(locked_until));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_failed_login(
+        &self,
+        user_id: Uuid,
+        client: &ClientContext,
+    ) -> Result<AuthError, Box<dyn Error>> {
+        let lockout = self
+            .lockout_repository
+            .increment_failed_attempts(user_id)
+            .await?;
+        if lockout.failed_attempts < self.lockout_cfg.threshold {
+            return Ok(AuthError::InvalidCredentials);
+        }
+
+        let locked_until = Utc::now() + self.lockout_duration(lockout.lockouts);
+        self.lockout_repository.lock(user_id, locked_until).await?;
+        self.lockout_repository
+            .record_event(
+                user_id,
+                LOCKED_EVENT,
+                client.ip_address.as_deref(),
+                None,
+                Some(locked_until),
+            )
+            .await?;
+        tracing::warn!(user_id = %user_id, %locked_until, "account locked after failed logins");
+
+        Ok(AuthError::Locked(locked_until))
+    }
+
+    fn lockout_duration(&self, previous_lockouts: i32) -> chrono::Duration {
+        let factor = 2_i64.saturating_pow(previous_lockouts.clamp(0, 30) as u32);
+        let seconds = self
+            .lockout_cfg
+            .base
+            .saturating_mul(factor)
+            .min(self.lockout_cfg.max);
+        chrono::Duration::seconds(seconds)
+    }
+}
@@ -0,0 +1,166 @@
+use std::{error::Error, fmt};
+
+use chrono::{DateTime, Utc};
+use data_encoding::BASE64URL_NOPAD;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    application::repositories::dpop_proof_repository::DpopProofRepository,
+    config::DpopConfig,
+    util::dpop::{access_token_hash, decode_proof, htu_matches},
+};
+
+/// Why a DPoP proof was not accepted, named after the error codes of
+/// RFC 9449.
+#[derive(Debug)]
+pub enum DpopError {
+    InvalidProof(&'static str),
+    /// The proof lacks a current server nonce. The client retries with
+    /// the one sent along with this error.
+    UseNonce,
+    Internal(Box<dyn Error>),
+}
+
+impl DpopError {
+    /// The `error` value sent to the client.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DpopError::InvalidProof(_) => "invalid_dpop_proof",
+            DpopError::UseNonce => "use_dpop_nonce",
+            DpopError::Internal(_) => "server_error",
+        }
+    }
+}
+
+impl fmt::Display for DpopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DpopError::InvalidProof(description) => write!(f, "{}", description),
+            DpopError::UseNonce => write!(f, "DPoP Nonce Required"),
+            DpopError::Internal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for DpopError {}
+
+impl From<Box<dyn Error>> for DpopError {
+    fn from(err: Box<dyn Error>) -> Self {
+        DpopError::Internal(err)
+    }
+}
+
+/// Checks DPoP proofs (RFC 9449) for the token endpoint and the protected
+/// API. A proof has to be signed by the key it carries, made for this very
+/// request, recent, and not seen before. Server nonces are stateless: a
+/// timestamp signed with a key derived from the token secret.
+pub struct DpopUseCase<P: DpopProofRepository> {
+    proofs: P,
+    key: Vec<u8>,
+    config: DpopConfig,
+}
+
+impl<P: DpopProofRepository> DpopUseCase<P> {
+    pub fn new(proofs: P, secret: &str, config: DpopConfig) -> Self {
+        Self {
+            proofs,
+            key: format!("dpop-nonce:{}", secret).into_bytes(),
+            config,
+        }
+    }
+
+    /// Whether clients are handed nonces to put in their proofs.
+    pub fn requires_nonce(&self) -> bool {
+        self.config.nonce
+    }
+
+    /// A fresh nonce for the `DPoP-Nonce` header.
+    pub fn nonce(&self) -> String {
+        let issued_at = Utc::now().timestamp().to_string();
+        format!("{}.{}", issued_at, self.sign_nonce(&issued_at))
+    }
+
+    fn sign_nonce(&self, issued_at: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(issued_at.as_bytes());
+        BASE64URL_NOPAD.encode(&mac.finalize().into_bytes())
+    }
+
+    fn nonce_is_current(&self, nonce: &str) -> bool {
+        let Some((issued_at, signature)) = nonce.split_once('.') else {
+            return false;
+        };
+        let Ok(timestamp) = issued_at.parse::<i64>() else {
+            return false;
+        };
+        let age = Utc::now().timestamp() - timestamp;
+        (0..=self.config.expiry).contains(&age)
+            && bool::from(
+                self.sign_nonce(issued_at)
+                    .as_bytes()
+                    .ct_eq(signature.as_bytes()),
+            )
+    }
+
+    /// Checks a proof made for a request with this method and URL, and,
+    /// at the protected API, the access token sent with it. Returns the
+    /// thumbprint of the proof key.
+    pub async fn verify(
+        &self,
+        proof: &str,
+        method: &str,
+        url: &str,
+        access_token: Option<&str>,
+    ) -> Result<String, DpopError> {
+        let proof = decode_proof(proof).ok_or(DpopError::InvalidProof("Invalid DPoP Proof"))?;
+        let claims = &proof.claims;
+        if claims.htm != method || !htu_matches(&claims.htu, url) {
+            return Err(DpopError::InvalidProof(
+                "DPoP Proof Made For Another Request",
+            ));
+        }
+        if (Utc::now().timestamp() - claims.iat).abs() > self.config.window {
+            return Err(DpopError::InvalidProof("DPoP Proof Expired"));
+        }
+        if let Some(access_token) = access_token {
+            let bound = claims.ath.as_deref().is_some_and(|ath| {
+                bool::from(
+                    ath.as_bytes()
+                        .ct_eq(access_token_hash(access_token).as_bytes()),
+                )
+            });
+            if !bound {
+                return Err(DpopError::InvalidProof(
+                    "DPoP Proof Made For Another Access Token",
+                ));
+            }
+        }
+        if self.config.nonce
+            && !claims
+                .nonce
+                .as_deref()
+                .is_some_and(|nonce| self.nonce_is_current(nonce))
+        {
+            return Err(DpopError::UseNonce);
+        }
+        if claims.jti.is_empty() || claims.jti.len() > 255 {
+            return Err(DpopError::InvalidProof("Invalid DPoP Proof"));
+        }
+
+        let expires_at = DateTime::from_timestamp(claims.iat + self.config.window, 0)
+            .ok_or(DpopError::InvalidProof("Invalid DPoP Proof"))?;
+        let recorded = self
+            .proofs
+            .record(&proof.jkt, &claims.jti, expires_at)
+            .await?;
+        if recorded.rows_affected() == 0 {
+            tracing::warn!(jkt = %proof.jkt, "DPoP proof replayed");
+            return Err(DpopError::InvalidProof("DPoP Proof Replayed"));
+        }
+
+        Ok(proof.jkt)
+    }
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::login_event_repository::LoginEventRepository,
+    config::LoginEventConfig,
+    domain::login_event::{LoginEvent, NewLoginEvent},
+};
+
+/// Largest page the security event feed hands out.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// History of sign-in attempts per user.
+pub struct LoginEventUseCase<E: LoginEventRepository> {
+    repository: E,
+    config: LoginEventConfig,
+}
+
+impl<E: LoginEventRepository> LoginEventUseCase<E> {
+    pub fn new(repository: E, config: LoginEventConfig) -> Self {
+        Self { repository, config }
+    }
+
+    /// Stores an event. Failures are logged and swallowed so that a problem
+    /// with the history never blocks a sign-in.
+    pub async fn record(&self, event: NewLoginEvent) {
+        if let Err(err) = self.repository.record(&event).await {
+            tracing::error!(user_id = ?event.user_id, error = %err, "failed to record login event");
+        }
+    }
+
+    /// One page of the user's events, newest first, with the total count.
+    /// `page` starts at 1.
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        page: i64,
+        per_page: i64,
+    ) -> Result<(Vec<LoginEvent>, i64), Box<dyn Error>> {
+        let per_page = per_page.clamp(1, MAX_PAGE_SIZE);
+        let offset = (page.max(1) - 1).saturating_mul(per_page);
+
+        let events = self
+            .repository
+            .find_by_user(user_id, per_page, offset)
+            .await?;
+        let total = self.repository.count_by_user(user_id).await?;
+        Ok((events, total))
+    }
+
+    /// Drops events older than the retention period. Returns how many went.
+    pub async fn prune(&self) -> Result<u64, Box<dyn Error>> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.config.retention);
+        let pruned = self.repository.delete_before(cutoff).await?.rows_affected();
+        if pruned > 0 {
+            tracing::info!(pruned, "pruned old login events");
+        }
+        Ok(pruned)
+    }
+}
@@ -0,0 +1,104 @@
+use std::{error::Error, sync::Arc};
+
+use chrono::Utc;
+
+use crate::{
+    application::{
+        repositories::login_event_repository::LoginEventRepository,
+        services::notifier::{LoginNotification, Notifier},
+    },
+    config::RiskConfig,
+    domain::{client::ClientContext, login_risk::LoginRisk, user::UserWithPassword},
+};
+
+/// Scores sign-ins against the user's login history and tells them about
+/// the unusual ones.
+pub struct LoginRiskUseCase<E: LoginEventRepository> {
+    repository: E,
+    notifier: Arc<dyn Notifier + Send + Sync>,
+    config: RiskConfig,
+}
+
+impl<E: LoginEventRepository> LoginRiskUseCase<E> {
+    pub fn new(
+        repository: E,
+        notifier: Arc<dyn Notifier + Send + Sync>,
+        config: RiskConfig,
+    ) -> Self {
+        Self {
+            repository,
+            notifier,
+            config,
+        }
+    }
+
+    /// Scores a sign-in whose first factor has just been verified and
+    /// notifies the user when the score reaches `notify`. The notification
+    /// goes out before any extra verification, so the owner also hears about
+    /// a stolen password that got stuck at the second step.
+    ///
+    /// Errors are logged and count as no risk, like a missing history would,
+    /// so a problem with the history never blocks a sign-in.
+    pub async fn assess(&self, user: &UserWithPassword, client: &ClientContext) -> LoginRisk {
+        let risk = match self.score(user, client).await {
+            Ok(risk) => risk,
+            Err(err) => {
+                tracing::error!(user_id = %user.id, error = %err, "failed to assess login risk");
+                return LoginRisk::default();
+            }
+        };
+
+        if risk.score >= self.config.notify {
+            tracing::warn!(
+                user_id = %user.id,
+                score = risk.score,
+                new_device = risk.new_device,
+                new_country = risk.new_country,
+                "unusual sign-in"
+            );
+            let notification = LoginNotification {
+                user_id: user.id,
+                username: user.username.clone(),
+                email: user.email.clone(),
+                risk: risk.clone(),
+                ip_address: client.ip_address.clone(),
+                user_agent: client.user_agent.clone(),
+                country: client.country.clone(),
+                occurred_at: Utc::now(),
+            };
+            if let Err(err) = self.notifier.notify(notification).await {
+                tracing::error!(user_id = %user.id, error = %err, "failed to send sign-in notification");
+            }
+        }
+
+        risk
+    }
+
+    /// Whether the sign-in has to be confirmed with an extra step.
+    pub fn requires_verification(&self, risk: &LoginRisk) -> bool {
+        risk.score >= self.config.challenge
+    }
+
+    async fn score(
+        &self,
+        user: &UserWithPassword,
+        client: &ClientContext,
+    ) -> Result<LoginRisk, Box<dyn Error>> {
+        let device_hash = client.device_hash();
+        let history = self
+            .repository
+            .find_history(
+                user.id,
+                device_hash.as_deref(),
+                client.country.as_deref(),
+                Utc::now() - chrono::Duration::days(1),
+            )
+            .await?;
+
+        Ok(LoginRisk::assess(
+            &history,
+            device_hash.as_deref(),
+            client.country.as_deref(),
+        ))
+    }
+}
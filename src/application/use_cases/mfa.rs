@@ -0,0 +1,299 @@
+use std::{error::Error, fmt, sync::Arc};
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use chrono::Utc;
+use data_encoding::{BASE32_NOPAD, HEXLOWER};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repositories::{
+            mfa_repository::MfaRepository, one_time_code_repository::OneTimeCodeRepository,
+            user_repository::UserRepository, webauthn_repository::WebauthnRepository,
+        },
+        use_cases::{phone::PhoneUseCase, webauthn::WebauthnUseCase},
+    },
+    config::MfaConfig,
+    domain::{mfa::MfaCode, principal::Principal},
+    util::{
+        cipher::SecretCipher,
+        totp::{qr_svg, Totp},
+    },
+};
+
+#[derive(Debug)]
+pub enum MfaError {
+    UserNotFound,
+    AlreadyEnrolled,
+    NotEnrolled,
+    InvalidCode,
+    Internal(Box<dyn Error>),
+}
+
+impl fmt::Display for MfaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MfaError::UserNotFound => write!(f, "User Not Found"),
+            MfaError::AlreadyEnrolled => write!(f, "MFA Already Enabled"),
+            MfaError::NotEnrolled => write!(f, "MFA Enrollment Not Started"),
+            MfaError::InvalidCode => write!(f, "Invalid MFA Code"),
+            MfaError::Internal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for MfaError {}
+
+impl From<Box<dyn Error>> for MfaError {
+    fn from(err: Box<dyn Error>) -> Self {
+        MfaError::Internal(err)
+    }
+}
+
+/// What an authenticator app needs to add the account.
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_svg: String,
+}
+
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    HEXLOWER.encode(&Sha256::digest(normalized.as_bytes()))
+}
+
+/// Ten base32 characters (50 bits) shown as `xxxxx-xxxxx`. The codes are
+/// random enough that a plain SHA-256 digest is a safe way to store them.
+fn generate_recovery_code() -> String {
+    let mut bytes = [0_u8; 7];
+    OsRng.fill_bytes(&mut bytes);
+    let encoded = BASE32_NOPAD.encode(&bytes).to_ascii_lowercase();
+    format!("{}-{}", &encoded[..5], &encoded[5..10])
+}
+
+/// Second factors: TOTP, recovery codes, WebAuthn credentials and SMS
+/// codes to a verified phone number.
+pub struct MfaUseCase<
+    R: UserRepository,
+    M: MfaRepository,
+    W: WebauthnRepository,
+    O: OneTimeCodeRepository,
+> {
+    user_repository: R,
+    repository: M,
+    webauthn: Arc<WebauthnUseCase<R, W>>,
+    phone: Arc<PhoneUseCase<R, O>>,
+    cipher: SecretCipher,
+    config: MfaConfig,
+}
+
+impl<R: UserRepository, M: MfaRepository, W: WebauthnRepository, O: OneTimeCodeRepository>
+    MfaUseCase<R, M, W, O>
+{
+    pub fn new(
+        user_repository: R,
+        repository: M,
+        webauthn: Arc<WebauthnUseCase<R, W>>,
+        phone: Arc<PhoneUseCase<R, O>>,
+        config: MfaConfig,
+    ) -> Self {
+        Self {
+            user_repository,
+            repository,
+            webauthn,
+            phone,
+            cipher: SecretCipher::new(&config.key),
+            config,
+        }
+    }
+
+    pub fn webauthn(&self) -> &WebauthnUseCase<R, W> {
+        &self.webauthn
+    }
+
+    pub fn phone(&self) -> &PhoneUseCase<R, O> {
+        &self.phone
+    }
+
+    /// Lifetime of the challenge token issued between password and code.
+    pub fn challenge_ttl(&self) -> i64 {
+        self.config.ttl
+    }
+
+    /// Starts TOTP enrollment. Calling it again before confirming replaces the
+    /// pending secret.
+    pub async fn enroll(&self, user_id: Uuid) -> Result<TotpEnrollment, MfaError> {
+        let user = self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(MfaError::UserNotFound)?;
+
+        let totp = Totp::generate();
+        let (ciphertext, nonce) = self
+            .cipher
+            .encrypt(totp.secret(), user_id.as_bytes())
+            .map_err(|err| MfaError::Internal(Box::new(err)))?;
+        let result = self
+            .repository
+            .save_pending_totp(user_id, &ciphertext, &nonce)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(MfaError::AlreadyEnrolled);
+        }
+
+        let otpauth_uri = totp.provisioning_uri(&self.config.issuer, &user.username);
+        let qr_svg = qr_svg(&otpauth_uri).map_err(|err| MfaError::Internal(Box::new(err)))?;
+
+        Ok(TotpEnrollment {
+            secret: totp.secret_base32(),
+            otpauth_uri,
+            qr_svg,
+        })
+    }
+
+    /// Confirms enrollment with the first code from the authenticator and
+    /// returns a fresh set of recovery codes. They are only shown once.
+    pub async fn confirm(&self, user_id: Uuid, code: &str) -> Result<Vec<String>, MfaError> {
+        let totp = self
+            .repository
+            .find_totp(user_id)
+            .await?
+            .ok_or(MfaError::NotEnrolled)?;
+        if totp.is_confirmed() {
+            return Err(MfaError::AlreadyEnrolled);
+        }
+
+        let secret = self
+            .cipher
+            .decrypt(
+                &totp.secret_ciphertext,
+                &totp.secret_nonce,
+                user_id.as_bytes(),
+            )
+            .map_err(|err| MfaError::Internal(Box::new(err)))?;
+        let step = Totp::new(secret)
+            .verify(code, Utc::now().timestamp(), self.config.skew)
+            .ok_or(MfaError::InvalidCode)?;
+
+        let recovery_codes: Vec<String> = (0..self.config.codes)
+            .map(|_| generate_recovery_code())
+            .collect();
+        let code_hashes: Vec<String> = recovery_codes
+            .iter()
+            .map(|code| hash_recovery_code(code))
+            .collect();
+        let result = self
+            .repository
+            .confirm_totp(user_id, step as i64, &code_hashes)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(MfaError::AlreadyEnrolled);
+        }
+
+        tracing::info!(user_id = %user_id, "TOTP enabled");
+        Ok(recovery_codes)
+    }
+
+    /// Whether the user has a confirmed authenticator app, a registered
+    /// WebAuthn credential or a verified phone number.
+    pub async fn is_enabled(&self, user_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let totp_enabled = self
+            .repository
+            .find_totp(user_id)
+            .await?
+            .is_some_and(|totp| totp.is_confirmed());
+
+        Ok(totp_enabled
+            || self.webauthn.has_credentials(user_id).await?
+            || self.phone.has_verified_phone(user_id).await?)
+    }
+
+    /// Checks a second factor. TOTP codes are accepted once per time step,
+    /// recovery codes once in total, WebAuthn assertions once per challenge
+    /// and SMS codes once per message.
+    pub async fn verify(&self, user_id: Uuid, code: &MfaCode) -> Result<bool, Box<dyn Error>> {
+        match code {
+            MfaCode::Totp(code) => {
+                let Some(totp) = self
+                    .repository
+                    .find_totp(user_id)
+                    .await?
+                    .filter(|totp| totp.is_confirmed())
+                else {
+                    return Ok(false);
+                };
+
+                let secret = self.cipher.decrypt(
+                    &totp.secret_ciphertext,
+                    &totp.secret_nonce,
+                    user_id.as_bytes(),
+                )?;
+                let Some(step) =
+                    Totp::new(secret).verify(code, Utc::now().timestamp(), self.config.skew)
+                else {
+                    return Ok(false);
+                };
+
+                let result = self
+                    .repository
+                    .record_totp_step(user_id, step as i64)
+                    .await?;
+                Ok(result.rows_affected() == 1)
+            }
+            MfaCode::Recovery(code) => {
+                let result = self
+                    .repository
+                    .redeem_recovery_code(user_id, &hash_recovery_code(code))
+                    .await?;
+                if result.rows_affected() == 1 {
+                    tracing::warn!(user_id = %user_id, "MFA recovery code redeemed");
+                }
+                Ok(result.rows_affected() == 1)
+            }
+            MfaCode::WebAuthn(assertion) => Ok(self
+                .webauthn
+                .authenticate(assertion, Some(user_id), false)
+                .await?
+                .is_some()),
+            MfaCode::Sms(code) => self.phone.verify_mfa_code(user_id, code).await,
+            // Emailed codes confirm risky sign-ins and are checked by the
+            // login flow; they are never an enrolled factor.
+            MfaCode::Email(_) => Ok(false),
+        }
+    }
+
+    /// Removes the user's authenticator, recovery codes and phone number on
+    /// behalf of an admin. Returns `false` when neither was set up.
+    pub async fn reset(&self, user_id: Uuid, admin: &Principal) -> Result<bool, Box<dyn Error>> {
+        let totp_removed = self.repository.delete(user_id).await?.rows_affected() > 0;
+        let phone_removed = self.phone.remove_phone_number(user_id).await?;
+        if !totp_removed && !phone_removed {
+            return Ok(false);
+        }
+
+        tracing::info!(user_id = %user_id, admin_id = %admin.user_id, "MFA reset");
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_code_hash_ignores_formatting() {
+        let code = generate_recovery_code();
+        assert_eq!(11, code.len());
+        assert_eq!(
+            hash_recovery_code(&code),
+            hash_recovery_code(&code.replace('-', "").to_uppercase())
+        );
+        assert_ne!(hash_recovery_code(&code), hash_recovery_code("aaaaa-aaaaa"));
+    }
+}
@@ -1 +1,14 @@
+pub mod auth;
+pub mod dpop;
+pub mod login_event;
+pub mod login_risk;
+pub mod mfa;
+pub mod oauth;
+pub mod oauth_client;
+pub mod oauth_grant;
+pub mod passwordless;
+pub mod phone;
+pub mod revocation;
+pub mod session;
 pub mod user;
+pub mod webauthn;
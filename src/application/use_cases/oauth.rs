@@ -0,0 +1,1668 @@
+use std::{error::Error, fmt, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::{
+        authorization_code_repository::AuthorizationCodeRepository,
+        client_assertion_repository::ClientAssertionRepository,
+        device_code_repository::DeviceCodeRepository, dpop_proof_repository::DpopProofRepository,
+        oauth_client_repository::OAuthClientRepository,
+        oauth_grant_repository::OAuthGrantRepository,
+        pushed_request_repository::PushedRequestRepository,
+        refresh_token_repository::RefreshTokenRepository,
+        revoked_token_repository::RevokedTokenRepository, user_repository::UserRepository,
+    },
+    application::use_cases::{
+        dpop::{DpopError, DpopUseCase},
+        oauth_grant::OAuthGrantUseCase,
+        revocation::RevocationUseCase,
+    },
+    config::OAuthConfig,
+    domain::{
+        auth_context::AuthContext,
+        oauth::{
+            has_scope, AuthorizationCode, DeviceCode, NewAuthorizationCode, NewDeviceCode,
+            NewPushedRequest, NewRefreshToken, OAuthClient, CLIENT_ASSERTION_JWT_BEARER,
+            DEVICE_APPROVED, DEVICE_DENIED, GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS,
+            GRANT_DEVICE_CODE, GRANT_REFRESH_TOKEN, GRANT_TOKEN_EXCHANGE, PKCE_METHOD_S256,
+            PROMPT_CONSENT, REQUEST_URI_PREFIX, RESPONSE_TYPE_CODE, SCOPE_BIRTHDATE, SCOPE_EMAIL,
+            SCOPE_OPENID, SCOPE_PROFILE, SUPPORTED_GRANT_TYPES, TOKEN_TYPE_ACCESS,
+            TOKEN_TYPE_ACCESS_URN, TOKEN_TYPE_REFRESH,
+        },
+    },
+    dto::oauth_dto::{
+        AuthorizeRequest, ClientCredentials, DeviceAuthorizationResponse, IntrospectionResponse,
+        OpenIdConfiguration, PushedAuthorizationResponse, TokenRequest, UserInfoResponse,
+    },
+    util::{
+        dpop::DPOP_SIGNING_ALGS,
+        id_token::{at_hash, IdTokenClaims, IdTokenSigner},
+        otp::{generate_secret, generate_user_code, normalize_user_code},
+        pkce::{is_valid_pkce_value, verify_s256},
+        token::{AccessClaims, Actor, Confirmation, IssuedToken, TokenService},
+    },
+};
+
+/// Request objects have to be signed with a key the client registered, so
+/// only asymmetric algorithms are accepted.
+const REQUEST_OBJECT_SIGNING_ALGS: &[&str] = &[
+    "RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "ES256", "ES384", "EdDSA",
+];
+
+/// Errors of the authorization and token endpoints, named after the error
+/// codes of RFC 6749.
+#[derive(Debug)]
+pub enum OAuthError {
+    InvalidRequest(&'static str),
+    InvalidClient,
+    InvalidGrant,
+    InvalidScope,
+    /// Token exchange: the audience is not one the client may ask for.
+    InvalidTarget,
+    UnauthorizedClient,
+    UnsupportedGrantType,
+    UnsupportedResponseType,
+    /// The `token_type_hint` names a kind of token this server does not
+    /// have.
+    UnsupportedTokenType,
+    /// Device grant: the user has not decided yet.
+    AuthorizationPending,
+    /// Device grant: the device polled before its interval was up.
+    SlowDown,
+    AccessDenied,
+    ExpiredToken,
+    /// The code typed in on the verification page matches no pending
+    /// request.
+    InvalidUserCode,
+    /// The DPoP proof sent to the token endpoint was not accepted.
+    InvalidDpopProof(&'static str),
+    /// The DPoP proof has to carry the nonce sent along with this error.
+    UseDpopNonce,
+    /// The `request_uri` is unknown, expired or was used already.
+    InvalidRequestUri,
+    /// The request object is not signed by the client or not meant for
+    /// this server.
+    InvalidRequestObject,
+    Internal(Box<dyn Error>),
+}
+
+impl OAuthError {
+    /// The `error` value sent to the client.
+    pub fn code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest(_) => "invalid_request",
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::InvalidGrant => "invalid_grant",
+            OAuthError::InvalidScope => "invalid_scope",
+            OAuthError::InvalidTarget => "invalid_target",
+            OAuthError::UnauthorizedClient => "unauthorized_client",
+            OAuthError::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthError::UnsupportedResponseType => "unsupported_response_type",
+            OAuthError::UnsupportedTokenType => "unsupported_token_type",
+            OAuthError::AuthorizationPending => "authorization_pending",
+            OAuthError::SlowDown => "slow_down",
+            OAuthError::AccessDenied => "access_denied",
+            OAuthError::ExpiredToken => "expired_token",
+            OAuthError::InvalidUserCode => "invalid_request",
+            OAuthError::InvalidDpopProof(_) => "invalid_dpop_proof",
+            OAuthError::UseDpopNonce => "use_dpop_nonce",
+            OAuthError::InvalidRequestUri => "invalid_request_uri",
+            OAuthError::InvalidRequestObject => "invalid_request_object",
+            OAuthError::Internal(_) => "server_error",
+        }
+    }
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthError::InvalidRequest(description) => write!(f, "{}", description),
+            OAuthError::InvalidClient => write!(f, "Client Authentication Failed"),
+            OAuthError::InvalidGrant => write!(f, "Invalid or Expired Grant"),
+            OAuthError::InvalidScope => write!(f, "Scope Not Allowed For This Client"),
+            OAuthError::InvalidTarget => write!(f, "Audience Not Allowed For This Client"),
+            OAuthError::UnauthorizedClient => write!(f, "Grant Type Not Allowed For This Client"),
+            OAuthError::UnsupportedGrantType => write!(f, "Unsupported Grant Type"),
+            OAuthError::UnsupportedResponseType => write!(f, "Unsupported Response Type"),
+            OAuthError::UnsupportedTokenType => write!(f, "Unsupported Token Type"),
+            OAuthError::AuthorizationPending => write!(f, "Authorization Pending"),
+            OAuthError::SlowDown => write!(f, "Polling Too Frequently"),
+            OAuthError::AccessDenied => write!(f, "Access Denied"),
+            OAuthError::ExpiredToken => write!(f, "Device Code Expired"),
+            OAuthError::InvalidUserCode => write!(f, "Invalid or Expired Code"),
+            OAuthError::InvalidDpopProof(description) => write!(f, "{}", description),
+            OAuthError::UseDpopNonce => write!(f, "DPoP Nonce Required"),
+            OAuthError::InvalidRequestUri => write!(f, "Invalid or Expired request_uri"),
+            OAuthError::InvalidRequestObject => write!(f, "Invalid Request Object"),
+            OAuthError::Internal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for OAuthError {}
+
+impl From<Box<dyn Error>> for OAuthError {
+    fn from(err: Box<dyn Error>) -> Self {
+        OAuthError::Internal(err)
+    }
+}
+
+impl From<DpopError> for OAuthError {
+    fn from(err: DpopError) -> Self {
+        match err {
+            DpopError::InvalidProof(description) => OAuthError::InvalidDpopProof(description),
+            DpopError::UseNonce => OAuthError::UseDpopNonce,
+            DpopError::Internal(err) => OAuthError::Internal(err),
+        }
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for OAuthError {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        OAuthError::Internal(Box::new(err))
+    }
+}
+
+/// How a client identified itself at the token endpoint.
+pub enum ClientAuthentication {
+    /// A public client, which only names itself.
+    None { client_id: String },
+    /// `client_secret_basic` or `client_secret_post`.
+    Secret {
+        client_id: String,
+        client_secret: String,
+    },
+    /// `private_key_jwt`: an assertion signed with a registered key.
+    PrivateKeyJwt {
+        client_id: Option<String>,
+        assertion: String,
+    },
+}
+
+impl ClientAuthentication {
+    /// Picks the method from the credentials sent. A client may only use
+    /// one, so a Basic header alongside credentials in the body is refused.
+    pub fn from_request(
+        basic: Option<(String, String)>,
+        request: &ClientCredentials,
+    ) -> Result<Self, OAuthError> {
+        let assertion = match (
+            request.client_assertion_type.as_deref(),
+            &request.client_assertion,
+        ) {
+            (None, None) => None,
+            (Some(CLIENT_ASSERTION_JWT_BEARER), Some(assertion)) => Some(assertion.clone()),
+            _ => {
+                return Err(OAuthError::InvalidRequest(
+                    "Unsupported client_assertion_type",
+                ))
+            }
+        };
+
+        match (basic, &request.client_secret, assertion) {
+            (Some((client_id, client_secret)), None, None) => {
+                if request
+                    .client_id
+                    .as_ref()
+                    .is_some_and(|form_id| *form_id != client_id)
+                {
+                    return Err(OAuthError::InvalidClient);
+                }
+                Ok(Self::Secret {
+                    client_id,
+                    client_secret,
+                })
+            }
+            (None, Some(client_secret), None) => Ok(Self::Secret {
+                client_id: request.client_id.clone().ok_or(OAuthError::InvalidClient)?,
+                client_secret: client_secret.clone(),
+            }),
+            (None, None, Some(assertion)) => Ok(Self::PrivateKeyJwt {
+                client_id: request.client_id.clone(),
+                assertion,
+            }),
+            (None, None, None) => Ok(Self::None {
+                client_id: request.client_id.clone().ok_or(OAuthError::InvalidClient)?,
+            }),
+            _ => Err(OAuthError::InvalidRequest(
+                "Use Only One Client Authentication Method",
+            )),
+        }
+    }
+}
+
+/// Claims of a private_key_jwt client assertion (RFC 7523, section 3).
+#[derive(Deserialize)]
+struct ClientAssertionClaims {
+    sub: String,
+    exp: i64,
+    jti: String,
+}
+
+/// What the token endpoint hands out.
+pub struct OAuthTokens {
+    pub access: IssuedToken,
+    /// Present when the client is registered for the refresh token grant.
+    pub refresh_token: Option<String>,
+    /// Present when the user granted the `openid` scope.
+    pub id_token: Option<String>,
+    /// Present for token exchange, which has to name what it issued.
+    pub issued_token_type: Option<&'static str>,
+}
+
+impl From<IssuedToken> for OAuthTokens {
+    fn from(access: IssuedToken) -> Self {
+        Self {
+            access,
+            refresh_token: None,
+            id_token: None,
+            issued_token_type: None,
+        }
+    }
+}
+
+/// OAuth 2.0 authorization server and OpenID Connect provider: the
+/// authorization code grant with mandatory PKCE and user consent for
+/// third-party clients, the client credentials
+/// grant for services, the device authorization grant for
+/// input-constrained devices, rotating refresh tokens and token exchange,
+/// with token introspection and revocation for resource servers and
+/// clients. Access tokens requested with a DPoP proof are bound to its key.
+/// Authorization requests may be pushed ahead of the browser and signed
+/// with the client's key.
+pub struct OAuthUseCase<
+    C: OAuthClientRepository,
+    A: AuthorizationCodeRepository,
+    J: ClientAssertionRepository,
+    D: DeviceCodeRepository,
+    U: UserRepository,
+    R: RefreshTokenRepository,
+    V: RevokedTokenRepository,
+    G: OAuthGrantRepository,
+    P: DpopProofRepository,
+    Q: PushedRequestRepository,
+> {
+    clients: C,
+    codes: A,
+    assertions: J,
+    devices: D,
+    users: U,
+    refresh_tokens: R,
+    revocation: Arc<RevocationUseCase<V>>,
+    grants: Arc<OAuthGrantUseCase<G, R>>,
+    dpop: Arc<DpopUseCase<P>>,
+    pushed: Q,
+    tokens: Arc<TokenService>,
+    id_tokens: Arc<IdTokenSigner>,
+    config: OAuthConfig,
+}
+
+impl<
+        C: OAuthClientRepository,
+        A: AuthorizationCodeRepository,
+        J: ClientAssertionRepository,
+        D: DeviceCodeRepository,
+        U: UserRepository,
+        R: RefreshTokenRepository,
+        V: RevokedTokenRepository,
+        G: OAuthGrantRepository,
+        P: DpopProofRepository,
+        Q: PushedRequestRepository,
+    > OAuthUseCase<C, A, J, D, U, R, V, G, P, Q>
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        clients: C,
+        codes: A,
+        assertions: J,
+        devices: D,
+        users: U,
+        refresh_tokens: R,
+        revocation: Arc<RevocationUseCase<V>>,
+        grants: Arc<OAuthGrantUseCase<G, R>>,
+        dpop: Arc<DpopUseCase<P>>,
+        pushed: Q,
+        tokens: Arc<TokenService>,
+        id_tokens: Arc<IdTokenSigner>,
+        config: OAuthConfig,
+    ) -> Self {
+        Self {
+            clients,
+            codes,
+            assertions,
+            devices,
+            users,
+            refresh_tokens,
+            revocation,
+            grants,
+            dpop,
+            pushed,
+            tokens,
+            id_tokens,
+            config,
+        }
+    }
+
+    /// Looks up the client, resolves a pushed request or a request object
+    /// into the parameters it carries and checks the redirect URI. Until
+    /// all of that is known to be good, errors must not be sent to the
+    /// redirect URI. The resolved request keeps `request_uri` or `request`,
+    /// so the sign-in and consent forms can carry it on.
+    pub async fn resolve_request(
+        &self,
+        request: &AuthorizeRequest,
+    ) -> Result<(OAuthClient, AuthorizeRequest), OAuthError> {
+        let client_id = request
+            .client_id
+            .as_deref()
+            .ok_or(OAuthError::InvalidClient)?;
+        let client = self.find_client_by_id(client_id).await?;
+
+        let resolved = match (&request.request_uri, &request.request) {
+            (Some(_), Some(_)) => {
+                return Err(OAuthError::InvalidRequest(
+                    "Use Either request Or request_uri",
+                ))
+            }
+            (Some(request_uri), None) => self.find_pushed_request(&client, request_uri).await?,
+            (None, _) if client.require_pushed_authorization_requests => {
+                return Err(OAuthError::InvalidRequest(
+                    "Pushed Authorization Request Required",
+                ))
+            }
+            (None, Some(request_object)) => AuthorizeRequest {
+                request: Some(request_object.clone()),
+                ..self.verify_request_object(&client, request_object)?
+            },
+            (None, None) if client.require_signed_request_object => {
+                return Err(OAuthError::InvalidRequest("Signed Request Object Required"))
+            }
+            (None, None) => request.clone(),
+        };
+
+        let redirect_uri = resolved
+            .redirect_uri
+            .as_deref()
+            .ok_or(OAuthError::InvalidRequest("Missing redirect_uri"))?;
+        if !client.allows_redirect_uri(redirect_uri) {
+            return Err(OAuthError::InvalidRequest("Redirect URI Not Registered"));
+        }
+
+        Ok((client, resolved))
+    }
+
+    /// The parameters behind a `request_uri`, which only the client that
+    /// pushed them may use.
+    async fn find_pushed_request(
+        &self,
+        client: &OAuthClient,
+        request_uri: &str,
+    ) -> Result<AuthorizeRequest, OAuthError> {
+        if !request_uri.starts_with(REQUEST_URI_PREFIX) {
+            return Err(OAuthError::InvalidRequestUri);
+        }
+        let pushed = self
+            .pushed
+            .find_by_request_uri_hash(&hash_code(request_uri))
+            .await?
+            .filter(|pushed| pushed.client_id == client.id && pushed.is_active())
+            .ok_or(OAuthError::InvalidRequestUri)?;
+        let parameters: AuthorizeRequest = serde_json::from_value(pushed.parameters)
+            .map_err(|err| OAuthError::Internal(Box::new(err)))?;
+
+        Ok(AuthorizeRequest {
+            request_uri: Some(request_uri.to_string()),
+            ..parameters
+        })
+    }
+
+    /// Checks a request object (RFC 9101): signed with one of the client's
+    /// registered keys, issued by the client, addressed to this server and
+    /// unexpired. Its claims replace the parameters sent alongside.
+    fn verify_request_object(
+        &self,
+        client: &OAuthClient,
+        request_object: &str,
+    ) -> Result<AuthorizeRequest, OAuthError> {
+        let header = jsonwebtoken::decode_header(request_object)
+            .map_err(|_| OAuthError::InvalidRequestObject)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OAuthError::InvalidRequestObject);
+        }
+        let key = client
+            .assertion_key(header.kid.as_deref())
+            .and_then(|jwk| DecodingKey::from_jwk(jwk).ok())
+            .ok_or(OAuthError::InvalidRequestObject)?;
+
+        let client_id = client.id.to_string();
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&client_id]);
+        validation.set_audience(&[self.issuer()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+        let claims = jsonwebtoken::decode::<AuthorizeRequest>(request_object, &key, &validation)
+            .map_err(|_| OAuthError::InvalidRequestObject)?
+            .claims;
+        if claims.request.is_some() || claims.request_uri.is_some() {
+            return Err(OAuthError::InvalidRequestObject);
+        }
+        if claims
+            .client_id
+            .as_ref()
+            .is_some_and(|claimed| *claimed != client_id)
+        {
+            return Err(OAuthError::InvalidRequestObject);
+        }
+
+        Ok(AuthorizeRequest {
+            client_id: Some(client_id),
+            ..claims
+        })
+    }
+
+    /// Pushed authorization request endpoint (RFC 9126). The client
+    /// authenticates and the request is checked as the authorization
+    /// endpoint would; the browser then only carries the `request_uri`.
+    pub async fn push_authorization_request(
+        &self,
+        authentication: ClientAuthentication,
+        request: AuthorizeRequest,
+    ) -> Result<PushedAuthorizationResponse, OAuthError> {
+        let client = self.authenticate_client(authentication).await?;
+        if request.request_uri.is_some() {
+            return Err(OAuthError::InvalidRequest("request_uri Cannot Be Pushed"));
+        }
+        let request = match &request.request {
+            Some(request_object) => self.verify_request_object(&client, request_object)?,
+            None if client.require_signed_request_object => {
+                return Err(OAuthError::InvalidRequest("Signed Request Object Required"))
+            }
+            None => request,
+        };
+        if !request
+            .redirect_uri
+            .as_deref()
+            .is_some_and(|redirect_uri| client.allows_redirect_uri(redirect_uri))
+        {
+            return Err(OAuthError::InvalidRequest("Redirect URI Not Registered"));
+        }
+        self.check_request(&client, &request)?;
+
+        let request_uri = format!("{}{}", REQUEST_URI_PREFIX, generate_secret());
+        let parameters = serde_json::to_value(AuthorizeRequest {
+            client_id: Some(client.id.to_string()),
+            ..request
+        })
+        .map_err(|err| OAuthError::Internal(Box::new(err)))?;
+        self.pushed
+            .create(&NewPushedRequest {
+                request_uri_hash: hash_code(&request_uri),
+                client_id: client.id,
+                parameters,
+                expires_at: Utc::now() + chrono::Duration::seconds(self.config.pushed),
+            })
+            .await?;
+
+        Ok(PushedAuthorizationResponse {
+            request_uri,
+            expires_in: self.config.pushed,
+        })
+    }
+
+    /// Checks the rest of the request against the client's registration.
+    /// These errors go back to the client through the redirect URI.
+    pub fn check_request(
+        &self,
+        client: &OAuthClient,
+        request: &AuthorizeRequest,
+    ) -> Result<(), OAuthError> {
+        if request.response_type.as_deref() != Some(RESPONSE_TYPE_CODE) {
+            return Err(OAuthError::UnsupportedResponseType);
+        }
+        if !client.allows_grant_type(GRANT_AUTHORIZATION_CODE) {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        if !client.allows_scope(request.scope.as_deref().unwrap_or_default()) {
+            return Err(OAuthError::InvalidScope);
+        }
+        if request.code_challenge_method.as_deref() != Some(PKCE_METHOD_S256) {
+            return Err(OAuthError::InvalidRequest("PKCE With S256 Is Required"));
+        }
+        if !request
+            .code_challenge
+            .as_deref()
+            .is_some_and(is_valid_pkce_value)
+        {
+            return Err(OAuthError::InvalidRequest("Invalid code_challenge"));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the user has to be asked before the client gets a code.
+    /// First-party clients are trusted; others need a stored grant covering
+    /// the requested scopes. `prompt=consent` always asks.
+    pub async fn needs_consent(
+        &self,
+        client: &OAuthClient,
+        request: &AuthorizeRequest,
+        user_id: Uuid,
+    ) -> Result<bool, OAuthError> {
+        if request
+            .prompt
+            .as_deref()
+            .is_some_and(|prompt| has_scope(prompt, PROMPT_CONSENT))
+        {
+            return Ok(true);
+        }
+        if client.first_party {
+            return Ok(false);
+        }
+        let scope = request.scope.as_deref().unwrap_or_default();
+
+        Ok(!self.grants.covers(user_id, client.id, scope).await?)
+    }
+
+    /// Carries the signed-in user through the consent screen.
+    pub fn consent_ticket(
+        &self,
+        client: &OAuthClient,
+        request: &AuthorizeRequest,
+        user_id: Uuid,
+        auth: AuthContext,
+    ) -> Result<String, OAuthError> {
+        Ok(self.tokens.issue_consent_ticket(
+            user_id,
+            client.id,
+            request.scope.clone().unwrap_or_default(),
+            auth,
+            self.config.consent,
+        )?)
+    }
+
+    /// Applies the user's answer from the consent screen. Approving stores
+    /// the grant and issues a code.
+    pub async fn consent(
+        &self,
+        client: &OAuthClient,
+        request: &AuthorizeRequest,
+        consent_ticket: &str,
+        approve: bool,
+    ) -> Result<String, OAuthError> {
+        let claims = self
+            .tokens
+            .verify_consent_ticket(consent_ticket)
+            .map_err(|_| OAuthError::InvalidRequest("Consent Request Expired"))?;
+        if claims.client_id != client.id
+            || claims.scope != request.scope.as_deref().unwrap_or_default()
+        {
+            return Err(OAuthError::InvalidRequest("Consent Request Does Not Match"));
+        }
+        if !approve {
+            tracing::info!(user_id = %claims.sub, client_id = %client.id, "oauth consent denied");
+            return Err(OAuthError::AccessDenied);
+        }
+
+        self.grants
+            .record(claims.sub, client.id, &claims.scope)
+            .await?;
+        self.issue_code(client, request, claims.sub, claims.auth)
+            .await
+    }
+
+    /// Issues a single-use code for a user who has signed in. Only its hash
+    /// is stored. A pushed request is used up with it.
+    pub async fn issue_code(
+        &self,
+        client: &OAuthClient,
+        request: &AuthorizeRequest,
+        user_id: Uuid,
+        auth: AuthContext,
+    ) -> Result<String, OAuthError> {
+        let (Some(redirect_uri), Some(code_challenge)) =
+            (&request.redirect_uri, &request.code_challenge)
+        else {
+            return Err(OAuthError::InvalidRequest(
+                "Incomplete Authorization Request",
+            ));
+        };
+        if let Some(request_uri) = &request.request_uri {
+            let consumed = self.pushed.consume(&hash_code(request_uri)).await?;
+            if consumed.rows_affected() == 0 {
+                return Err(OAuthError::InvalidRequestUri);
+            }
+        }
+
+        let code = generate_secret();
+        self.codes
+            .create(&NewAuthorizationCode {
+                code_hash: hash_code(&code),
+                client_id: client.id,
+                user_id,
+                redirect_uri: redirect_uri.clone(),
+                scope: request.scope.clone().unwrap_or_default(),
+                code_challenge: code_challenge.clone(),
+                nonce: request.nonce.clone(),
+                auth,
+                expires_at: Utc::now() + chrono::Duration::seconds(self.config.code),
+            })
+            .await?;
+
+        Ok(code)
+    }
+
+    /// Token endpoint. The client is authenticated before any grant is
+    /// looked at. With a DPoP proof the tokens are bound to its key.
+    pub async fn token(
+        &self,
+        authentication: ClientAuthentication,
+        request: TokenRequest,
+        dpop_proof: Option<&str>,
+    ) -> Result<OAuthTokens, OAuthError> {
+        let grant_type = request
+            .grant_type
+            .clone()
+            .ok_or(OAuthError::InvalidRequest("Missing grant_type"))?;
+        if !SUPPORTED_GRANT_TYPES.contains(&grant_type.as_str()) {
+            return Err(OAuthError::UnsupportedGrantType);
+        }
+
+        let client = self.authenticate_client(authentication).await?;
+        if !client.allows_grant_type(&grant_type) {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        let jkt = match dpop_proof {
+            Some(proof) => Some(
+                self.dpop
+                    .verify(proof, "POST", &self.token_endpoint(), None)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        match grant_type.as_str() {
+            GRANT_AUTHORIZATION_CODE => self.exchange_code(&client, request, jkt).await,
+            GRANT_CLIENT_CREDENTIALS => self
+                .client_credentials(&client, request, jkt)
+                .map(Into::into),
+            GRANT_DEVICE_CODE => self.exchange_device_code(&client, request, jkt).await,
+            GRANT_REFRESH_TOKEN => self.exchange_refresh_token(&client, request, jkt).await,
+            GRANT_TOKEN_EXCHANGE => self.exchange_token(&client, request, jkt).await,
+            _ => Err(OAuthError::UnsupportedGrantType),
+        }
+    }
+
+    /// Resolves the client and checks its credentials. Confidential clients
+    /// must authenticate; public clients have nothing to authenticate with.
+    async fn authenticate_client(
+        &self,
+        authentication: ClientAuthentication,
+    ) -> Result<OAuthClient, OAuthError> {
+        match authentication {
+            ClientAuthentication::None { client_id } => {
+                let client = self.find_client_by_id(&client_id).await?;
+                if client.is_confidential() {
+                    return Err(OAuthError::InvalidClient);
+                }
+                Ok(client)
+            }
+            ClientAuthentication::Secret {
+                client_id,
+                client_secret,
+            } => {
+                let client = self.find_client_by_id(&client_id).await?;
+                if !client.verify_secret(&client_secret) {
+                    return Err(OAuthError::InvalidClient);
+                }
+                Ok(client)
+            }
+            ClientAuthentication::PrivateKeyJwt {
+                client_id,
+                assertion,
+            } => self.verify_client_assertion(client_id, &assertion).await,
+        }
+    }
+
+    /// Checks a private_key_jwt assertion: signed with one of the client's
+    /// registered keys, issued by and about the client, addressed to the
+    /// token endpoint, unexpired and not seen before.
+    async fn verify_client_assertion(
+        &self,
+        client_id: Option<String>,
+        assertion: &str,
+    ) -> Result<OAuthClient, OAuthError> {
+        let client_id = match client_id {
+            Some(client_id) => client_id,
+            None => unverified_issuer(assertion).ok_or(OAuthError::InvalidClient)?,
+        };
+        let client = self.find_client_by_id(&client_id).await?;
+
+        let header =
+            jsonwebtoken::decode_header(assertion).map_err(|_| OAuthError::InvalidClient)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OAuthError::InvalidClient);
+        }
+        let key = client
+            .assertion_key(header.kid.as_deref())
+            .and_then(|jwk| DecodingKey::from_jwk(jwk).ok())
+            .ok_or(OAuthError::InvalidClient)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&client_id]);
+        validation.set_audience(&[self.token_endpoint(), self.config.url.clone()]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+        let claims = jsonwebtoken::decode::<ClientAssertionClaims>(assertion, &key, &validation)
+            .map_err(|_| OAuthError::InvalidClient)?
+            .claims;
+        if claims.sub != client_id {
+            return Err(OAuthError::InvalidClient);
+        }
+
+        let expires_at =
+            DateTime::from_timestamp(claims.exp, 0).ok_or(OAuthError::InvalidClient)?;
+        let recorded = self
+            .assertions
+            .record(client.id, &claims.jti, expires_at)
+            .await?;
+        if recorded.rows_affected() == 0 {
+            tracing::warn!(client_id = %client.id, "client assertion replayed");
+            return Err(OAuthError::InvalidClient);
+        }
+
+        Ok(client)
+    }
+
+    async fn find_client_by_id(&self, client_id: &str) -> Result<OAuthClient, OAuthError> {
+        let client_id = Uuid::parse_str(client_id).map_err(|_| OAuthError::InvalidClient)?;
+        self.clients
+            .find_by_id(client_id)
+            .await?
+            .ok_or(OAuthError::InvalidClient)
+    }
+
+    /// The OpenID Connect issuer, which is also the base of every endpoint.
+    pub fn issuer(&self) -> &str {
+        self.config.url.trim_end_matches('/')
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.issuer(), path)
+    }
+
+    /// Where client assertions have to be addressed.
+    pub fn token_endpoint(&self) -> String {
+        self.endpoint("token")
+    }
+
+    /// A nonce for the `DPoP-Nonce` header, when proofs have to carry one.
+    pub fn dpop_nonce(&self) -> Option<String> {
+        self.dpop.requires_nonce().then(|| self.dpop.nonce())
+    }
+
+    /// Provider metadata for OpenID Connect discovery.
+    pub fn discovery(&self) -> OpenIdConfiguration {
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+
+        OpenIdConfiguration {
+            issuer: self.issuer().to_string(),
+            authorization_endpoint: self.endpoint("authorize"),
+            token_endpoint: self.token_endpoint(),
+            device_authorization_endpoint: self.endpoint("device_authorization"),
+            introspection_endpoint: self.endpoint("introspect"),
+            revocation_endpoint: self.endpoint("revoke"),
+            userinfo_endpoint: self.endpoint("userinfo"),
+            jwks_uri: self.endpoint("jwks"),
+            response_types_supported: strings(&[RESPONSE_TYPE_CODE]),
+            grant_types_supported: strings(SUPPORTED_GRANT_TYPES),
+            subject_types_supported: strings(&["public"]),
+            id_token_signing_alg_values_supported: strings(&["RS256"]),
+            scopes_supported: strings(&[SCOPE_OPENID, SCOPE_PROFILE, SCOPE_EMAIL, SCOPE_BIRTHDATE]),
+            claims_supported: strings(&[
+                "sub",
+                "iss",
+                "aud",
+                "exp",
+                "iat",
+                "auth_time",
+                "nonce",
+                "amr",
+                "acr",
+                "at_hash",
+                "preferred_username",
+                "name",
+                "given_name",
+                "family_name",
+                "email",
+                "birthdate",
+            ]),
+            token_endpoint_auth_methods_supported: strings(&[
+                "none",
+                "client_secret_basic",
+                "client_secret_post",
+                "private_key_jwt",
+            ]),
+            code_challenge_methods_supported: strings(&[PKCE_METHOD_S256]),
+            dpop_signing_alg_values_supported: strings(DPOP_SIGNING_ALGS),
+            pushed_authorization_request_endpoint: self.endpoint("par"),
+            require_pushed_authorization_requests: false,
+            request_parameter_supported: true,
+            request_uri_parameter_supported: false,
+            request_object_signing_alg_values_supported: strings(REQUEST_OBJECT_SIGNING_ALGS),
+        }
+    }
+
+    /// The keys ID tokens are signed with.
+    pub fn jwks(&self) -> &JwkSet {
+        self.id_tokens.jwks()
+    }
+
+    /// Claims about the user behind an access token, limited to the scopes
+    /// granted to the client. None once the user has been deleted.
+    pub async fn userinfo(
+        &self,
+        user_id: Uuid,
+        scope: &str,
+    ) -> Result<Option<UserInfoResponse>, OAuthError> {
+        let user = self.users.find_by_id(user_id).await?;
+
+        Ok(user.map(|user| UserInfoResponse::new(user, scope)))
+    }
+
+    /// Issues a token to the client itself. Without a requested scope the
+    /// token carries every scope the client is registered for.
+    fn client_credentials(
+        &self,
+        client: &OAuthClient,
+        request: TokenRequest,
+        jkt: Option<String>,
+    ) -> Result<IssuedToken, OAuthError> {
+        if !client.is_confidential() {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        let scope = match request.scope {
+            Some(scope) if !client.allows_scope(&scope) => return Err(OAuthError::InvalidScope),
+            Some(scope) => scope,
+            None => client.scopes.join(" "),
+        };
+
+        Ok(self.tokens.issue_oauth_access_token(
+            client.id,
+            AuthContext::default(),
+            client.id,
+            Some(scope).filter(|scope| !scope.is_empty()),
+            client.access_ttl_or(self.tokens.ttl()),
+            jkt,
+        )?)
+    }
+
+    /// Device authorization endpoint. Starts a request the user approves on
+    /// another device while this one polls the token endpoint.
+    pub async fn authorize_device(
+        &self,
+        authentication: ClientAuthentication,
+        scope: Option<String>,
+    ) -> Result<DeviceAuthorizationResponse, OAuthError> {
+        let client = self.authenticate_client(authentication).await?;
+        if !client.allows_grant_type(GRANT_DEVICE_CODE) {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        let scope = scope.unwrap_or_default();
+        if !client.allows_scope(&scope) {
+            return Err(OAuthError::InvalidScope);
+        }
+
+        let device_code = generate_secret();
+        let user_code = generate_user_code();
+        self.devices
+            .create(&NewDeviceCode {
+                device_code_hash: hash_code(&device_code),
+                user_code_hash: hash_user_code(&user_code),
+                client_id: client.id,
+                scope,
+                poll_interval: self.config.interval,
+                expires_at: Utc::now() + chrono::Duration::seconds(self.config.device),
+            })
+            .await?;
+
+        let verification_uri = self.endpoint("device");
+        Ok(DeviceAuthorizationResponse {
+            verification_uri_complete: format!("{}?user_code={}", verification_uri, user_code),
+            verification_uri,
+            device_code,
+            user_code,
+            expires_in: self.config.device,
+            interval: self.config.interval,
+        })
+    }
+
+    /// Looks up a pending request by the code the user typed in, with the
+    /// client it came from so the user can see who is asking.
+    pub async fn find_device_request(
+        &self,
+        user_code: &str,
+    ) -> Result<(DeviceCode, OAuthClient), OAuthError> {
+        let device = self
+            .devices
+            .find_by_user_code_hash(&hash_user_code(user_code))
+            .await?
+            .filter(DeviceCode::is_pending)
+            .ok_or(OAuthError::InvalidUserCode)?;
+        let client = self
+            .clients
+            .find_by_id(device.client_id)
+            .await?
+            .ok_or(OAuthError::InvalidUserCode)?;
+
+        Ok((device, client))
+    }
+
+    /// Records the signed-in user's answer to a device request.
+    pub async fn decide_device_request(
+        &self,
+        user_code: &str,
+        user_id: Uuid,
+        auth: &AuthContext,
+        approve: bool,
+    ) -> Result<(), OAuthError> {
+        let (device, client) = self.find_device_request(user_code).await?;
+        let result = if approve {
+            self.devices.approve(device.id, user_id, auth).await?
+        } else {
+            self.devices.deny(device.id, user_id).await?
+        };
+        if result.rows_affected() == 0 {
+            return Err(OAuthError::InvalidUserCode);
+        }
+
+        tracing::info!(user_id = %user_id, client_id = %client.id, approve, "device authorization decided");
+        Ok(())
+    }
+
+    /// Answers a polling device. Polling faster than the interval pushes the
+    /// interval up by five seconds, as RFC 8628 asks.
+    async fn exchange_device_code(
+        &self,
+        client: &OAuthClient,
+        request: TokenRequest,
+        jkt: Option<String>,
+    ) -> Result<OAuthTokens, OAuthError> {
+        let device_code = request
+            .device_code
+            .ok_or(OAuthError::InvalidRequest("Missing device_code"))?;
+        let device = self
+            .devices
+            .find_by_device_code_hash(&hash_code(&device_code))
+            .await?
+            .filter(|device| device.client_id == client.id && device.consumed_at.is_none())
+            .ok_or(OAuthError::InvalidGrant)?;
+        if device.is_expired() {
+            return Err(OAuthError::ExpiredToken);
+        }
+
+        if device.polled_too_soon() {
+            self.devices
+                .record_poll(device.id, device.poll_interval + 5)
+                .await?;
+            return Err(OAuthError::SlowDown);
+        }
+        self.devices
+            .record_poll(device.id, device.poll_interval)
+            .await?;
+
+        match device.status.as_str() {
+            DEVICE_APPROVED => {}
+            DEVICE_DENIED => return Err(OAuthError::AccessDenied),
+            _ => return Err(OAuthError::AuthorizationPending),
+        }
+        let user_id = device.user_id.ok_or(OAuthError::InvalidGrant)?;
+        if self.devices.consume(device.id).await?.rows_affected() == 0 {
+            return Err(OAuthError::InvalidGrant);
+        }
+
+        self.issue_user_tokens(
+            client,
+            user_id,
+            device.auth_context(),
+            &device.scope,
+            None,
+            Uuid::new_v4(),
+            jkt,
+        )
+        .await
+    }
+
+    /// Redeems a refresh token for a new pair. The presented token is
+    /// retired; presenting it again is taken as a sign it was stolen and
+    /// ends every token rotated from the same grant. A narrower scope may
+    /// be asked for, never a wider one.
+    async fn exchange_refresh_token(
+        &self,
+        client: &OAuthClient,
+        request: TokenRequest,
+        jkt: Option<String>,
+    ) -> Result<OAuthTokens, OAuthError> {
+        let refresh_token = request
+            .refresh_token
+            .ok_or(OAuthError::InvalidRequest("Missing refresh_token"))?;
+        let stored = self
+            .refresh_tokens
+            .find_by_token_hash(&hash_code(&refresh_token))
+            .await?
+            .filter(|stored| stored.client_id == client.id)
+            .ok_or(OAuthError::InvalidGrant)?;
+        if stored.revoked_at.is_some() {
+            tracing::warn!(user_id = %stored.user_id, client_id = %client.id, "refresh token reused");
+            self.refresh_tokens.revoke_family(stored.family_id).await?;
+            return Err(OAuthError::InvalidGrant);
+        }
+        if !stored.is_active() {
+            return Err(OAuthError::InvalidGrant);
+        }
+        match (stored.jkt.as_deref(), jkt.as_deref()) {
+            (Some(_), None) => return Err(OAuthError::InvalidDpopProof("Missing DPoP Proof")),
+            (Some(bound), Some(jkt)) if bound != jkt => {
+                return Err(OAuthError::InvalidDpopProof(
+                    "Refresh Token Is Bound To Another DPoP Key",
+                ))
+            }
+            _ => {}
+        }
+
+        let scope = match request.scope {
+            Some(scope)
+                if !scope
+                    .split_whitespace()
+                    .all(|requested| has_scope(&stored.scope, requested)) =>
+            {
+                return Err(OAuthError::InvalidScope)
+            }
+            Some(scope) => scope,
+            None => stored.scope.clone(),
+        };
+        if self.refresh_tokens.revoke(stored.id).await?.rows_affected() == 0 {
+            return Err(OAuthError::InvalidGrant);
+        }
+
+        self.issue_user_tokens(
+            client,
+            stored.user_id,
+            stored.auth_context(),
+            &scope,
+            None,
+            stored.family_id,
+            jkt,
+        )
+        .await
+    }
+
+    /// Token exchange (RFC 8693). The subject token is traded for one
+    /// addressed to an audience the client is registered for, with no more
+    /// than the subject token's scopes. An actor token makes it delegation:
+    /// the actor goes into the `act` claim, ahead of any earlier actors.
+    /// Without one the client impersonates the subject.
+    async fn exchange_token(
+        &self,
+        client: &OAuthClient,
+        request: TokenRequest,
+        jkt: Option<String>,
+    ) -> Result<OAuthTokens, OAuthError> {
+        if !client.is_confidential() {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        if request
+            .requested_token_type
+            .as_deref()
+            .is_some_and(|token_type| token_type != TOKEN_TYPE_ACCESS_URN)
+        {
+            return Err(OAuthError::InvalidRequest(
+                "Unsupported requested_token_type",
+            ));
+        }
+        let audience = request
+            .audience
+            .ok_or(OAuthError::InvalidRequest("Missing audience"))?;
+        if !client.allows_audience(&audience) {
+            return Err(OAuthError::InvalidTarget);
+        }
+
+        let subject_token = request
+            .subject_token
+            .ok_or(OAuthError::InvalidRequest("Missing subject_token"))?;
+        if request.subject_token_type.as_deref() != Some(TOKEN_TYPE_ACCESS_URN) {
+            return Err(OAuthError::InvalidRequest("Unsupported subject_token_type"));
+        }
+        let subject = self
+            .verify_presented_token(&subject_token)
+            .await?
+            .ok_or(OAuthError::InvalidRequest("Invalid subject_token"))?;
+
+        let act = match (request.actor_token, request.actor_token_type.as_deref()) {
+            (None, None) => subject.act.clone(),
+            (Some(actor_token), Some(TOKEN_TYPE_ACCESS_URN)) => {
+                let actor = self
+                    .verify_presented_token(&actor_token)
+                    .await?
+                    .ok_or(OAuthError::InvalidRequest("Invalid actor_token"))?;
+                Some(Actor {
+                    sub: actor.sub,
+                    client_id: actor.client_id,
+                    act: subject.act.clone().map(Box::new),
+                })
+            }
+            _ => return Err(OAuthError::InvalidRequest("Unsupported actor_token_type")),
+        };
+
+        // A token from signing in to this service carries the user's full
+        // access; one issued to a client only the scopes it was granted.
+        let granted = subject
+            .client_id
+            .map(|_| subject.scope.clone().unwrap_or_default());
+        let scope = match request.scope {
+            Some(scope) => scope,
+            None => granted
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter(|granted| client.allows_scope(granted))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        let within_subject = |requested: &str| {
+            granted
+                .as_deref()
+                .is_none_or(|granted| has_scope(granted, requested))
+        };
+        if !client.allows_scope(&scope) || !scope.split_whitespace().all(within_subject) {
+            return Err(OAuthError::InvalidScope);
+        }
+
+        let access = self.tokens.issue_exchanged_access_token(
+            &subject,
+            client.id,
+            Some(scope).filter(|scope| !scope.is_empty()),
+            audience,
+            act,
+            client.access_ttl_or(self.tokens.ttl()),
+            jkt,
+        )?;
+        tracing::info!(
+            subject = %subject.sub,
+            client_id = %client.id,
+            audience = access.claims.aud.as_deref().unwrap_or_default(),
+            actor = ?access.claims.act.as_ref().map(|act| act.sub),
+            "token exchanged"
+        );
+
+        Ok(OAuthTokens {
+            issued_token_type: Some(TOKEN_TYPE_ACCESS_URN),
+            ..OAuthTokens::from(access)
+        })
+    }
+
+    /// An access token issued here that is still good, or None.
+    async fn verify_presented_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<AccessClaims>, OAuthError> {
+        let Ok(claims) = self.tokens.verify_issued_access_token(token) else {
+            return Ok(None);
+        };
+        if self.revocation.is_revoked(claims.jti, claims.exp).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(claims))
+    }
+
+    /// Introspection for resource servers, which have to authenticate as
+    /// confidential clients. Access tokens are reported to any of them;
+    /// refresh tokens only to the client holding them.
+    pub async fn introspect(
+        &self,
+        authentication: ClientAuthentication,
+        token: Option<String>,
+        token_type_hint: Option<&str>,
+    ) -> Result<IntrospectionResponse, OAuthError> {
+        let client = self.authenticate_client(authentication).await?;
+        if !client.is_confidential() {
+            return Err(OAuthError::InvalidClient);
+        }
+        let token = token.ok_or(OAuthError::InvalidRequest("Missing token"))?;
+        check_token_type_hint(token_type_hint)?;
+
+        if let Ok(claims) = self.tokens.verify_issued_access_token(&token) {
+            if self.revocation.is_revoked(claims.jti, claims.exp).await? {
+                return Ok(IntrospectionResponse::default());
+            }
+            return Ok(IntrospectionResponse {
+                active: true,
+                scope: claims.scope,
+                client_id: claims.client_id,
+                token_type: Some(TOKEN_TYPE_ACCESS.to_string()),
+                exp: Some(claims.exp),
+                iat: Some(claims.iat),
+                sub: Some(claims.sub),
+                iss: Some(claims.iss),
+                jti: Some(claims.jti),
+                aud: claims.aud,
+                act: claims.act,
+                cnf: claims.cnf,
+            });
+        }
+
+        let refresh_token = self
+            .refresh_tokens
+            .find_by_token_hash(&hash_code(&token))
+            .await?
+            .filter(|stored| stored.client_id == client.id && stored.is_active());
+        Ok(match refresh_token {
+            Some(stored) => IntrospectionResponse {
+                active: true,
+                scope: Some(stored.scope).filter(|scope| !scope.is_empty()),
+                client_id: Some(stored.client_id),
+                token_type: Some(TOKEN_TYPE_REFRESH.to_string()),
+                exp: Some(stored.expires_at.timestamp()),
+                iat: Some(stored.created_at.timestamp()),
+                sub: Some(stored.user_id),
+                iss: Some(self.issuer().to_string()),
+                jti: None,
+                aud: None,
+                act: None,
+                cnf: stored.jkt.map(|jkt| Confirmation { jkt }),
+            },
+            None => IntrospectionResponse::default(),
+        })
+    }
+
+    /// Revocation for clients giving up a token. Unknown and expired tokens
+    /// count as revoked, as RFC 7009 asks; tokens of another client are
+    /// refused. Revoking a refresh token ends its whole family, but access
+    /// tokens already issued from it run until they expire.
+    pub async fn revoke(
+        &self,
+        authentication: ClientAuthentication,
+        token: Option<String>,
+        token_type_hint: Option<&str>,
+    ) -> Result<(), OAuthError> {
+        let client = self.authenticate_client(authentication).await?;
+        let token = token.ok_or(OAuthError::InvalidRequest("Missing token"))?;
+        check_token_type_hint(token_type_hint)?;
+
+        if let Ok(claims) = self.tokens.verify_issued_access_token(&token) {
+            if claims.client_id != Some(client.id) {
+                return Err(OAuthError::InvalidRequest(
+                    "Token Was Issued To Another Client",
+                ));
+            }
+            self.revocation
+                .revoke(claims.jti, Some(claims.sub), claims.exp)
+                .await?;
+            return Ok(());
+        }
+
+        if let Some(stored) = self
+            .refresh_tokens
+            .find_by_token_hash(&hash_code(&token))
+            .await?
+        {
+            if stored.client_id != client.id {
+                return Err(OAuthError::InvalidRequest(
+                    "Token Was Issued To Another Client",
+                ));
+            }
+            self.refresh_tokens.revoke_family(stored.family_id).await?;
+            tracing::info!(user_id = %stored.user_id, client_id = %client.id, "refresh token revoked");
+        }
+
+        Ok(())
+    }
+
+    /// Redeems an authorization code. The client, redirect URI and PKCE
+    /// verifier all have to match what the code was issued for.
+    async fn exchange_code(
+        &self,
+        client: &OAuthClient,
+        request: TokenRequest,
+        jkt: Option<String>,
+    ) -> Result<OAuthTokens, OAuthError> {
+        let (Some(code), Some(redirect_uri), Some(code_verifier)) =
+            (request.code, request.redirect_uri, request.code_verifier)
+        else {
+            return Err(OAuthError::InvalidRequest(
+                "Missing code, redirect_uri or code_verifier",
+            ));
+        };
+
+        let stored = self
+            .codes
+            .find_by_code_hash(&hash_code(&code))
+            .await?
+            .ok_or(OAuthError::InvalidGrant)?;
+        if stored.consumed_at.is_some() {
+            tracing::warn!(user_id = %stored.user_id, client_id = %stored.client_id, "authorization code replayed, revoking its tokens");
+            self.revoke_code_tokens(&stored).await?;
+            return Err(OAuthError::InvalidGrant);
+        }
+        if stored.is_expired()
+            || stored.client_id != client.id
+            || stored.redirect_uri != redirect_uri
+            || !verify_s256(&code_verifier, &stored.code_challenge)
+        {
+            return Err(OAuthError::InvalidGrant);
+        }
+
+        let family_id = Uuid::new_v4();
+        if self
+            .codes
+            .consume(stored.id, family_id)
+            .await?
+            .rows_affected()
+            == 0
+        {
+            return Err(OAuthError::InvalidGrant);
+        }
+
+        let tokens = self
+            .issue_user_tokens(
+                client,
+                stored.user_id,
+                stored.auth_context(),
+                &stored.scope,
+                stored.nonce.clone(),
+                family_id,
+                jkt,
+            )
+            .await?;
+        let expires_at =
+            DateTime::from_timestamp(tokens.access.claims.exp, 0).unwrap_or_else(Utc::now);
+        self.codes
+            .record_access_token(stored.id, tokens.access.claims.jti, expires_at)
+            .await?;
+
+        Ok(tokens)
+    }
+
+    /// A code that comes back after it was exchanged may have been stolen,
+    /// so the tokens issued for it are revoked, as RFC 6749 section 4.1.2
+    /// recommends.
+    async fn revoke_code_tokens(&self, code: &AuthorizationCode) -> Result<(), OAuthError> {
+        if let Some(family_id) = code.family_id {
+            self.refresh_tokens.revoke_family(family_id).await?;
+        }
+        if let (Some(jti), Some(expires_at)) = (code.access_jti, code.access_expires_at) {
+            self.revocation
+                .revoke(jti, Some(code.user_id), expires_at.timestamp())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Issues the access token for a user's grant, a refresh token in the
+    /// given family when the client may refresh, and an ID token when the
+    /// `openid` scope was granted. The ID token lives as long as the access
+    /// token it is bound to. Public clients cannot keep a refresh token to
+    /// themselves, so theirs is bound to the DPoP key as well.
+    #[allow(clippy::too_many_arguments)]
+    async fn issue_user_tokens(
+        &self,
+        client: &OAuthClient,
+        user_id: Uuid,
+        auth: AuthContext,
+        scope: &str,
+        nonce: Option<String>,
+        family_id: Uuid,
+        jkt: Option<String>,
+    ) -> Result<OAuthTokens, OAuthError> {
+        let access = self.tokens.issue_oauth_access_token(
+            user_id,
+            auth.clone(),
+            client.id,
+            Some(scope.to_string()).filter(|scope| !scope.is_empty()),
+            client.access_ttl_or(self.tokens.ttl()),
+            jkt.clone(),
+        )?;
+
+        let refresh_token = if client.allows_grant_type(GRANT_REFRESH_TOKEN) {
+            let refresh_token = generate_secret();
+            self.refresh_tokens
+                .create(&NewRefreshToken {
+                    token_hash: hash_code(&refresh_token),
+                    family_id,
+                    client_id: client.id,
+                    user_id,
+                    scope: scope.to_string(),
+                    auth: auth.clone(),
+                    jkt: jkt.filter(|_| !client.is_confidential()),
+                    expires_at: Utc::now()
+                        + chrono::Duration::seconds(client.refresh_ttl_or(self.config.refresh)),
+                })
+                .await?;
+            Some(refresh_token)
+        } else {
+            None
+        };
+
+        let id_token = if has_scope(scope, SCOPE_OPENID) {
+            Some(self.id_tokens.sign(&IdTokenClaims {
+                iss: self.issuer().to_string(),
+                sub: user_id,
+                aud: client.id.to_string(),
+                iat: access.claims.iat,
+                exp: access.claims.exp,
+                auth,
+                nonce,
+                at_hash: Some(at_hash(&access.token)),
+            })?)
+        } else {
+            None
+        };
+
+        Ok(OAuthTokens {
+            access,
+            refresh_token,
+            id_token,
+            issued_token_type: None,
+        })
+    }
+}
+
+/// The hint only says where to look first, and both kinds of token are
+/// cheap to look up, so it is only checked for values this server knows.
+fn check_token_type_hint(token_type_hint: Option<&str>) -> Result<(), OAuthError> {
+    match token_type_hint {
+        None | Some(TOKEN_TYPE_ACCESS) | Some(TOKEN_TYPE_REFRESH) => Ok(()),
+        Some(_) => Err(OAuthError::UnsupportedTokenType),
+    }
+}
+
+fn hash_code(code: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(code.as_bytes()))
+}
+
+/// User codes are hashed in their normalised form, so the way the user
+/// types them in does not matter. Unlike one-time sign-in codes they need
+/// no keyed hash: knowing one only lets someone answer the request with
+/// their own account.
+fn hash_user_code(user_code: &str) -> String {
+    hash_code(&normalize_user_code(user_code))
+}
+
+/// The `iss` of an assertion before its signature is checked, used only to
+/// find the client whose keys will check it.
+fn unverified_issuer(assertion: &str) -> Option<String> {
+    let payload = BASE64URL_NOPAD
+        .decode(assertion.split('.').nth(1)?.as_bytes())
+        .ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    claims.get("iss")?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::oauth::PostgresOAuthUseCase,
+        config::{DpopConfig, RevocationConfig, TokenConfig},
+        domain::{
+            auth_context::AMR_PASSWORD,
+            oauth::{NewOAuthClient, CLIENT_PUBLIC},
+        },
+        dto::oauth_dto::AuthorizeRequest,
+        infrastructure::repositories::{
+            postgres_authorization_code_repo::PostgresAuthorizationCodeRepository,
+            postgres_client_assertion_repo::PostgresClientAssertionRepository,
+            postgres_device_code_repo::PostgresDeviceCodeRepository,
+            postgres_dpop_proof_repo::PostgresDpopProofRepository,
+            postgres_oauth_client_repo::PostgresOAuthClientRepository,
+            postgres_oauth_grant_repo::PostgresOAuthGrantRepository,
+            postgres_pushed_request_repo::PostgresPushedRequestRepository,
+            postgres_refresh_token_repo::PostgresRefreshTokenRepository,
+            postgres_revoked_token_repo::PostgresRevokedTokenRepository,
+            postgres_user_repo::PostgresUserRepository,
+            test_support::{create_user, setup_database},
+        },
+        util::pkce::s256_challenge,
+    };
+
+    const REDIRECT_URI: &str = "https://replay.example.com/cb";
+    const CODE_VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    #[tokio::test]
+    async fn test_replayed_code_revokes_its_tokens() {
+        let pool = setup_database().await;
+        sqlx::query("DELETE FROM users WHERE username = 'codereplay'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM oauth_clients WHERE name = 'replay'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let user_id = create_user(&pool, "codereplay").await;
+        let client = PostgresOAuthClientRepository::new(pool.clone())
+            .create(&NewOAuthClient {
+                name: "replay".to_string(),
+                client_type: CLIENT_PUBLIC.to_string(),
+                first_party: false,
+                require_pushed_authorization_requests: false,
+                require_signed_request_object: false,
+                secret_hash: None,
+                redirect_uris: vec![REDIRECT_URI.to_string()],
+                grant_types: vec![
+                    GRANT_AUTHORIZATION_CODE.to_string(),
+                    GRANT_REFRESH_TOKEN.to_string(),
+                ],
+                scopes: vec!["profile".to_string()],
+                audiences: Vec::new(),
+                access_ttl: None,
+                refresh_ttl: None,
+                jwks: None,
+            })
+            .await
+            .unwrap();
+
+        let tokens = Arc::new(TokenService::new(TokenConfig::default()));
+        let refresh_tokens = PostgresRefreshTokenRepository::new(pool.clone());
+        let revocation = Arc::new(RevocationUseCase::new(
+            PostgresRevokedTokenRepository::new(pool.clone()),
+            RevocationConfig::default(),
+        ));
+        let oauth: PostgresOAuthUseCase = OAuthUseCase::new(
+            PostgresOAuthClientRepository::new(pool.clone()),
+            PostgresAuthorizationCodeRepository::new(pool.clone()),
+            PostgresClientAssertionRepository::new(pool.clone()),
+            PostgresDeviceCodeRepository::new(pool.clone()),
+            PostgresUserRepository::new(pool.clone()),
+            PostgresRefreshTokenRepository::new(pool.clone()),
+            Arc::clone(&revocation),
+            Arc::new(OAuthGrantUseCase::new(
+                PostgresOAuthGrantRepository::new(pool.clone()),
+                PostgresRefreshTokenRepository::new(pool.clone()),
+            )),
+            Arc::new(DpopUseCase::new(
+                PostgresDpopProofRepository::new(pool.clone()),
+                "secret",
+                DpopConfig::default(),
+            )),
+            PostgresPushedRequestRepository::new(pool.clone()),
+            tokens,
+            Arc::new(IdTokenSigner::generate().unwrap()),
+            OAuthConfig::default(),
+        );
+
+        let code = oauth
+            .issue_code(
+                &client,
+                &AuthorizeRequest {
+                    redirect_uri: Some(REDIRECT_URI.to_string()),
+                    scope: Some("profile".to_string()),
+                    code_challenge: Some(s256_challenge(CODE_VERIFIER)),
+                    ..AuthorizeRequest::default()
+                },
+                user_id,
+                AuthContext::new(&[AMR_PASSWORD]),
+            )
+            .await
+            .unwrap();
+        let exchange = || {
+            oauth.token(
+                ClientAuthentication::None {
+                    client_id: client.id.to_string(),
+                },
+                TokenRequest {
+                    grant_type: Some(GRANT_AUTHORIZATION_CODE.to_string()),
+                    code: Some(code.clone()),
+                    redirect_uri: Some(REDIRECT_URI.to_string()),
+                    code_verifier: Some(CODE_VERIFIER.to_string()),
+                    ..TokenRequest::default()
+                },
+                None,
+            )
+        };
+
+        let issued = exchange().await.unwrap();
+        let refresh_token = issued.refresh_token.unwrap();
+        assert!(refresh_tokens
+            .find_by_token_hash(&hash_code(&refresh_token))
+            .await
+            .unwrap()
+            .unwrap()
+            .is_active());
+
+        assert!(matches!(exchange().await, Err(OAuthError::InvalidGrant)));
+        assert!(!refresh_tokens
+            .find_by_token_hash(&hash_code(&refresh_token))
+            .await
+            .unwrap()
+            .unwrap()
+            .is_active());
+        assert!(revocation
+            .is_revoked(issued.access.claims.jti, issued.access.claims.exp)
+            .await
+            .unwrap());
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM oauth_clients WHERE id = $1")
+            .bind(client.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
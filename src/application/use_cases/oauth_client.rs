@@ -0,0 +1,265 @@
+use std::{error::Error, fmt};
+
+use chrono::Utc;
+use jsonwebtoken::jwk::AlgorithmParameters;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::oauth_client_repository::OAuthClientRepository,
+    config::OAuthConfig,
+    domain::{
+        oauth::{
+            hash_client_secret, NewOAuthClient, OAuthClient, CLIENT_CONFIDENTIAL, CLIENT_PUBLIC,
+            GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS, GRANT_TOKEN_EXCHANGE,
+            SUPPORTED_GRANT_TYPES,
+        },
+        principal::Principal,
+    },
+    dto::oauth_client_dto::{CreateClientRequest, UpdateClientRequest},
+    util::otp::generate_secret,
+};
+
+#[derive(Debug)]
+pub enum OAuthClientError {
+    Invalid(&'static str),
+    NotFound,
+    PublicClient,
+    Internal(Box<dyn Error>),
+}
+
+impl fmt::Display for OAuthClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthClientError::Invalid(description) => write!(f, "{}", description),
+            OAuthClientError::NotFound => write!(f, "Client Not Found"),
+            OAuthClientError::PublicClient => write!(f, "Public Clients Have No Secret"),
+            OAuthClientError::Internal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for OAuthClientError {}
+
+impl From<Box<dyn Error>> for OAuthClientError {
+    fn from(err: Box<dyn Error>) -> Self {
+        OAuthClientError::Internal(err)
+    }
+}
+
+/// Admin management of registered OAuth clients.
+pub struct OAuthClientUseCase<C: OAuthClientRepository> {
+    repository: C,
+    config: OAuthConfig,
+}
+
+impl<C: OAuthClientRepository> OAuthClientUseCase<C> {
+    pub fn new(repository: C, config: OAuthConfig) -> Self {
+        Self { repository, config }
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<OAuthClient>, OAuthClientError> {
+        Ok(self.repository.find_all().await?)
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<OAuthClient, OAuthClientError> {
+        self.repository
+            .find_by_id(id)
+            .await?
+            .ok_or(OAuthClientError::NotFound)
+    }
+
+    /// Registers a client. Confidential clients get a secret, returned here
+    /// and never again.
+    pub async fn create(
+        &self,
+        request: CreateClientRequest,
+        admin: &Principal,
+    ) -> Result<(OAuthClient, Option<String>), OAuthClientError> {
+        let secret = match request.client_type.as_str() {
+            CLIENT_PUBLIC => None,
+            CLIENT_CONFIDENTIAL => Some(generate_secret()),
+            _ => return Err(OAuthClientError::Invalid("Unknown Client Type")),
+        };
+        validate(&UpdateClientRequest::from(&request), &request.client_type)?;
+
+        let client = self
+            .repository
+            .create(&NewOAuthClient {
+                name: request.name,
+                client_type: request.client_type,
+                first_party: request.first_party,
+                require_pushed_authorization_requests: request
+                    .require_pushed_authorization_requests,
+                require_signed_request_object: request.require_signed_request_object,
+                secret_hash: secret.as_deref().map(hash_client_secret),
+                redirect_uris: request.redirect_uris,
+                grant_types: request.grant_types,
+                scopes: request.scopes,
+                audiences: request.audiences,
+                access_ttl: request.access_ttl,
+                refresh_ttl: request.refresh_ttl,
+                jwks: request.jwks,
+            })
+            .await?;
+
+        tracing::info!(client_id = %client.id, admin_id = %admin.user_id, "OAuth client registered");
+        Ok((client, secret))
+    }
+
+    pub async fn update(
+        &self,
+        id: Uuid,
+        request: UpdateClientRequest,
+        admin: &Principal,
+    ) -> Result<OAuthClient, OAuthClientError> {
+        let existing = self.find_by_id(id).await?;
+        validate(&request, &existing.client_type)?;
+
+        let client = self
+            .repository
+            .update(id, &request)
+            .await?
+            .ok_or(OAuthClientError::NotFound)?;
+
+        tracing::info!(client_id = %id, admin_id = %admin.user_id, "OAuth client updated");
+        Ok(client)
+    }
+
+    /// Issues a new secret. The old one keeps working for the configured
+    /// overlap so the client can be redeployed without downtime; rotating
+    /// again within that window retires it at once.
+    pub async fn rotate_secret(
+        &self,
+        id: Uuid,
+        admin: &Principal,
+    ) -> Result<(OAuthClient, String), OAuthClientError> {
+        if !self.find_by_id(id).await?.is_confidential() {
+            return Err(OAuthClientError::PublicClient);
+        }
+
+        let secret = generate_secret();
+        let client = self
+            .repository
+            .rotate_secret(
+                id,
+                &hash_client_secret(&secret),
+                Utc::now() + chrono::Duration::seconds(self.config.overlap),
+            )
+            .await?
+            .ok_or(OAuthClientError::NotFound)?;
+
+        tracing::info!(client_id = %id, admin_id = %admin.user_id, "OAuth client secret rotated");
+        Ok((client, secret))
+    }
+
+    pub async fn delete(&self, id: Uuid, admin: &Principal) -> Result<(), OAuthClientError> {
+        if self.repository.delete(id).await?.rows_affected() == 0 {
+            return Err(OAuthClientError::NotFound);
+        }
+
+        tracing::info!(client_id = %id, admin_id = %admin.user_id, "OAuth client deleted");
+        Ok(())
+    }
+}
+
+fn validate(request: &UpdateClientRequest, client_type: &str) -> Result<(), OAuthClientError> {
+    if request.name.trim().is_empty() || request.name.chars().count() > 100 {
+        return Err(OAuthClientError::Invalid("Invalid Client Name"));
+    }
+    if request.grant_types.is_empty()
+        || !request
+            .grant_types
+            .iter()
+            .all(|grant_type| SUPPORTED_GRANT_TYPES.contains(&grant_type.as_str()))
+    {
+        return Err(OAuthClientError::Invalid("Unsupported Grant Type"));
+    }
+    if client_type != CLIENT_CONFIDENTIAL
+        && request
+            .grant_types
+            .iter()
+            .any(|grant_type| grant_type == GRANT_CLIENT_CREDENTIALS)
+    {
+        return Err(OAuthClientError::Invalid(
+            "Client Credentials Require A Confidential Client",
+        ));
+    }
+    if client_type != CLIENT_CONFIDENTIAL
+        && request
+            .grant_types
+            .iter()
+            .any(|grant_type| grant_type == GRANT_TOKEN_EXCHANGE)
+    {
+        return Err(OAuthClientError::Invalid(
+            "Token Exchange Requires A Confidential Client",
+        ));
+    }
+    if !request
+        .redirect_uris
+        .iter()
+        .all(|uri| is_valid_redirect_uri(uri))
+    {
+        return Err(OAuthClientError::Invalid("Invalid Redirect URI"));
+    }
+    if request.redirect_uris.is_empty()
+        && request
+            .grant_types
+            .iter()
+            .any(|grant_type| grant_type == GRANT_AUTHORIZATION_CODE)
+    {
+        return Err(OAuthClientError::Invalid("Redirect URI Required"));
+    }
+    if !request.scopes.iter().all(|scope| is_valid_scope(scope)) {
+        return Err(OAuthClientError::Invalid("Invalid Scope"));
+    }
+    if request
+        .audiences
+        .iter()
+        .any(|audience| audience.trim().is_empty() || audience.len() > 255)
+    {
+        return Err(OAuthClientError::Invalid("Invalid Audience"));
+    }
+    if [request.access_ttl, request.refresh_ttl]
+        .iter()
+        .flatten()
+        .any(|ttl| *ttl <= 0)
+    {
+        return Err(OAuthClientError::Invalid("Invalid Token Lifetime"));
+    }
+    if request.jwks.as_ref().is_some_and(|jwks| {
+        jwks.keys
+            .iter()
+            .any(|key| matches!(key.algorithm, AlgorithmParameters::OctetKey(_)))
+    }) {
+        return Err(OAuthClientError::Invalid(
+            "Only Public Keys Can Be Registered",
+        ));
+    }
+    if request.require_signed_request_object
+        && request
+            .jwks
+            .as_ref()
+            .is_none_or(|jwks| jwks.keys.is_empty())
+    {
+        return Err(OAuthClientError::Invalid(
+            "Signed Request Objects Require Registered Keys",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Absolute URIs without a fragment (RFC 6749, section 3.1.2). Custom
+/// schemes are allowed for native apps.
+fn is_valid_redirect_uri(uri: &str) -> bool {
+    Url::parse(uri).is_ok_and(|url| url.fragment().is_none() && !url.cannot_be_a_base())
+}
+
+/// A scope-token of RFC 6749, section 3.3.
+fn is_valid_scope(scope: &str) -> bool {
+    !scope.is_empty()
+        && scope
+            .bytes()
+            .all(|byte| matches!(byte, 0x21 | 0x23..=0x5b | 0x5d..=0x7e))
+}
@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::{
+        oauth_grant_repository::OAuthGrantRepository,
+        refresh_token_repository::RefreshTokenRepository,
+    },
+    domain::oauth::OAuthGrant,
+};
+
+/// Scopes users have agreed to share with third-party clients. A stored
+/// grant spares the user the consent screen next time the client asks for
+/// the same scopes.
+pub struct OAuthGrantUseCase<G: OAuthGrantRepository, R: RefreshTokenRepository> {
+    grants: G,
+    refresh_tokens: R,
+}
+
+impl<G: OAuthGrantRepository, R: RefreshTokenRepository> OAuthGrantUseCase<G, R> {
+    pub fn new(grants: G, refresh_tokens: R) -> Self {
+        Self {
+            grants,
+            refresh_tokens,
+        }
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<OAuthGrant>, Box<dyn Error>> {
+        self.grants.find_by_user(user_id).await
+    }
+
+    /// Whether the user already agreed to every scope in the list.
+    pub async fn covers(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+        scope: &str,
+    ) -> Result<bool, Box<dyn Error>> {
+        let grant = self.grants.find(user_id, client_id).await?;
+
+        Ok(grant.is_some_and(|grant| grant.covers(scope)))
+    }
+
+    /// Adds the scopes to the user's grant to the client.
+    pub async fn record(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+        scope: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let scopes: Vec<String> = scope.split_whitespace().map(str::to_string).collect();
+        self.grants.save(user_id, client_id, &scopes).await?;
+
+        tracing::info!(user_id = %user_id, client_id = %client_id, scope, "oauth grant recorded");
+        Ok(())
+    }
+
+    /// Withdraws the grant and ends the refresh tokens it produced, so the
+    /// client loses access once its current access token expires. False
+    /// when there was no grant.
+    pub async fn revoke(&self, user_id: Uuid, client_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let deleted = self.grants.delete(user_id, client_id).await?;
+        self.refresh_tokens
+            .revoke_for_client(user_id, client_id)
+            .await?;
+        if deleted.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        tracing::info!(user_id = %user_id, client_id = %client_id, "oauth grant revoked");
+        Ok(true)
+    }
+}
@@ -0,0 +1,410 @@
+use std::{error::Error, sync::Arc};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repositories::{
+            login_event_repository::LoginEventRepository,
+            one_time_code_repository::OneTimeCodeRepository, user_repository::UserRepository,
+        },
+        services::mailer::{EmailMessage, Mailer},
+        use_cases::{auth::AuthError, login_event::LoginEventUseCase},
+    },
+    config::PasswordlessConfig,
+    domain::{
+        auth_context::AMR_EMAIL,
+        client::ClientContext,
+        login_event::{LoginAttempt, NewLoginEvent},
+        one_time_code::{
+            PasswordlessMethod, EMAIL_CODE_PURPOSE, LOGIN_VERIFY_PURPOSE, MAGIC_LINK_PURPOSE,
+        },
+    },
+    util::{
+        otp::{generate_numeric_code, generate_secret, OneTimeCodeSigner},
+        throttle::LoginThrottle,
+    },
+};
+
+const CODE_DIGITS: u32 = 6;
+
+/// A started passwordless sign-in. `binding` goes back to the browser and
+/// has to be presented again with the code or link.
+pub struct PasswordlessChallenge {
+    pub flow_id: Uuid,
+    pub binding: String,
+    pub expires_in: i64,
+}
+
+/// Sign-in with an emailed one-time code or magic link. Emailed codes also
+/// confirm password sign-ins that the risk checks flagged.
+pub struct PasswordlessUseCase<R: UserRepository, O: OneTimeCodeRepository, E: LoginEventRepository>
+{
+    user_repository: R,
+    repository: O,
+    events: Arc<LoginEventUseCase<E>>,
+    mailer: Arc<dyn Mailer + Send + Sync>,
+    throttle: Arc<LoginThrottle>,
+    signer: OneTimeCodeSigner,
+    config: PasswordlessConfig,
+}
+
+impl<R: UserRepository, O: OneTimeCodeRepository, E: LoginEventRepository>
+    PasswordlessUseCase<R, O, E>
+{
+    pub fn new(
+        user_repository: R,
+        repository: O,
+        events: Arc<LoginEventUseCase<E>>,
+        mailer: Arc<dyn Mailer + Send + Sync>,
+        throttle: Arc<LoginThrottle>,
+        config: PasswordlessConfig,
+    ) -> Self {
+        Self {
+            user_repository,
+            repository,
+            events,
+            mailer,
+            throttle,
+            signer: OneTimeCodeSigner::new(&config.key),
+            config,
+        }
+    }
+
+    /// Lifetime of a code or link in seconds.
+    pub fn code_ttl(&self) -> i64 {
+        self.config.ttl
+    }
+
+    /// Whether the browser binding cookie may only travel over HTTPS.
+    pub fn secure_cookie(&self) -> bool {
+        self.config.secure
+    }
+
+    /// Emails a code or link to the account with `email`.
+    ///
+    /// Unknown addresses get the same answer without an email being sent, so
+    /// the endpoint cannot be used to probe for accounts. Both paths do
+    /// comparable work and the email goes out in the background, so the
+    /// response time gives nothing away either. Delivery failures are logged
+    /// rather than returned for the same reason.
+    pub async fn start(
+        &self,
+        email: &str,
+        method: PasswordlessMethod,
+        client: &ClientContext,
+    ) -> Result<PasswordlessChallenge, AuthError> {
+        self.throttle
+            .attempt(
+                client.ip_address.as_deref(),
+                &format!("passwordless:{}", email),
+            )
+            .map_err(AuthError::Throttled)?;
+
+        let binding = generate_secret();
+        let expires_in = self.config.ttl;
+        let (purpose, secret) = match method {
+            PasswordlessMethod::Code => (EMAIL_CODE_PURPOSE, generate_numeric_code(CODE_DIGITS)),
+            PasswordlessMethod::Link => (MAGIC_LINK_PURPOSE, generate_secret()),
+        };
+        let code_hash = self.signer.hash(&secret);
+        let binding_hash = self.signer.hash(&binding);
+
+        let Some(user) = self.user_repository.find_by_email(email).await? else {
+            // Stands in for storing the code.
+            self.repository.purge_expired().await?;
+            return Ok(PasswordlessChallenge {
+                flow_id: Uuid::new_v4(),
+                binding,
+                expires_in,
+            });
+        };
+
+        let flow_id = self
+            .repository
+            .create(
+                user.id,
+                purpose,
+                &code_hash,
+                &binding_hash,
+                Utc::now() + chrono::Duration::seconds(expires_in),
+            )
+            .await?;
+
+        let minutes = (expires_in / 60).max(1);
+        let message = match method {
+            PasswordlessMethod::Code => EmailMessage {
+                to: user.email,
+                subject: "Your sign-in code".to_string(),
+                body: format!(
+                    "Your sign-in code is {}. It expires in {} minutes.",
+                    secret, minutes
+                ),
+            },
+            PasswordlessMethod::Link => EmailMessage {
+                to: user.email,
+                subject: "Your sign-in link".to_string(),
+                body: format!(
+                    "Open this link in the browser you signed in from: {}?token={}\nIt expires in {} minutes.",
+                    self.config.url,
+                    self.signer.sign_link(flow_id, &secret),
+                    minutes
+                ),
+            },
+        };
+        let mailer = Arc::clone(&self.mailer);
+        tokio::spawn(async move {
+            if let Err(err) = mailer.send(message).await {
+                tracing::error!(user_id = %user.id, error = %err, "failed to send passwordless email");
+            }
+        });
+
+        Ok(PasswordlessChallenge {
+            flow_id,
+            binding,
+            expires_in,
+        })
+    }
+
+    /// Redeems an emailed code and returns the user it signs in.
+    pub async fn verify_code(
+        &self,
+        flow_id: Uuid,
+        code: &str,
+        binding: &str,
+        client: &ClientContext,
+    ) -> Result<Uuid, AuthError> {
+        self.throttle
+            .attempt(
+                client.ip_address.as_deref(),
+                &format!("passwordless:{}", flow_id),
+            )
+            .map_err(AuthError::Throttled)?;
+
+        self.redeem(flow_id, EMAIL_CODE_PURPOSE, code.trim(), binding, client)
+            .await
+    }
+
+    /// Redeems a magic link and returns the user it signs in.
+    pub async fn verify_link(
+        &self,
+        token: &str,
+        binding: &str,
+        client: &ClientContext,
+    ) -> Result<Uuid, AuthError> {
+        let (flow_id, secret) = self
+            .signer
+            .verify_link(token)
+            .ok_or(AuthError::InvalidCode)?;
+        self.throttle
+            .attempt(
+                client.ip_address.as_deref(),
+                &format!("passwordless:{}", flow_id),
+            )
+            .map_err(AuthError::Throttled)?;
+
+        self.redeem(flow_id, MAGIC_LINK_PURPOSE, &secret, binding, client)
+            .await
+    }
+
+    /// Emails a code that confirms a flagged sign-in. The code is bound to
+    /// the address it went to, so changing the email invalidates it.
+    pub async fn send_verification_code(
+        &self,
+        user_id: Uuid,
+        client: &ClientContext,
+    ) -> Result<(), AuthError> {
+        self.throttle
+            .attempt(
+                client.ip_address.as_deref(),
+                &format!("verification:{}", user_id),
+            )
+            .map_err(AuthError::Throttled)?;
+
+        let user = self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(AuthError::InvalidMfa)?;
+
+        let code = generate_numeric_code(CODE_DIGITS);
+        self.repository
+            .create(
+                user.id,
+                LOGIN_VERIFY_PURPOSE,
+                &self.signer.hash(&code),
+                &self.signer.hash(&user.email),
+                Utc::now() + chrono::Duration::seconds(self.config.ttl),
+            )
+            .await?;
+
+        self.mailer
+            .send(EmailMessage {
+                to: user.email,
+                subject: "Confirm your sign-in".to_string(),
+                body: format!(
+                    "Someone signed in to your account from a new device or location. \
+                     If it was you, enter {} to continue. It expires in {} minutes.",
+                    code,
+                    (self.config.ttl / 60).max(1)
+                ),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Checks a code sent by [`Self::send_verification_code`]. Every attempt
+    /// is counted before the code is compared.
+    pub async fn verify_verification_code(
+        &self,
+        user_id: Uuid,
+        code: &str,
+    ) -> Result<bool, Box<dyn Error>> {
+        let Some(user) = self.user_repository.find_by_id(user_id).await? else {
+            return Ok(false);
+        };
+        let Some(stored) = self
+            .repository
+            .find_latest(user_id, LOGIN_VERIFY_PURPOSE)
+            .await?
+            .filter(|stored| stored.is_usable())
+        else {
+            return Ok(false);
+        };
+
+        if self.repository.record_attempt(stored.id).await? > self.config.attempts {
+            return Ok(false);
+        }
+
+        if stored.binding_hash != self.signer.hash(&user.email)
+            || stored.code_hash != self.signer.hash(code.trim())
+        {
+            return Ok(false);
+        }
+
+        Ok(self.repository.consume(stored.id).await?.rows_affected() == 1)
+    }
+
+    /// Failed redemptions are recorded as login events here. Successful ones
+    /// are recorded once the login itself completes.
+    async fn redeem(
+        &self,
+        flow_id: Uuid,
+        purpose: &str,
+        secret: &str,
+        binding: &str,
+        client: &ClientContext,
+    ) -> Result<Uuid, AuthError> {
+        let mut attempt = LoginAttempt::new(AMR_EMAIL);
+        let result = self
+            .check_code(flow_id, purpose, secret, binding, &mut attempt)
+            .await;
+
+        if let Err(err) = &result {
+            if let Some(reason) = err.failure_reason(&attempt) {
+                self.events
+                    .record(NewLoginEvent::new(attempt, Some(reason), client))
+                    .await;
+            }
+        }
+        result
+    }
+
+    /// Every attempt is counted before the secret is compared, so a code is
+    /// burned after `attempts` guesses even if they arrive concurrently.
+    async fn check_code(
+        &self,
+        flow_id: Uuid,
+        purpose: &str,
+        secret: &str,
+        binding: &str,
+        attempt: &mut LoginAttempt,
+    ) -> Result<Uuid, AuthError> {
+        let code = self
+            .repository
+            .find(flow_id)
+            .await?
+            .filter(|code| code.purpose == purpose && code.is_usable())
+            .ok_or(AuthError::InvalidCode)?;
+        attempt.user_id = Some(code.user_id);
+
+        if self.repository.record_attempt(code.id).await? > self.config.attempts {
+            return Err(AuthError::InvalidCode);
+        }
+
+        if code.binding_hash != self.signer.hash(binding)
+            || code.code_hash != self.signer.hash(secret)
+        {
+            return Err(AuthError::InvalidCode);
+        }
+
+        if self.repository.consume(code.id).await?.rows_affected() == 0 {
+            return Err(AuthError::InvalidCode);
+        }
+
+        Ok(code.user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future, time::Duration};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{
+        config::{LoginEventConfig, ThrottleConfig},
+        infrastructure::repositories::{
+            postgres_login_event_repo::PostgresLoginEventRepository,
+            postgres_one_time_code_repo::PostgresOneTimeCodeRepository,
+            postgres_user_repo::PostgresUserRepository,
+            test_support::{create_user, reset_tables, setup_database},
+        },
+    };
+
+    /// Never finishes sending.
+    struct StalledMailer;
+
+    #[async_trait]
+    impl Mailer for StalledMailer {
+        async fn send(&self, _message: EmailMessage) -> Result<(), Box<dyn Error>> {
+            future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_does_not_wait_for_the_mailer() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        create_user(&pool, "passwordless").await;
+        let use_case = PasswordlessUseCase::new(
+            PostgresUserRepository::new(pool.clone()),
+            PostgresOneTimeCodeRepository::new(pool.clone()),
+            Arc::new(LoginEventUseCase::new(
+                PostgresLoginEventRepository::new(pool.clone()),
+                LoginEventConfig::default(),
+            )),
+            Arc::new(StalledMailer),
+            Arc::new(LoginThrottle::new(&ThrottleConfig::default())),
+            PasswordlessConfig::default(),
+        );
+        let repository = PostgresOneTimeCodeRepository::new(pool);
+        let client = ClientContext::default();
+
+        for email in ["passwordless@example.com", "nobody@example.com"] {
+            let challenge = tokio::time::timeout(
+                Duration::from_secs(5),
+                use_case.start(email, PasswordlessMethod::Code, &client),
+            )
+            .await
+            .expect("start waited for the mailer")
+            .unwrap();
+
+            let stored = repository.find(challenge.flow_id).await.unwrap();
+            assert_eq!(email == "passwordless@example.com", stored.is_some());
+        }
+    }
+}
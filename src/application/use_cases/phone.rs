@@ -0,0 +1,274 @@
+use std::{error::Error, fmt, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repositories::{
+            one_time_code_repository::OneTimeCodeRepository, user_repository::UserRepository,
+        },
+        services::sms_sender::{SmsMessage, SmsSender},
+    },
+    config::SmsConfig,
+    domain::one_time_code::{PHONE_VERIFY_PURPOSE, SMS_MFA_PURPOSE},
+    util::{
+        otp::{generate_numeric_code, OneTimeCodeSigner},
+        phone::normalize_e164,
+        throttle::Throttle,
+    },
+};
+
+const CODE_DIGITS: u32 = 6;
+
+#[derive(Debug)]
+pub enum PhoneError {
+    InvalidPhoneNumber,
+    UserNotFound,
+    NoPhoneNumber,
+    InvalidCode,
+    Throttled(Duration),
+    Internal(Box<dyn Error>),
+}
+
+impl fmt::Display for PhoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhoneError::InvalidPhoneNumber => write!(f, "Phone Number Must Be In E.164 Format"),
+            PhoneError::UserNotFound => write!(f, "User Not Found"),
+            PhoneError::NoPhoneNumber => write!(f, "No Phone Number To Verify"),
+            PhoneError::InvalidCode => write!(f, "Invalid or Expired Code"),
+            PhoneError::Throttled(_) => write!(f, "Too Many Codes Requested"),
+            PhoneError::Internal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for PhoneError {}
+
+impl From<Box<dyn Error>> for PhoneError {
+    fn from(err: Box<dyn Error>) -> Self {
+        PhoneError::Internal(err)
+    }
+}
+
+/// Phone number verification and SMS one-time codes. A verified number
+/// doubles as a second factor.
+pub struct PhoneUseCase<R: UserRepository, O: OneTimeCodeRepository> {
+    user_repository: R,
+    repository: O,
+    sender: Arc<dyn SmsSender + Send + Sync>,
+    throttle: Arc<Throttle>,
+    signer: OneTimeCodeSigner,
+    config: SmsConfig,
+}
+
+impl<R: UserRepository, O: OneTimeCodeRepository> PhoneUseCase<R, O> {
+    pub fn new(
+        user_repository: R,
+        repository: O,
+        sender: Arc<dyn SmsSender + Send + Sync>,
+        throttle: Arc<Throttle>,
+        config: SmsConfig,
+    ) -> Self {
+        Self {
+            user_repository,
+            repository,
+            sender,
+            throttle,
+            signer: OneTimeCodeSigner::new(&config.key),
+            config,
+        }
+    }
+
+    /// Lifetime of a code in seconds.
+    pub fn code_ttl(&self) -> i64 {
+        self.config.ttl
+    }
+
+    /// Stores an unverified number and texts a code to it. Returns the
+    /// normalized number.
+    pub async fn set_phone_number(
+        &self,
+        user_id: Uuid,
+        phone_number: &str,
+    ) -> Result<String, PhoneError> {
+        let phone_number = normalize_e164(phone_number).ok_or(PhoneError::InvalidPhoneNumber)?;
+
+        let result = self
+            .user_repository
+            .update_phone_number(user_id, Some(&phone_number))
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(PhoneError::UserNotFound);
+        }
+
+        self.send_code(user_id, &phone_number, PHONE_VERIFY_PURPOSE)
+            .await?;
+        Ok(phone_number)
+    }
+
+    /// Texts a new verification code to the stored, unverified number.
+    pub async fn resend_verification(&self, user_id: Uuid) -> Result<(), PhoneError> {
+        let user = self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(PhoneError::UserNotFound)?;
+        let phone_number = user
+            .phone_number
+            .filter(|_| user.phone_verified_at.is_none())
+            .ok_or(PhoneError::NoPhoneNumber)?;
+
+        self.send_code(user_id, &phone_number, PHONE_VERIFY_PURPOSE)
+            .await
+    }
+
+    pub async fn verify_phone_number(&self, user_id: Uuid, code: &str) -> Result<(), PhoneError> {
+        let user = self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(PhoneError::UserNotFound)?;
+        let phone_number = user.phone_number.ok_or(PhoneError::NoPhoneNumber)?;
+
+        if !self
+            .redeem(user_id, &phone_number, PHONE_VERIFY_PURPOSE, code)
+            .await?
+        {
+            return Err(PhoneError::InvalidCode);
+        }
+
+        let result = self
+            .user_repository
+            .mark_phone_verified(user_id, &phone_number)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(PhoneError::InvalidCode);
+        }
+
+        tracing::info!(user_id = %user_id, "phone number verified");
+        Ok(())
+    }
+
+    /// Returns `false` when the user had no phone number.
+    pub async fn remove_phone_number(&self, user_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let Some(user) = self.user_repository.find_by_id(user_id).await? else {
+            return Ok(false);
+        };
+        if user.phone_number.is_none() {
+            return Ok(false);
+        }
+
+        self.user_repository
+            .update_phone_number(user_id, None)
+            .await?;
+        Ok(true)
+    }
+
+    pub async fn has_verified_phone(&self, user_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        Ok(self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .is_some_and(|user| user.verified_phone_number().is_some()))
+    }
+
+    /// Texts a second-factor code to the verified number. Returns the number
+    /// it was sent to.
+    pub async fn send_mfa_code(&self, user_id: Uuid) -> Result<String, PhoneError> {
+        let user = self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(PhoneError::UserNotFound)?;
+        let phone_number = user
+            .verified_phone_number()
+            .ok_or(PhoneError::NoPhoneNumber)?
+            .to_string();
+
+        self.send_code(user_id, &phone_number, SMS_MFA_PURPOSE)
+            .await?;
+        Ok(phone_number)
+    }
+
+    /// Checks a second-factor code. Codes are bound to the number they were
+    /// sent to, so changing the number invalidates them.
+    pub async fn verify_mfa_code(&self, user_id: Uuid, code: &str) -> Result<bool, Box<dyn Error>> {
+        let Some(user) = self.user_repository.find_by_id(user_id).await? else {
+            return Ok(false);
+        };
+        let Some(phone_number) = user.verified_phone_number() else {
+            return Ok(false);
+        };
+
+        self.redeem(user_id, phone_number, SMS_MFA_PURPOSE, code)
+            .await
+    }
+
+    async fn send_code(
+        &self,
+        user_id: Uuid,
+        phone_number: &str,
+        purpose: &str,
+    ) -> Result<(), PhoneError> {
+        self.throttle
+            .attempt(&user_id.to_string())
+            .map_err(PhoneError::Throttled)?;
+
+        let code = generate_numeric_code(CODE_DIGITS);
+        self.repository
+            .create(
+                user_id,
+                purpose,
+                &self.signer.hash(&code),
+                &self.signer.hash(phone_number),
+                Utc::now() + chrono::Duration::seconds(self.config.ttl),
+            )
+            .await?;
+
+        self.sender
+            .send(SmsMessage {
+                to: phone_number.to_string(),
+                body: format!(
+                    "Your verification code is {}. It expires in {} minutes.",
+                    code,
+                    (self.config.ttl / 60).max(1)
+                ),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts the attempt before comparing, so a code is burned after
+    /// `attempts` wrong guesses.
+    async fn redeem(
+        &self,
+        user_id: Uuid,
+        phone_number: &str,
+        purpose: &str,
+        code: &str,
+    ) -> Result<bool, Box<dyn Error>> {
+        let Some(stored) = self
+            .repository
+            .find_latest(user_id, purpose)
+            .await?
+            .filter(|stored| stored.is_usable())
+        else {
+            return Ok(false);
+        };
+
+        if self.repository.record_attempt(stored.id).await? > self.config.attempts {
+            return Ok(false);
+        }
+
+        if stored.binding_hash != self.signer.hash(phone_number)
+            || stored.code_hash != self.signer.hash(code.trim())
+        {
+            return Ok(false);
+        }
+
+        Ok(self.repository.consume(stored.id).await?.rows_affected() == 1)
+    }
+}
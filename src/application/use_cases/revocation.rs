@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::revoked_token_repository::RevokedTokenRepository,
+    config::RevocationConfig,
+};
+
+/// Upper bound on cached token ids before stale entries are swept.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct CachedStatus {
+    revoked: bool,
+    expires_at: DateTime<Utc>,
+    checked_at: Instant,
+}
+
+/// Denylist of token ids (`jti`) revoked before their expiry.
+///
+/// Revocations are permanent until the token expires, so a revoked answer is
+/// cached for the token's lifetime. A not-revoked answer is only reused for
+/// `cache` seconds, after which the database is asked again.
+pub struct RevocationUseCase<D: RevokedTokenRepository> {
+    repository: D,
+    cache: Mutex<HashMap<Uuid, CachedStatus>>,
+    ttl: Duration,
+}
+
+impl<D: RevokedTokenRepository> RevocationUseCase<D> {
+    pub fn new(repository: D, config: RevocationConfig) -> Self {
+        Self {
+            repository,
+            cache: Mutex::new(HashMap::new()),
+            ttl: Duration::from_secs(config.cache),
+        }
+    }
+
+    /// Revokes a token until `expires_at`, its `exp` claim as a timestamp.
+    pub async fn revoke(
+        &self,
+        jti: Uuid,
+        user_id: Option<Uuid>,
+        expires_at: i64,
+    ) -> Result<(), Box<dyn Error>> {
+        let expires_at = DateTime::from_timestamp(expires_at, 0).unwrap_or_else(Utc::now);
+        self.repository.revoke(jti, user_id, expires_at).await?;
+        self.remember(jti, true, expires_at);
+
+        tracing::info!(jti = %jti, user_id = ?user_id, "token revoked");
+        Ok(())
+    }
+
+    /// Whether the token with this id and `exp` has been revoked.
+    pub async fn is_revoked(&self, jti: Uuid, expires_at: i64) -> Result<bool, Box<dyn Error>> {
+        if let Some(revoked) = self.cached(jti) {
+            return Ok(revoked);
+        }
+
+        let revoked = self.repository.is_revoked(jti).await?;
+        let expires_at = DateTime::from_timestamp(expires_at, 0).unwrap_or_else(Utc::now);
+        self.remember(jti, revoked, expires_at);
+        Ok(revoked)
+    }
+
+    fn cached(&self, jti: Uuid) -> Option<bool> {
+        let cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        let status = cache.get(&jti)?;
+        if status.revoked || status.checked_at.elapsed() < self.ttl {
+            Some(status.revoked)
+        } else {
+            None
+        }
+    }
+
+    fn remember(&self, jti: Uuid, revoked: bool, expires_at: DateTime<Utc>) {
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        if cache.len() > SWEEP_THRESHOLD {
+            let now = Utc::now();
+            cache.retain(|_, status| {
+                status.expires_at > now
+                    && (status.revoked || status.checked_at.elapsed() < self.ttl)
+            });
+        }
+
+        cache.insert(
+            jti,
+            CachedStatus {
+                revoked,
+                expires_at,
+                checked_at: Instant::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use sqlx::postgres::PgQueryResult;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingRepository {
+        lookups: AtomicUsize,
+        revoked: Mutex<Vec<Uuid>>,
+    }
+
+    #[async_trait]
+    impl RevokedTokenRepository for CountingRepository {
+        async fn revoke(
+            &self,
+            jti: Uuid,
+            _user_id: Option<Uuid>,
+            _expires_at: DateTime<Utc>,
+        ) -> Result<PgQueryResult, Box<dyn Error>> {
+            self.revoked.lock().unwrap().push(jti);
+            Ok(PgQueryResult::default())
+        }
+
+        async fn is_revoked(&self, jti: Uuid) -> Result<bool, Box<dyn Error>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.revoked.lock().unwrap().contains(&jti))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_answers_are_cached() {
+        let revocation = RevocationUseCase::new(
+            CountingRepository::default(),
+            RevocationConfig { cache: 60 },
+        );
+        let jti = Uuid::new_v4();
+        let exp = Utc::now().timestamp() + 3600;
+
+        assert!(!revocation.is_revoked(jti, exp).await.unwrap());
+        assert!(!revocation.is_revoked(jti, exp).await.unwrap());
+        assert_eq!(1, revocation.repository.lookups.load(Ordering::SeqCst));
+
+        revocation.revoke(jti, None, exp).await.unwrap();
+        assert!(revocation.is_revoked(jti, exp).await.unwrap());
+        assert_eq!(1, revocation.repository.lookups.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_zero_cache_always_asks() {
+        let revocation =
+            RevocationUseCase::new(CountingRepository::default(), RevocationConfig { cache: 0 });
+        let jti = Uuid::new_v4();
+        let exp = Utc::now().timestamp() + 3600;
+
+        revocation.is_revoked(jti, exp).await.unwrap();
+        revocation.is_revoked(jti, exp).await.unwrap();
+        assert_eq!(2, revocation.repository.lookups.load(Ordering::SeqCst));
+    }
+}
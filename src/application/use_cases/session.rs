@@ -0,0 +1,155 @@
+use std::error::Error;
+
+use chrono::Utc;
+use data_encoding::HEXLOWER;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::session_repository::SessionRepository,
+    config::SessionConfig,
+    domain::{
+        auth_context::AuthContext,
+        client::ClientContext,
+        session::{NewSession, Session, SessionDevice},
+    },
+    util::{otp::generate_secret, user_agent::parse_user_agent},
+};
+
+/// Activity within this many seconds of the last recorded request does not
+/// write to the database again.
+const TOUCH_INTERVAL: i64 = 60;
+
+/// Cookie-backed sessions for browser clients. Only a hash of the session id
+/// is stored, so the table cannot be used to hijack sessions.
+pub struct SessionUseCase<S: SessionRepository> {
+    repository: S,
+    config: SessionConfig,
+}
+
+impl<S: SessionRepository> SessionUseCase<S> {
+    pub fn new(repository: S, config: SessionConfig) -> Self {
+        Self { repository, config }
+    }
+
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Starts a session and returns the cookie value with it.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        roles: &[String],
+        auth: &AuthContext,
+        client: &ClientContext,
+    ) -> Result<(String, Session), Box<dyn Error>> {
+        let user_agent = client
+            .user_agent
+            .as_deref()
+            .map(parse_user_agent)
+            .unwrap_or_default();
+        let token = generate_secret();
+        let session = self
+            .repository
+            .create(&NewSession {
+                user_id,
+                token_hash: hash_token(&token),
+                csrf_token: generate_secret(),
+                roles: roles.to_vec(),
+                auth: auth.clone(),
+                device: SessionDevice {
+                    user_agent: client.user_agent.clone(),
+                    browser: user_agent.browser,
+                    os: user_agent.os,
+                    ip_address: client.ip_address.clone(),
+                },
+                expires_at: Utc::now() + chrono::Duration::seconds(self.config.ttl),
+            })
+            .await?;
+
+        tracing::info!(user_id = %user_id, session_id = %session.id, "session started");
+        Ok((token, session))
+    }
+
+    /// Looks up a live session and slides its idle timeout forward.
+    pub async fn authenticate(&self, token: &str) -> Result<Option<Session>, Box<dyn Error>> {
+        let Some(mut session) = self
+            .repository
+            .find_by_token_hash(&hash_token(token))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        if session.valid_until(self.config.idle) <= now {
+            self.repository.delete(session.id).await?;
+            return Ok(None);
+        }
+
+        if (now - session.last_seen_at).num_seconds() >= TOUCH_INTERVAL {
+            self.repository.touch(session.id).await?;
+            session.last_seen_at = now;
+        }
+
+        Ok(Some(session))
+    }
+
+    /// Sessions of the user that are still live.
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<Session>, Box<dyn Error>> {
+        let now = Utc::now();
+        Ok(self
+            .repository
+            .find_by_user(user_id)
+            .await?
+            .into_iter()
+            .filter(|session| session.valid_until(self.config.idle) > now)
+            .collect())
+    }
+
+    /// Ends one of the user's sessions. Returns `false` when it does not
+    /// exist or belongs to someone else.
+    pub async fn revoke(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let revoked = self
+            .repository
+            .delete_for_user(user_id, session_id)
+            .await?
+            .rows_affected()
+            > 0;
+        if revoked {
+            tracing::info!(user_id = %user_id, session_id = %session_id, "session revoked");
+        }
+        Ok(revoked)
+    }
+
+    /// Signs the user out everywhere. Returns how many sessions were ended.
+    pub async fn revoke_all(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>> {
+        let revoked = self
+            .repository
+            .delete_all_for_user(user_id)
+            .await?
+            .rows_affected();
+        tracing::info!(user_id = %user_id, revoked, "all sessions revoked");
+        Ok(revoked)
+    }
+
+    /// Ends the session behind `token`. Returns `false` when there was none.
+    pub async fn destroy(&self, token: &str) -> Result<bool, Box<dyn Error>> {
+        let Some(session) = self
+            .repository
+            .find_by_token_hash(&hash_token(token))
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        self.repository.delete(session.id).await?;
+        tracing::info!(user_id = %session.user_id, session_id = %session.id, "session ended");
+        Ok(true)
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
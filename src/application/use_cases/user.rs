@@ -1,37 +1,42 @@
-use std::error::Error;
+use std::{error::Error, sync::Arc};
 
 use uuid::Uuid;
 
 use crate::{
     application::repositories::user_repository::UserRepository,
-    config::PwdConfig,
     domain::user::{User, UserId},
-    dto::user_dto::{CreateRequest, UpdateRequest},
-    util::pwd::Pwd,
+    dto::user_dto::{CreateRequest, ImportRequest, UpdateRequest},
+    util::pwd_pool::PwdPool,
 };
 
 pub struct UserUseCase<R: UserRepository> {
     repository: R,
+    pwd_pool: Arc<PwdPool>,
 }
 
 impl<R: UserRepository> UserUseCase<R> {
-    pub fn new(repository: R) -> Self {
-        Self { repository }
+    pub fn new(repository: R, pwd_pool: Arc<PwdPool>) -> Self {
+        Self {
+            repository,
+            pwd_pool,
+        }
     }
 
-    pub async fn create(
-        &self,
-        mut new_user: CreateRequest,
-        pwd_cfg: PwdConfig,
-    ) -> Result<UserId, Box<dyn Error>> {
-        let pwd = Pwd::new(&pwd_cfg);
-        new_user.password = pwd
+    pub async fn create(&self, mut new_user: CreateRequest) -> Result<UserId, Box<dyn Error>> {
+        new_user.password = self
+            .pwd_pool
             .generate_password_hash(new_user.password.as_str())
-            .unwrap();
+            .await?;
 
         self.repository.create(&new_user).await
     }
 
+    /// Stores a user whose password was hashed elsewhere. The hash is kept
+    /// as-is and upgraded to Argon2id on the user's first successful login.
+    pub async fn import(&self, user: ImportRequest) -> Result<UserId, Box<dyn Error>> {
+        self.repository.create(&user.into()).await
+    }
+
     pub async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>> {
         self.repository.find_all().await
     }
@@ -51,7 +56,7 @@ impl<R: UserRepository> UserUseCase<R> {
     pub async fn delete(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>> {
         match self.repository.delete(user_id).await {
             Ok(result) => Ok(result.rows_affected()),
-            Err(err) => Err(err)
+            Err(err) => Err(err),
         }
     }
 }
@@ -0,0 +1,276 @@
+use std::error::Error;
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::repositories::{
+        user_repository::UserRepository, webauthn_repository::WebauthnRepository,
+    },
+    config::WebauthnConfig,
+    domain::webauthn::{
+        WebauthnAssertion, WebauthnCredential, AUTHENTICATION_CEREMONY, REGISTRATION_CEREMONY,
+    },
+    dto::webauthn_dto::{
+        AuthenticatorSelection, CreationOptionsResponse, CredentialDescriptor, CredentialParameter,
+        PublicKeyCreationOptions, PublicKeyRequestOptions, RegisterCredentialRequest,
+        RelyingPartyEntity, RequestOptionsResponse, UserEntity, PUBLIC_KEY_TYPE,
+    },
+    util::webauthn::{
+        decode_base64url, encode_base64url, RelyingParty, WebauthnError, SUPPORTED_ALGORITHMS,
+    },
+};
+
+const DEFAULT_CREDENTIAL_NAME: &str = "Passkey";
+
+fn new_challenge() -> Vec<u8> {
+    let mut challenge = vec![0_u8; 32];
+    OsRng.fill_bytes(&mut challenge);
+    challenge
+}
+
+pub struct WebauthnUseCase<R: UserRepository, W: WebauthnRepository> {
+    user_repository: R,
+    repository: W,
+    relying_party: RelyingParty,
+    config: WebauthnConfig,
+}
+
+impl<R: UserRepository, W: WebauthnRepository> WebauthnUseCase<R, W> {
+    pub fn new(user_repository: R, repository: W, config: WebauthnConfig) -> Self {
+        Self {
+            user_repository,
+            repository,
+            relying_party: RelyingParty::new(&config.rpid, &config.origin),
+            config,
+        }
+    }
+
+    fn timeout_ms(&self) -> u64 {
+        self.config.ttl.max(0) as u64 * 1000
+    }
+
+    /// Starts registering a new credential. Returns `None` for unknown users.
+    pub async fn registration_options(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<CreationOptionsResponse>, Box<dyn Error>> {
+        let Some(user) = self.user_repository.find_by_id(user_id).await? else {
+            return Ok(None);
+        };
+
+        let challenge = new_challenge();
+        let expires_at = Utc::now() + Duration::seconds(self.config.ttl);
+        let challenge_id = self
+            .repository
+            .create_challenge(Some(user_id), REGISTRATION_CEREMONY, &challenge, expires_at)
+            .await?;
+        let existing = self.repository.find_credentials(user_id).await?;
+
+        Ok(Some(CreationOptionsResponse {
+            challenge_id,
+            public_key: PublicKeyCreationOptions {
+                rp: RelyingPartyEntity {
+                    id: self.config.rpid.clone(),
+                    name: self.config.name.clone(),
+                },
+                user: UserEntity {
+                    id: encode_base64url(user.id.as_bytes()),
+                    name: user.username.clone(),
+                    display_name: user.username,
+                },
+                challenge: encode_base64url(&challenge),
+                pub_key_cred_params: SUPPORTED_ALGORITHMS
+                    .iter()
+                    .map(|alg| CredentialParameter {
+                        kind: PUBLIC_KEY_TYPE.to_string(),
+                        alg: *alg,
+                    })
+                    .collect(),
+                timeout: self.timeout_ms(),
+                attestation: "none".to_string(),
+                authenticator_selection: AuthenticatorSelection {
+                    resident_key: "preferred".to_string(),
+                    user_verification: "preferred".to_string(),
+                },
+                exclude_credentials: existing.iter().map(CredentialDescriptor::from).collect(),
+            },
+        }))
+    }
+
+    /// Finishes registration. Verification failures are returned as
+    /// [`WebauthnError`].
+    pub async fn register(
+        &self,
+        user_id: Uuid,
+        request: RegisterCredentialRequest,
+    ) -> Result<WebauthnCredential, Box<dyn Error>> {
+        let challenge = self
+            .repository
+            .take_challenge(request.challenge_id, REGISTRATION_CEREMONY)
+            .await?
+            .filter(|challenge| {
+                challenge.user_id == Some(user_id) && challenge.expires_at > Utc::now()
+            })
+            .ok_or(WebauthnError::UnknownChallenge)?;
+
+        let response = request.credential.response;
+        let registered = self.relying_party.verify_registration(
+            &challenge.challenge,
+            &decode_base64url(&response.client_data_json)?,
+            &decode_base64url(&response.attestation_object)?,
+            false,
+        )?;
+
+        if self
+            .repository
+            .find_credential(&registered.credential_id)
+            .await?
+            .is_some()
+        {
+            return Err(Box::new(WebauthnError::AlreadyRegistered));
+        }
+
+        let name = request
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| DEFAULT_CREDENTIAL_NAME.to_string());
+        let credential = self
+            .repository
+            .create_credential(
+                user_id,
+                &registered.credential_id,
+                &registered.public_key,
+                registered.sign_count as i64,
+                &name,
+            )
+            .await?;
+
+        tracing::info!(user_id = %user_id, credential_id = %credential.id, "WebAuthn credential registered");
+        Ok(credential)
+    }
+
+    /// Starts an authentication ceremony. With a user the challenge is bound
+    /// to them and lists their credentials; without one any discoverable
+    /// credential may answer. Passwordless sign-in requires user verification.
+    pub async fn authentication_options(
+        &self,
+        user_id: Option<Uuid>,
+        passwordless: bool,
+    ) -> Result<RequestOptionsResponse, Box<dyn Error>> {
+        let challenge = new_challenge();
+        let expires_at = Utc::now() + Duration::seconds(self.config.ttl);
+        let challenge_id = self
+            .repository
+            .create_challenge(user_id, AUTHENTICATION_CEREMONY, &challenge, expires_at)
+            .await?;
+        let allow_credentials = match user_id {
+            Some(user_id) => self
+                .repository
+                .find_credentials(user_id)
+                .await?
+                .iter()
+                .map(CredentialDescriptor::from)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(RequestOptionsResponse {
+            challenge_id,
+            public_key: PublicKeyRequestOptions {
+                challenge: encode_base64url(&challenge),
+                rp_id: self.config.rpid.clone(),
+                timeout: self.timeout_ms(),
+                user_verification: if passwordless {
+                    "required"
+                } else {
+                    "preferred"
+                }
+                .to_string(),
+                allow_credentials,
+            },
+        })
+    }
+
+    /// Verifies an assertion and returns the user it belongs to, or `None`
+    /// when it does not check out. When `user_id` is given the assertion must
+    /// come from one of that user's credentials.
+    pub async fn authenticate(
+        &self,
+        assertion: &WebauthnAssertion,
+        user_id: Option<Uuid>,
+        passwordless: bool,
+    ) -> Result<Option<Uuid>, Box<dyn Error>> {
+        let Some(challenge) = self
+            .repository
+            .take_challenge(assertion.challenge_id, AUTHENTICATION_CEREMONY)
+            .await?
+            .filter(|challenge| challenge.user_id == user_id && challenge.expires_at > Utc::now())
+        else {
+            return Ok(None);
+        };
+
+        let Some(credential) = self
+            .repository
+            .find_credential(&assertion.credential_id)
+            .await?
+            .filter(|credential| user_id.is_none_or(|user_id| credential.user_id == user_id))
+            .filter(|credential| {
+                assertion
+                    .user_handle
+                    .as_ref()
+                    .is_none_or(|handle| handle.as_slice() == credential.user_id.as_bytes())
+            })
+        else {
+            return Ok(None);
+        };
+
+        match self.relying_party.verify_assertion(
+            &challenge.challenge,
+            assertion,
+            &credential.public_key,
+            credential.sign_count.clamp(0, u32::MAX as i64) as u32,
+            passwordless,
+        ) {
+            Ok(sign_count) => {
+                self.repository
+                    .update_sign_count(credential.id, sign_count as i64)
+                    .await?;
+                Ok(Some(credential.user_id))
+            }
+            Err(err) => {
+                tracing::warn!(credential_id = %credential.id, error = %err, "WebAuthn assertion rejected");
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn has_credentials(&self, user_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        Ok(!self.repository.find_credentials(user_id).await?.is_empty())
+    }
+
+    pub async fn find_credentials(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<WebauthnCredential>, Box<dyn Error>> {
+        self.repository.find_credentials(user_id).await
+    }
+
+    pub async fn rename(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        name: &str,
+    ) -> Result<Option<WebauthnCredential>, Box<dyn Error>> {
+        self.repository
+            .rename_credential(user_id, id, name.trim())
+            .await
+    }
+
+    pub async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<u64, Box<dyn Error>> {
+        let result = self.repository.delete_credential(user_id, id).await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -26,6 +26,34 @@ pub struct AppConfig {
     pub loki: LokiConfig,
     pub database: DatabaseConfig,
     pub pwd: PwdConfig,
+    pub token: TokenConfig,
+    #[serde(default)]
+    pub lockout: LockoutConfig,
+    #[serde(default)]
+    pub throttle: ThrottleConfig,
+    #[serde(default)]
+    pub ratelimit: RateLimitConfig,
+    pub mfa: MfaConfig,
+    #[serde(default)]
+    pub webauthn: WebauthnConfig,
+    #[serde(default)]
+    pub mail: MailConfig,
+    pub passwordless: PasswordlessConfig,
+    pub sms: SmsConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub revocation: RevocationConfig,
+    #[serde(default)]
+    pub events: LoginEventConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    #[serde(default)]
+    pub oauth: OAuthConfig,
+    #[serde(default)]
+    pub dpop: DpopConfig,
+    #[serde(default)]
+    pub proxy: ProxyConfig,
 }
 
 impl Default for AppConfig {
@@ -37,6 +65,22 @@ impl Default for AppConfig {
             loki: LokiConfig::default(),
             database: DatabaseConfig::default(),
             pwd: PwdConfig::default(),
+            token: TokenConfig::default(),
+            lockout: LockoutConfig::default(),
+            throttle: ThrottleConfig::default(),
+            ratelimit: RateLimitConfig::default(),
+            mfa: MfaConfig::default(),
+            webauthn: WebauthnConfig::default(),
+            mail: MailConfig::default(),
+            passwordless: PasswordlessConfig::default(),
+            sms: SmsConfig::default(),
+            session: SessionConfig::default(),
+            revocation: RevocationConfig::default(),
+            events: LoginEventConfig::default(),
+            risk: RiskConfig::default(),
+            oauth: OAuthConfig::default(),
+            dpop: DpopConfig::default(),
+            proxy: ProxyConfig::default(),
         }
     }
 }
@@ -100,16 +144,507 @@ impl Default for DatabaseConfig {
 #[derive(Deserialize, Debug, Clone)]
 pub struct PwdConfig {
     pub secret: String,
+    /// Maximum number of password hashes computed at the same time.
+    #[serde(default = "default_pwd_concurrency")]
+    pub concurrency: usize,
+    /// How long, in milliseconds, a request waits for a free hashing slot.
+    #[serde(default = "default_pwd_timeout")]
+    pub timeout: u64,
+}
+
+fn default_pwd_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(2)
+}
+
+fn default_pwd_timeout() -> u64 {
+    5_000
 }
 
 impl Default for PwdConfig {
     fn default() -> Self {
         Self {
             secret: "SuperDuperSecret".to_string(),
+            concurrency: default_pwd_concurrency(),
+            timeout: default_pwd_timeout(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TokenConfig {
+    pub secret: String,
+    #[serde(default = "default_token_issuer")]
+    pub issuer: String,
+    /// Access token lifetime in seconds.
+    #[serde(default = "default_token_ttl")]
+    pub ttl: i64,
+}
+
+fn default_token_issuer() -> String {
+    env!("CARGO_PKG_NAME").to_string()
+}
+
+fn default_token_ttl() -> i64 {
+    900
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            secret: "SuperDuperTokenSecret".to_string(),
+            issuer: default_token_issuer(),
+            ttl: default_token_ttl(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LockoutConfig {
+    /// Failed logins in a row before the account is locked.
+    pub threshold: i32,
+    /// Length of the first lockout in seconds, doubled on every further lockout.
+    pub base: i64,
+    /// Upper bound for a single lockout in seconds.
+    pub max: i64,
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 5,
+            base: 30,
+            max: 3600,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ThrottleConfig {
+    /// Length of the throttling window in seconds.
+    pub window: u64,
+    /// Credential checks allowed per client IP within a window.
+    pub ip: u32,
+    /// Credential checks allowed per username within a window.
+    pub username: u32,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            window: 60,
+            ip: 20,
+            username: 10,
         }
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Burst size for routes without their own limit.
+    pub capacity: u32,
+    /// Seconds an empty bucket takes to refill to `capacity`.
+    pub period: u64,
+    /// Per-route limits as `METHOD /api/v1/pattern=capacity/period`, separated
+    /// by `;`. `*` matches any method.
+    pub routes: String,
+    /// SHA-256 hex digests of API keys that get their own bucket, separated by `,`.
+    pub keys: String,
+    /// `memory`, or `redis` when built with the `redis` feature.
+    pub backend: String,
+    pub url: String,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: 100,
+            period: 60,
+            routes: "POST /api/v1/auth/login=10/60".to_string(),
+            keys: String::new(),
+            backend: "memory".to_string(),
+            url: "redis://localhost:6379".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MfaConfig {
+    /// Secret the TOTP encryption key is derived from.
+    pub key: String,
+    /// Issuer shown by authenticator apps.
+    #[serde(default = "default_mfa_issuer")]
+    pub issuer: String,
+    /// Lifetime of the MFA challenge token in seconds.
+    #[serde(default = "default_mfa_ttl")]
+    pub ttl: i64,
+    /// Time steps either side of now in which a code is still accepted.
+    #[serde(default = "default_mfa_skew")]
+    pub skew: u64,
+    /// Number of recovery codes issued when TOTP is confirmed.
+    #[serde(default = "default_mfa_codes")]
+    pub codes: usize,
+}
+
+fn default_mfa_issuer() -> String {
+    env!("CARGO_PKG_NAME").to_string()
+}
+
+fn default_mfa_ttl() -> i64 {
+    300
+}
+
+fn default_mfa_skew() -> u64 {
+    1
+}
+
+fn default_mfa_codes() -> usize {
+    10
+}
+
+impl Default for MfaConfig {
+    fn default() -> Self {
+        Self {
+            key: "SuperDuperMfaKey".to_string(),
+            issuer: default_mfa_issuer(),
+            ttl: default_mfa_ttl(),
+            skew: default_mfa_skew(),
+            codes: default_mfa_codes(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WebauthnConfig {
+    /// Relying party ID, the registrable domain passkeys are scoped to.
+    pub rpid: String,
+    /// Exact origin the browser reports, e.g. `https://auth.example.com`.
+    pub origin: String,
+    /// Relying party name shown by the authenticator.
+    pub name: String,
+    /// Seconds a registration or authentication challenge stays valid.
+    pub ttl: i64,
+}
+
+impl Default for WebauthnConfig {
+    fn default() -> Self {
+        Self {
+            rpid: "localhost".to_string(),
+            origin: "http://localhost:8080".to_string(),
+            name: env!("CARGO_PKG_NAME").to_string(),
+            ttl: 300,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MailConfig {
+    /// `log` writes messages to the log instead of sending them, `smtp`
+    /// delivers them through `host` with STARTTLS.
+    pub backend: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            backend: "log".to_string(),
+            host: "localhost".to_string(),
+            port: 587,
+            username: String::new(),
+            password: String::new(),
+            from: "no-reply@localhost".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PasswordlessConfig {
+    /// Secret used to sign magic links and hash one-time codes.
+    pub key: String,
+    /// Seconds a code or link stays valid.
+    #[serde(default = "default_passwordless_ttl")]
+    pub ttl: i64,
+    /// Wrong codes allowed before a code is burned.
+    #[serde(default = "default_passwordless_attempts")]
+    pub attempts: i32,
+    /// Page the magic link points to; the token is appended as `?token=`.
+    #[serde(default = "default_passwordless_url")]
+    pub url: String,
+    /// Whether the browser binding cookie is marked `Secure`.
+    #[serde(default = "default_passwordless_secure")]
+    pub secure: bool,
+}
+
+fn default_passwordless_ttl() -> i64 {
+    600
+}
+
+fn default_passwordless_attempts() -> i32 {
+    5
+}
+
+fn default_passwordless_url() -> String {
+    "http://localhost:8080/api/v1/auth/passwordless/link".to_string()
+}
+
+fn default_passwordless_secure() -> bool {
+    true
+}
+
+impl Default for PasswordlessConfig {
+    fn default() -> Self {
+        Self {
+            key: "SuperDuperPasswordlessKey".to_string(),
+            ttl: default_passwordless_ttl(),
+            attempts: default_passwordless_attempts(),
+            url: default_passwordless_url(),
+            secure: default_passwordless_secure(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SmsConfig {
+    /// `log` writes messages to the log, or appends them to `file` when it
+    /// is set. `webhook` posts them as JSON to `url`.
+    #[serde(default = "default_sms_backend")]
+    pub backend: String,
+    #[serde(default)]
+    pub file: String,
+    #[serde(default)]
+    pub url: String,
+    /// Sent as a bearer token to the webhook when set.
+    #[serde(default)]
+    pub token: String,
+    /// Seconds to wait for the webhook to answer.
+    #[serde(default = "default_sms_timeout")]
+    pub timeout: u64,
+    /// Secret used to hash the codes before they are stored.
+    pub key: String,
+    /// Seconds a code stays valid.
+    #[serde(default = "default_sms_ttl")]
+    pub ttl: i64,
+    /// Wrong codes allowed before a code is burned.
+    #[serde(default = "default_sms_attempts")]
+    pub attempts: i32,
+    /// Messages a user may trigger per `window` seconds.
+    #[serde(default = "default_sms_sends")]
+    pub sends: u32,
+    #[serde(default = "default_sms_window")]
+    pub window: u64,
+}
+
+fn default_sms_backend() -> String {
+    "log".to_string()
+}
+
+fn default_sms_timeout() -> u64 {
+    5
+}
+
+fn default_sms_ttl() -> i64 {
+    300
+}
+
+fn default_sms_attempts() -> i32 {
+    5
+}
+
+fn default_sms_sends() -> u32 {
+    5
+}
+
+fn default_sms_window() -> u64 {
+    3600
+}
+
+impl Default for SmsConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_sms_backend(),
+            file: String::new(),
+            url: String::new(),
+            token: String::new(),
+            timeout: default_sms_timeout(),
+            key: "SuperDuperSmsKey".to_string(),
+            ttl: default_sms_ttl(),
+            attempts: default_sms_attempts(),
+            sends: default_sms_sends(),
+            window: default_sms_window(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Name of the session cookie.
+    pub name: String,
+    /// Seconds without a request after which a session ends.
+    pub idle: i64,
+    /// Seconds after sign-in after which a session ends regardless of use.
+    pub ttl: i64,
+    /// Whether the cookie is marked `Secure`.
+    pub secure: bool,
+    /// `strict`, `lax` or `none`. `none` requires `secure`.
+    pub samesite: String,
+    /// Cookie domain. Empty keeps the cookie on the issuing host.
+    pub domain: String,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            name: "session".to_string(),
+            idle: 1800,
+            ttl: 43200,
+            secure: true,
+            samesite: "lax".to_string(),
+            domain: String::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RevocationConfig {
+    /// Seconds a "not revoked" answer is reused before the database is asked
+    /// again. Bounds how long a revocation on another instance goes unseen.
+    pub cache: u64,
+}
+
+impl Default for RevocationConfig {
+    fn default() -> Self {
+        Self { cache: 30 }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LoginEventConfig {
+    /// Days login events are kept before they are pruned.
+    pub retention: i64,
+}
+
+impl Default for LoginEventConfig {
+    fn default() -> Self {
+        Self { retention: 90 }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RiskConfig {
+    /// Path to a MaxMind-format country database. Empty disables country
+    /// lookups.
+    pub geoip: String,
+    /// Score from which the user is told about a sign-in.
+    pub notify: u8,
+    /// Score from which an account without MFA has to confirm the sign-in
+    /// with an emailed code.
+    pub challenge: u8,
+    /// Where notifications go: `email` or `log`.
+    pub notifier: String,
+    /// Whether the device cookie may only travel over HTTPS.
+    pub secure: bool,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            geoip: String::new(),
+            notify: 40,
+            challenge: 70,
+            notifier: "email".to_string(),
+            secure: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct OAuthConfig {
+    /// Seconds an authorization code can be exchanged for a token.
+    pub code: i64,
+    /// Seconds a client secret keeps working after it was rotated out.
+    pub overlap: i64,
+    /// Public URL of the OAuth endpoints. Client assertions are addressed to
+    /// it or to its `/token` endpoint.
+    pub url: String,
+    /// Refresh token lifetime in seconds, for clients without their own.
+    pub refresh: i64,
+    /// Seconds a device authorization request waits for the user.
+    pub device: i64,
+    /// Seconds a device has to wait between polls of the token endpoint.
+    pub interval: i32,
+    /// Seconds the user has to answer the consent screen.
+    pub consent: i64,
+    /// Seconds a pushed authorization request can be used for.
+    pub pushed: i64,
+    /// Path to the PEM RSA private key ID tokens are signed with. Empty
+    /// generates a key at startup, which clients stop trusting on restart.
+    pub key: String,
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            code: 60,
+            overlap: 86400,
+            refresh: 2_592_000,
+            url: "http://localhost:8080/api/v1/oauth".to_string(),
+            device: 600,
+            interval: 5,
+            consent: 600,
+            pushed: 60,
+            key: String::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DpopConfig {
+    /// Seconds a DPoP proof's `iat` may differ from the server clock.
+    pub window: i64,
+    /// Requires proofs to carry a nonce handed out by the server.
+    pub nonce: bool,
+    /// Seconds a server nonce is accepted for.
+    pub expiry: i64,
+}
+
+impl Default for DpopConfig {
+    fn default() -> Self {
+        Self {
+            window: 60,
+            nonce: false,
+            expiry: 300,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Addresses or CIDR ranges of the reverse proxies in front of the
+    /// service, separated by `,`. Only their `X-Forwarded-For` is believed;
+    /// empty uses the address of the connecting peer.
+    pub trusted: String,
+}
+
 pub fn get_config_from_env() -> AppConfig {
     AppConfig::from_env()
 }
@@ -0,0 +1,129 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Authentication method references (RFC 8176) recorded in tokens.
+pub const AMR_PASSWORD: &str = "pwd";
+pub const AMR_OTP: &str = "otp";
+pub const AMR_HARDWARE_KEY: &str = "hwk";
+pub const AMR_SMS: &str = "sms";
+pub const AMR_EMAIL: &str = "email";
+pub const AMR_MULTI_FACTOR: &str = "mfa";
+
+/// Authentication context class levels. Level 2 means at least two factors,
+/// or a single factor that verifies the user on its own, such as a passkey.
+pub const ACR_SINGLE_FACTOR: u8 = 1;
+pub const ACR_MULTI_FACTOR: u8 = 2;
+
+/// How and when the user behind a token authenticated. Tokens issued before
+/// these claims existed decode to the default, which meets no requirement.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthContext {
+    pub auth_time: i64,
+    pub amr: Vec<String>,
+    pub acr: String,
+}
+
+impl AuthContext {
+    /// Records an authentication that happened now with the given methods.
+    /// Two distinct methods add `mfa` and raise the level to 2.
+    pub fn new(methods: &[&str]) -> Self {
+        let mut amr: Vec<String> = Vec::new();
+        for method in methods {
+            if !amr.iter().any(|existing| existing == method) {
+                amr.push(method.to_string());
+            }
+        }
+
+        let factors = amr
+            .iter()
+            .filter(|method| *method != AMR_MULTI_FACTOR)
+            .count();
+        if factors >= 2 && !amr.iter().any(|method| method == AMR_MULTI_FACTOR) {
+            amr.push(AMR_MULTI_FACTOR.to_string());
+        }
+
+        let level = if amr.iter().any(|method| method == AMR_MULTI_FACTOR) {
+            ACR_MULTI_FACTOR
+        } else {
+            ACR_SINGLE_FACTOR
+        };
+
+        Self {
+            auth_time: Utc::now().timestamp(),
+            amr,
+            acr: level.to_string(),
+        }
+    }
+
+    /// The `acr` as a number. Unknown values count as level 0.
+    pub fn level(&self) -> u8 {
+        self.acr.parse().unwrap_or(0)
+    }
+}
+
+/// What a route demands of the caller's authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepUpRequirement {
+    pub acr: u8,
+    /// Seconds since `auth_time` after which the caller has to sign in again.
+    pub max_age: Option<i64>,
+}
+
+impl StepUpRequirement {
+    /// Any sign-in within the last `max_age` seconds.
+    pub const fn recent(max_age: i64) -> Self {
+        Self {
+            acr: ACR_SINGLE_FACTOR,
+            max_age: Some(max_age),
+        }
+    }
+
+    /// A multi-factor sign-in, optionally no older than `max_age` seconds.
+    pub const fn multi_factor(max_age: Option<i64>) -> Self {
+        Self {
+            acr: ACR_MULTI_FACTOR,
+            max_age,
+        }
+    }
+
+    pub fn is_met_by(&self, context: &AuthContext) -> bool {
+        let recent_enough = self
+            .max_age
+            .is_none_or(|max_age| Utc::now().timestamp() - context.auth_time <= max_age);
+        context.level() >= self.acr && recent_enough
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_methods_are_multi_factor() {
+        let context = AuthContext::new(&[AMR_PASSWORD]);
+        assert_eq!(vec![AMR_PASSWORD.to_string()], context.amr);
+        assert_eq!(ACR_SINGLE_FACTOR, context.level());
+
+        let context = AuthContext::new(&[AMR_PASSWORD, AMR_OTP, AMR_OTP]);
+        assert_eq!(vec!["pwd", "otp", "mfa"], context.amr);
+        assert_eq!(ACR_MULTI_FACTOR, context.level());
+
+        let context = AuthContext::new(&[AMR_HARDWARE_KEY, AMR_MULTI_FACTOR]);
+        assert_eq!(vec!["hwk", "mfa"], context.amr);
+        assert_eq!(ACR_MULTI_FACTOR, context.level());
+    }
+
+    #[test]
+    fn test_requirement_checks_level_and_age() {
+        let mut context = AuthContext::new(&[AMR_PASSWORD]);
+        assert!(StepUpRequirement::recent(300).is_met_by(&context));
+        assert!(!StepUpRequirement::multi_factor(None).is_met_by(&context));
+
+        context.auth_time -= 301;
+        assert!(!StepUpRequirement::recent(300).is_met_by(&context));
+
+        let context = AuthContext::new(&[AMR_PASSWORD, AMR_SMS]);
+        assert!(StepUpRequirement::multi_factor(Some(300)).is_met_by(&context));
+    }
+}
@@ -0,0 +1,23 @@
+use data_encoding::HEXLOWER;
+use sha2::{Digest, Sha256};
+
+/// Details about the caller that security features key on.
+#[derive(Debug, Clone, Default)]
+pub struct ClientContext {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    /// Value of the long-lived device cookie.
+    pub device_id: Option<String>,
+    /// ISO country code of `ip_address`, when a GeoIP database is configured.
+    pub country: Option<String>,
+}
+
+impl ClientContext {
+    /// The device cookie as it is stored, so the history never holds a
+    /// value that could be replayed as the cookie.
+    pub fn device_hash(&self) -> Option<String> {
+        self.device_id
+            .as_ref()
+            .map(|device_id| HEXLOWER.encode(&Sha256::digest(device_id.as_bytes())))
+    }
+}
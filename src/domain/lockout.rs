@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct AccountLockout {
+    pub user_id: Uuid,
+    pub failed_attempts: i32,
+    pub lockouts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_failed_at: Option<DateTime<Utc>>,
+}
+
+impl AccountLockout {
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+}
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct LockoutEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub event: String,
+    pub ip_address: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::domain::client::ClientContext;
+
+/// Why a sign-in attempt was refused.
+pub const UNKNOWN_USER: &str = "unknown_user";
+pub const INVALID_CREDENTIALS: &str = "invalid_credentials";
+pub const INVALID_MFA: &str = "invalid_mfa";
+pub const INVALID_CODE: &str = "invalid_code";
+pub const ACCOUNT_LOCKED: &str = "account_locked";
+pub const PASSWORD_RESET_REQUIRED: &str = "password_reset_required";
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub success: bool,
+    pub method: String,
+    pub mfa_method: Option<String>,
+    pub failure_reason: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What is known about a sign-in attempt so far. Filled in as the attempt
+/// progresses, so a failure can still be attributed to the right account.
+#[derive(Debug, Clone)]
+pub struct LoginAttempt {
+    pub user_id: Option<Uuid>,
+    pub method: String,
+    pub mfa_method: Option<String>,
+}
+
+impl LoginAttempt {
+    pub fn new(method: &str) -> Self {
+        Self {
+            user_id: None,
+            method: method.to_string(),
+            mfa_method: None,
+        }
+    }
+}
+
+pub struct NewLoginEvent {
+    pub user_id: Option<Uuid>,
+    pub success: bool,
+    pub method: String,
+    pub mfa_method: Option<String>,
+    pub failure_reason: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_hash: Option<String>,
+    pub country: Option<String>,
+}
+
+impl NewLoginEvent {
+    /// `failure_reason` is `None` for a successful attempt.
+    pub fn new(
+        attempt: LoginAttempt,
+        failure_reason: Option<&str>,
+        client: &ClientContext,
+    ) -> Self {
+        Self {
+            user_id: attempt.user_id,
+            success: failure_reason.is_none(),
+            method: attempt.method,
+            mfa_method: attempt.mfa_method,
+            failure_reason: failure_reason.map(str::to_string),
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            device_hash: client.device_hash(),
+            country: client.country.clone(),
+        }
+    }
+}
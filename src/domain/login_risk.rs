@@ -0,0 +1,103 @@
+/// Points added to the risk score for each signal.
+pub const NEW_DEVICE_SCORE: u8 = 40;
+pub const NEW_COUNTRY_SCORE: u8 = 50;
+pub const RECENT_FAILURE_SCORE: u8 = 10;
+/// Failed attempts beyond this many add nothing more.
+pub const MAX_COUNTED_FAILURES: i64 = 3;
+
+/// What the user's earlier sign-ins say about the current device and
+/// country. Only successful sign-ins count as known.
+#[derive(Debug, Clone, Default)]
+pub struct LoginHistory {
+    /// Successful sign-ins that recorded a device.
+    pub devices: i64,
+    pub known_device: bool,
+    /// Successful sign-ins that recorded a country.
+    pub countries: i64,
+    pub known_country: bool,
+    /// Failed attempts during the last day.
+    pub recent_failures: i64,
+}
+
+/// How unusual a sign-in looks, scored from 0 to 100.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoginRisk {
+    pub score: u8,
+    pub new_device: bool,
+    pub new_country: bool,
+    pub recent_failures: i64,
+}
+
+impl LoginRisk {
+    /// A device or country only counts as new when the history has
+    /// something to compare it with, so the first sign-in after an account
+    /// is created, or after fingerprinting was introduced, scores nothing
+    /// for it.
+    pub fn assess(
+        history: &LoginHistory,
+        device_hash: Option<&str>,
+        country: Option<&str>,
+    ) -> Self {
+        let new_device = device_hash.is_some() && history.devices > 0 && !history.known_device;
+        let new_country = country.is_some() && history.countries > 0 && !history.known_country;
+        let failures = history.recent_failures.clamp(0, MAX_COUNTED_FAILURES);
+
+        let mut score = failures as u32 * RECENT_FAILURE_SCORE as u32;
+        if new_device {
+            score += NEW_DEVICE_SCORE as u32;
+        }
+        if new_country {
+            score += NEW_COUNTRY_SCORE as u32;
+        }
+
+        Self {
+            score: score.min(100) as u8,
+            new_device,
+            new_country,
+            recent_failures: history.recent_failures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_device_and_country_add_up() {
+        let history = LoginHistory {
+            devices: 3,
+            known_device: false,
+            countries: 3,
+            known_country: true,
+            recent_failures: 0,
+        };
+        let risk = LoginRisk::assess(&history, Some("abc"), Some("DE"));
+        assert!(risk.new_device);
+        assert!(!risk.new_country);
+        assert_eq!(NEW_DEVICE_SCORE, risk.score);
+
+        let history = LoginHistory {
+            known_country: false,
+            recent_failures: 10,
+            ..history
+        };
+        let risk = LoginRisk::assess(&history, Some("abc"), Some("DE"));
+        assert!(risk.new_country);
+        assert_eq!(100, risk.score);
+    }
+
+    #[test]
+    fn test_nothing_to_compare_is_not_new() {
+        let risk = LoginRisk::assess(&LoginHistory::default(), Some("abc"), Some("DE"));
+        assert_eq!(LoginRisk::default(), risk);
+
+        let history = LoginHistory {
+            devices: 1,
+            countries: 1,
+            ..LoginHistory::default()
+        };
+        let risk = LoginRisk::assess(&history, None, None);
+        assert_eq!(0, risk.score);
+    }
+}
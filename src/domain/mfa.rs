@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::domain::{
+    auth_context::{AMR_EMAIL, AMR_HARDWARE_KEY, AMR_OTP, AMR_SMS},
+    webauthn::WebauthnAssertion,
+};
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct UserTotp {
+    pub user_id: Uuid,
+    pub secret_ciphertext: Vec<u8>,
+    pub secret_nonce: Vec<u8>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserTotp {
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+/// A second factor presented after the password.
+pub enum MfaCode {
+    Totp(String),
+    Recovery(String),
+    WebAuthn(WebauthnAssertion),
+    Sms(String),
+    /// Only accepted for challenges raised by the sign-in risk checks.
+    Email(String),
+}
+
+impl MfaCode {
+    /// The authentication method reference recorded for this factor.
+    pub fn amr(&self) -> &'static str {
+        match self {
+            MfaCode::Totp(_) | MfaCode::Recovery(_) => AMR_OTP,
+            MfaCode::WebAuthn(_) => AMR_HARDWARE_KEY,
+            MfaCode::Sms(_) => AMR_SMS,
+            MfaCode::Email(_) => AMR_EMAIL,
+        }
+    }
+}
@@ -1 +1,13 @@
+pub mod auth_context;
+pub mod client;
+pub mod lockout;
+pub mod login_event;
+pub mod login_risk;
+pub mod mfa;
+pub mod oauth;
+pub mod one_time_code;
+pub mod principal;
+pub mod rate_limit;
+pub mod session;
 pub mod user;
+pub mod webauthn;
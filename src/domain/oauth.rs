@@ -0,0 +1,447 @@
+use chrono::{DateTime, Utc};
+use data_encoding::HEXLOWER;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{prelude::FromRow, types::Json};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::domain::auth_context::AuthContext;
+
+/// The only PKCE method accepted. `plain` would let anyone who sees the
+/// authorization request redeem the code.
+pub const PKCE_METHOD_S256: &str = "S256";
+
+pub const RESPONSE_TYPE_CODE: &str = "code";
+pub const GRANT_AUTHORIZATION_CODE: &str = "authorization_code";
+pub const GRANT_CLIENT_CREDENTIALS: &str = "client_credentials";
+pub const GRANT_DEVICE_CODE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+pub const GRANT_REFRESH_TOKEN: &str = "refresh_token";
+pub const GRANT_TOKEN_EXCHANGE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+/// Grant types a client can be registered for.
+pub const SUPPORTED_GRANT_TYPES: &[&str] = &[
+    GRANT_AUTHORIZATION_CODE,
+    GRANT_CLIENT_CREDENTIALS,
+    GRANT_DEVICE_CODE,
+    GRANT_REFRESH_TOKEN,
+    GRANT_TOKEN_EXCHANGE,
+];
+
+/// `token_type_hint` values of the introspection and revocation endpoints.
+pub const TOKEN_TYPE_ACCESS: &str = "access_token";
+pub const TOKEN_TYPE_REFRESH: &str = "refresh_token";
+
+/// Token type identifier of an access token in token exchange (RFC 8693).
+/// The only type exchanged here, in either direction.
+pub const TOKEN_TYPE_ACCESS_URN: &str = "urn:ietf:params:oauth:token-type:access_token";
+
+/// Scopes with a meaning of their own. `openid` asks for an ID token, the
+/// others release user claims from the userinfo endpoint.
+pub const SCOPE_OPENID: &str = "openid";
+pub const SCOPE_PROFILE: &str = "profile";
+pub const SCOPE_EMAIL: &str = "email";
+pub const SCOPE_BIRTHDATE: &str = "birthdate";
+
+/// `prompt` value that shows the consent screen even when the user agreed
+/// to the scopes before.
+pub const PROMPT_CONSENT: &str = "consent";
+
+/// Where a device authorization request stands.
+pub const DEVICE_PENDING: &str = "pending";
+pub const DEVICE_APPROVED: &str = "approved";
+pub const DEVICE_DENIED: &str = "denied";
+
+/// `client_assertion_type` of a private_key_jwt client assertion (RFC 7523).
+pub const CLIENT_ASSERTION_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+/// Prefix of the `request_uri` handed out for a pushed authorization
+/// request (RFC 9126).
+pub const REQUEST_URI_PREFIX: &str = "urn:ietf:params:oauth:request_uri:";
+
+/// Clients that cannot keep a secret, such as single-page and mobile apps.
+pub const CLIENT_PUBLIC: &str = "public";
+/// Clients running on a server, which authenticate with a secret.
+pub const CLIENT_CONFIDENTIAL: &str = "confidential";
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub name: String,
+    pub client_type: String,
+    /// Run by the service itself, so users are not asked for consent.
+    pub first_party: bool,
+    /// Authorization requests have to be pushed to the PAR endpoint first.
+    pub require_pushed_authorization_requests: bool,
+    /// Authorization requests have to come as a signed request object.
+    pub require_signed_request_object: bool,
+    #[serde(skip_serializing)]
+    pub secret_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub previous_secret_hash: Option<String>,
+    pub previous_secret_expires_at: Option<DateTime<Utc>>,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    /// Audiences the client may address tokens to through token exchange.
+    pub audiences: Vec<String>,
+    pub access_ttl: Option<i32>,
+    pub refresh_ttl: Option<i32>,
+    /// Public keys for private_key_jwt authentication and signed request
+    /// objects.
+    pub jwks: Option<Json<JwkSet>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    /// Redirect URIs are compared exactly, without normalisation, so a
+    /// registered URI cannot be stretched to one the client does not own.
+    pub fn allows_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+    }
+
+    pub fn allows_grant_type(&self, grant_type: &str) -> bool {
+        self.grant_types.iter().any(|allowed| allowed == grant_type)
+    }
+
+    /// Whether every scope in a space-separated list was registered.
+    pub fn allows_scope(&self, scope: &str) -> bool {
+        scope
+            .split_whitespace()
+            .all(|requested| self.scopes.iter().any(|allowed| allowed == requested))
+    }
+
+    pub fn allows_audience(&self, audience: &str) -> bool {
+        self.audiences.iter().any(|allowed| allowed == audience)
+    }
+
+    pub fn is_confidential(&self) -> bool {
+        self.client_type == CLIENT_CONFIDENTIAL
+    }
+
+    /// Checks a presented secret. After a rotation the previous secret keeps
+    /// working until its overlap period ends, so deployments can switch over.
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        let hash = hash_client_secret(secret);
+        let matches = |stored: &Option<String>| {
+            stored
+                .as_deref()
+                .is_some_and(|stored| bool::from(stored.as_bytes().ct_eq(hash.as_bytes())))
+        };
+        let previous_valid = self
+            .previous_secret_expires_at
+            .is_some_and(|expires_at| expires_at > Utc::now());
+
+        matches(&self.secret_hash) || (previous_valid && matches(&self.previous_secret_hash))
+    }
+
+    /// The registered key an assertion names by `kid`, or the only key when
+    /// it names none.
+    pub fn assertion_key(&self, kid: Option<&str>) -> Option<&Jwk> {
+        let keys = &self.jwks.as_ref()?.keys;
+        match kid {
+            Some(kid) => keys
+                .iter()
+                .find(|key| key.common.key_id.as_deref() == Some(kid)),
+            None if keys.len() == 1 => keys.first(),
+            None => None,
+        }
+    }
+
+    /// Lifetime of access tokens issued to this client.
+    pub fn access_ttl_or(&self, default: i64) -> i64 {
+        self.access_ttl.map(i64::from).unwrap_or(default)
+    }
+
+    /// Lifetime of refresh tokens issued to this client.
+    pub fn refresh_ttl_or(&self, default: i64) -> i64 {
+        self.refresh_ttl.map(i64::from).unwrap_or(default)
+    }
+}
+
+/// Whether a space-separated scope list contains the given scope.
+pub fn has_scope(scope: &str, wanted: &str) -> bool {
+    scope.split_whitespace().any(|granted| granted == wanted)
+}
+
+/// Client secrets are long random strings, so a plain SHA-256 is enough to
+/// keep a leaked table from revealing them.
+pub fn hash_client_secret(secret: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(secret.as_bytes()))
+}
+
+/// Everything needed to register a client.
+pub struct NewOAuthClient {
+    pub name: String,
+    pub client_type: String,
+    pub first_party: bool,
+    pub require_pushed_authorization_requests: bool,
+    pub require_signed_request_object: bool,
+    pub secret_hash: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub audiences: Vec<String>,
+    pub access_ttl: Option<i32>,
+    pub refresh_ttl: Option<i32>,
+    pub jwks: Option<JwkSet>,
+}
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct AuthorizationCode {
+    pub id: Uuid,
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub code_challenge: String,
+    pub nonce: Option<String>,
+    pub auth_time: DateTime<Utc>,
+    pub amr: Vec<String>,
+    pub acr: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    /// Refresh token family started when the code was exchanged.
+    pub family_id: Option<Uuid>,
+    /// The access token issued when the code was exchanged.
+    pub access_jti: Option<Uuid>,
+    pub access_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuthorizationCode {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    pub fn auth_context(&self) -> AuthContext {
+        AuthContext {
+            auth_time: self.auth_time.timestamp(),
+            amr: self.amr.clone(),
+            acr: self.acr.clone(),
+        }
+    }
+}
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct DeviceCode {
+    pub id: Uuid,
+    pub device_code_hash: String,
+    pub user_code_hash: String,
+    pub client_id: Uuid,
+    pub scope: String,
+    pub status: String,
+    pub user_id: Option<Uuid>,
+    pub auth_time: Option<DateTime<Utc>>,
+    pub amr: Vec<String>,
+    pub acr: Option<String>,
+    pub poll_interval: i32,
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DeviceCode {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == DEVICE_PENDING && self.consumed_at.is_none() && !self.is_expired()
+    }
+
+    /// Whether the device came back before its polling interval was up.
+    pub fn polled_too_soon(&self) -> bool {
+        self.last_polled_at.is_some_and(|last_polled_at| {
+            Utc::now() < last_polled_at + chrono::Duration::seconds(self.poll_interval.into())
+        })
+    }
+
+    pub fn auth_context(&self) -> AuthContext {
+        AuthContext {
+            auth_time: self
+                .auth_time
+                .map(|auth_time| auth_time.timestamp())
+                .unwrap_or(0),
+            amr: self.amr.clone(),
+            acr: self.acr.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Everything needed to store a new device authorization request.
+pub struct NewDeviceCode {
+    pub device_code_hash: String,
+    pub user_code_hash: String,
+    pub client_id: Uuid,
+    pub scope: String,
+    pub poll_interval: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub token_hash: String,
+    pub family_id: Uuid,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub scope: String,
+    pub auth_time: DateTime<Utc>,
+    pub amr: Vec<String>,
+    pub acr: String,
+    /// DPoP key a public client's token is bound to.
+    pub jkt: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RefreshToken {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+
+    pub fn auth_context(&self) -> AuthContext {
+        AuthContext {
+            auth_time: self.auth_time.timestamp(),
+            amr: self.amr.clone(),
+            acr: self.acr.clone(),
+        }
+    }
+}
+
+/// Everything needed to store a new refresh token.
+pub struct NewRefreshToken {
+    pub token_hash: String,
+    pub family_id: Uuid,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub scope: String,
+    pub auth: AuthContext,
+    pub jkt: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Scopes a user has agreed to share with a client.
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct OAuthGrant {
+    pub user_id: Uuid,
+    pub client_id: Uuid,
+    pub client_name: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OAuthGrant {
+    /// Whether every scope in a space-separated list was agreed to.
+    pub fn covers(&self, scope: &str) -> bool {
+        scope
+            .split_whitespace()
+            .all(|requested| self.scopes.iter().any(|granted| granted == requested))
+    }
+}
+
+/// Everything needed to store a new authorization code.
+pub struct NewAuthorizationCode {
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub code_challenge: String,
+    pub nonce: Option<String>,
+    pub auth: AuthContext,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An authorization request a client pushed ahead of sending the browser.
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct PushedRequest {
+    pub id: Uuid,
+    pub request_uri_hash: String,
+    pub client_id: Uuid,
+    /// The checked request parameters, stored as an `AuthorizeRequest`.
+    pub parameters: serde_json::Value,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PushedRequest {
+    pub fn is_active(&self) -> bool {
+        self.consumed_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+/// Everything needed to store a pushed authorization request.
+pub struct NewPushedRequest {
+    pub request_uri_hash: String,
+    pub client_id: Uuid,
+    pub parameters: serde_json::Value,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(secret: &str) -> OAuthClient {
+        OAuthClient {
+            id: Uuid::new_v4(),
+            name: "app".to_string(),
+            client_type: CLIENT_CONFIDENTIAL.to_string(),
+            first_party: false,
+            require_pushed_authorization_requests: false,
+            require_signed_request_object: false,
+            secret_hash: Some(hash_client_secret(secret)),
+            previous_secret_hash: None,
+            previous_secret_expires_at: None,
+            redirect_uris: vec!["https://app.example.com/cb".to_string()],
+            grant_types: vec![GRANT_AUTHORIZATION_CODE.to_string()],
+            scopes: vec!["profile".to_string(), "email".to_string()],
+            audiences: Vec::new(),
+            access_ttl: None,
+            refresh_ttl: None,
+            jwks: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_previous_secret_works_during_overlap() {
+        let mut client = client("new");
+        assert!(client.verify_secret("new"));
+        assert!(!client.verify_secret("old"));
+
+        client.previous_secret_hash = Some(hash_client_secret("old"));
+        client.previous_secret_expires_at = Some(Utc::now() + chrono::Duration::seconds(60));
+        assert!(client.verify_secret("old"));
+
+        client.previous_secret_expires_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert!(!client.verify_secret("old"));
+        assert!(client.verify_secret("new"));
+    }
+
+    #[test]
+    fn test_allows_registered_scopes_only() {
+        let client = client("secret");
+        assert!(client.allows_scope(""));
+        assert!(client.allows_scope("profile email"));
+        assert!(!client.allows_scope("profile admin"));
+    }
+
+    #[test]
+    fn test_has_scope_matches_whole_tokens() {
+        assert!(has_scope("openid profile", SCOPE_OPENID));
+        assert!(!has_scope("openidx profile", SCOPE_OPENID));
+        assert!(!has_scope("", SCOPE_OPENID));
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+pub const EMAIL_CODE_PURPOSE: &str = "email_code";
+pub const LOGIN_VERIFY_PURPOSE: &str = "login_verify";
+pub const MAGIC_LINK_PURPOSE: &str = "magic_link";
+pub const PHONE_VERIFY_PURPOSE: &str = "phone_verify";
+pub const SMS_MFA_PURPOSE: &str = "sms_mfa";
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct OneTimeCode {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: String,
+    pub code_hash: String,
+    pub binding_hash: String,
+    pub attempts: i32,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OneTimeCode {
+    pub fn is_usable(&self) -> bool {
+        self.consumed_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+/// How a passwordless sign-in is delivered by email.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordlessMethod {
+    Code,
+    Link,
+}
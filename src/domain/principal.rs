@@ -0,0 +1,41 @@
+use uuid::Uuid;
+
+use crate::domain::{auth_context::AuthContext, oauth::has_scope};
+
+pub const ADMIN_ROLE: &str = "admin";
+
+/// The authenticated caller of a request.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub user_id: Uuid,
+    pub roles: Vec<String>,
+    /// The access token's `jti`, or the session id for cookie sessions.
+    pub token_id: Uuid,
+    pub expires_at: i64,
+    pub auth: AuthContext,
+    /// Whether the caller authenticated with a session cookie.
+    pub session: bool,
+    /// The OAuth client acting for the user, if the token was issued to one.
+    pub client_id: Option<Uuid>,
+    /// Space-separated scopes granted to that client.
+    pub scope: Option<String>,
+}
+
+impl Principal {
+    pub fn is_admin(&self) -> bool {
+        self.roles.iter().any(|role| role == ADMIN_ROLE)
+    }
+
+    /// Whether the caller may act on the given user's resources.
+    pub fn can_access_user(&self, user_id: Uuid) -> bool {
+        self.user_id == user_id || self.is_admin()
+    }
+
+    /// Whether an OAuth client was granted the scope. First-party tokens and
+    /// sessions carry no scopes.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope
+            .as_deref()
+            .is_some_and(|granted| has_scope(granted, scope))
+    }
+}
@@ -0,0 +1,54 @@
+use std::{fmt, str::FromStr};
+
+/// Token bucket parameters: up to `capacity` requests in a burst, refilled
+/// evenly over `period` seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub period: u64,
+}
+
+impl RateLimit {
+    pub fn refill_per_second(&self) -> f64 {
+        self.capacity as f64 / self.period.max(1) as f64
+    }
+}
+
+impl fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{};w={}", self.capacity, self.period)
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (capacity, period) = value
+            .split_once('/')
+            .ok_or_else(|| format!("expected capacity/period, got {:?}", value))?;
+
+        Ok(Self {
+            capacity: capacity
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid capacity in {:?}", value))?,
+            period: period
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid period in {:?}", value))?,
+        })
+    }
+}
+
+/// Outcome of taking a token from a bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: RateLimit,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset: u64,
+    /// Seconds until the next request would be allowed.
+    pub retry_after: u64,
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::domain::auth_context::AuthContext;
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub csrf_token: String,
+    pub roles: Vec<String>,
+    pub auth_time: DateTime<Utc>,
+    pub amr: Vec<String>,
+    pub acr: String,
+    pub user_agent: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Where a session was started from.
+#[derive(Debug, Clone, Default)]
+pub struct SessionDevice {
+    pub user_agent: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// Everything needed to store a new session.
+pub struct NewSession {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub csrf_token: String,
+    pub roles: Vec<String>,
+    pub auth: AuthContext,
+    pub device: SessionDevice,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Session {
+    /// When the session ends: after `idle` seconds without a request, or at
+    /// its absolute expiry, whichever comes first.
+    pub fn valid_until(&self, idle: i64) -> DateTime<Utc> {
+        (self.last_seen_at + chrono::Duration::seconds(idle)).min(self.expires_at)
+    }
+
+    pub fn auth_context(&self) -> AuthContext {
+        AuthContext {
+            auth_time: self.auth_time.timestamp(),
+            amr: self.amr.clone(),
+            acr: self.acr.clone(),
+        }
+    }
+}
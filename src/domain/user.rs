@@ -1,8 +1,10 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
+use crate::domain::principal::ADMIN_ROLE;
+
 #[derive(FromRow, Deserialize, Serialize)]
 pub struct User {
     pub id: Uuid,
@@ -11,6 +13,19 @@ pub struct User {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
     pub date_of_birth: Option<NaiveDate>,
+    /// Kept out of responses: the user endpoints can be read without
+    /// signing in.
+    #[serde(skip_serializing)]
+    pub phone_number: Option<String>,
+    #[serde(skip_serializing)]
+    pub phone_verified_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    /// The phone number, if it has been verified.
+    pub fn verified_phone_number(&self) -> Option<&str> {
+        self.phone_verified_at.and(self.phone_number.as_deref())
+    }
 }
 
 #[derive(FromRow, Deserialize, Serialize)]
@@ -27,4 +42,33 @@ pub struct UserWithPassword {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
     pub date_of_birth: Option<NaiveDate>,
+    pub phone_number: Option<String>,
+    pub phone_verified_at: Option<DateTime<Utc>>,
+    pub password_reset_required: bool,
+    pub is_admin: bool,
+}
+
+impl UserWithPassword {
+    pub fn roles(&self) -> Vec<String> {
+        if self.is_admin {
+            vec![ADMIN_ROLE.to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl From<UserWithPassword> for User {
+    fn from(user: UserWithPassword) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            date_of_birth: user.date_of_birth,
+            phone_number: user.phone_number,
+            phone_verified_at: user.phone_verified_at,
+        }
+    }
 }
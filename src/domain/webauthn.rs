@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+pub const REGISTRATION_CEREMONY: &str = "registration";
+pub const AUTHENTICATION_CEREMONY: &str = "authentication";
+
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct WebauthnCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A challenge handed to the browser, consumed by the matching ceremony.
+#[derive(FromRow, Deserialize, Serialize)]
+pub struct WebauthnChallenge {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub ceremony: String,
+    pub challenge: Vec<u8>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The authenticator's answer to an authentication challenge, decoded from
+/// base64url.
+pub struct WebauthnAssertion {
+    pub challenge_id: Uuid,
+    pub credential_id: Vec<u8>,
+    pub client_data_json: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub user_handle: Option<Vec<u8>>,
+}
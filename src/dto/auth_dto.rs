@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    domain::one_time_code::PasswordlessMethod, dto::webauthn_dto::AssertionRequest,
+    util::token::IssuedToken,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct LoginResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
+
+impl From<IssuedToken> for LoginResponse {
+    fn from(issued: IssuedToken) -> Self {
+        Self {
+            access_token: issued.token,
+            token_type: "Bearer".to_string(),
+            expires_in: issued.expires_in,
+        }
+    }
+}
+
+/// Returned by login instead of [`LoginResponse`] when the account has MFA,
+/// or when an unusual sign-in has to be confirmed. `verify_email` means the
+/// challenge is answered with an `email_code` instead of a second factor.
+#[derive(Deserialize, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: i64,
+    #[serde(default)]
+    pub verify_email: bool,
+}
+
+/// Second login step. Exactly one of `code`, `recovery_code`, `webauthn`,
+/// `sms_code` and `email_code` is expected.
+#[derive(Deserialize, Serialize)]
+pub struct MfaVerifyRequest {
+    pub mfa_token: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+    pub webauthn: Option<AssertionRequest>,
+    pub sms_code: Option<String>,
+    pub email_code: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PasswordlessStartRequest {
+    pub email: String,
+    pub method: PasswordlessMethod,
+}
+
+/// The binding for the flow travels in a cookie, not in this body.
+#[derive(Deserialize, Serialize)]
+pub struct PasswordlessStartResponse {
+    pub flow_id: Uuid,
+    pub expires_in: i64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PasswordlessVerifyRequest {
+    pub flow_id: Uuid,
+    pub code: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PasswordlessLinkQuery {
+    pub token: String,
+}
@@ -4,3 +4,14 @@ use serde::{Deserialize, Serialize};
 pub struct ErrorResponse {
     pub message: String,
 }
+
+/// Returned when a route needs a stronger or more recent sign-in than the
+/// caller's token carries. Mirrors the `WWW-Authenticate` challenge.
+#[derive(Deserialize, Serialize)]
+pub struct StepUpResponse {
+    pub message: String,
+    pub error: String,
+    pub acr_values: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<i64>,
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::login_event::LoginEvent;
+
+fn default_page() -> i64 {
+    1
+}
+
+fn default_per_page() -> i64 {
+    20
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SecurityEventsQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SecurityEventsResponse {
+    pub data: Vec<LoginEvent>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
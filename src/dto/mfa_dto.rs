@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::application::use_cases::mfa::TotpEnrollment;
+
+#[derive(Deserialize, Serialize)]
+pub struct TotpEnrollResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_svg: String,
+}
+
+impl From<TotpEnrollment> for TotpEnrollResponse {
+    fn from(enrollment: TotpEnrollment) -> Self {
+        Self {
+            secret: enrollment.secret,
+            otpauth_uri: enrollment.otpauth_uri,
+            qr_svg: enrollment.qr_svg,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TotpConfirmRequest {
+    pub code: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TotpConfirmResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MfaResetResponse {
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MfaSmsRequest {
+    pub mfa_token: String,
+}
+
+/// `sent_to` is masked down to the last two digits.
+#[derive(Deserialize, Serialize)]
+pub struct MfaSmsResponse {
+    pub sent_to: String,
+    pub expires_in: i64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MfaEmailRequest {
+    pub mfa_token: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MfaEmailResponse {
+    pub expires_in: i64,
+}
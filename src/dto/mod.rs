@@ -1,2 +1,10 @@
+pub mod auth_dto;
+pub mod login_event_dto;
+pub mod mfa_dto;
+pub mod oauth_client_dto;
+pub mod oauth_dto;
+pub mod oauth_grant_dto;
+pub mod session_dto;
 pub mod user_dto;
+pub mod webauthn_dto;
 pub mod error;
@@ -0,0 +1,108 @@
+use jsonwebtoken::jwk::JwkSet;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::oauth::{OAuthClient, CLIENT_PUBLIC, GRANT_AUTHORIZATION_CODE};
+
+fn default_client_type() -> String {
+    CLIENT_PUBLIC.to_string()
+}
+
+fn default_grant_types() -> Vec<String> {
+    vec![GRANT_AUTHORIZATION_CODE.to_string()]
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateClientRequest {
+    pub name: String,
+    /// `public` or `confidential`. Fixed once the client is registered.
+    #[serde(default = "default_client_type")]
+    pub client_type: String,
+    /// Clients run by the service itself skip the consent screen.
+    #[serde(default)]
+    pub first_party: bool,
+    /// Only accept authorization requests pushed to the PAR endpoint.
+    #[serde(default)]
+    pub require_pushed_authorization_requests: bool,
+    /// Only accept authorization requests signed with a registered key.
+    #[serde(default)]
+    pub require_signed_request_object: bool,
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    #[serde(default = "default_grant_types")]
+    pub grant_types: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Audiences the client may ask for in token exchange.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// Token lifetimes in seconds. Left out, the service defaults apply.
+    pub access_ttl: Option<i32>,
+    pub refresh_ttl: Option<i32>,
+    /// Public keys for private_key_jwt client authentication and signed
+    /// request objects.
+    pub jwks: Option<JwkSet>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UpdateClientRequest {
+    pub name: String,
+    #[serde(default)]
+    pub first_party: bool,
+    #[serde(default)]
+    pub require_pushed_authorization_requests: bool,
+    #[serde(default)]
+    pub require_signed_request_object: bool,
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    #[serde(default = "default_grant_types")]
+    pub grant_types: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    pub access_ttl: Option<i32>,
+    pub refresh_ttl: Option<i32>,
+    pub jwks: Option<JwkSet>,
+}
+
+impl From<&CreateClientRequest> for UpdateClientRequest {
+    fn from(request: &CreateClientRequest) -> Self {
+        Self {
+            name: request.name.clone(),
+            first_party: request.first_party,
+            require_pushed_authorization_requests: request.require_pushed_authorization_requests,
+            require_signed_request_object: request.require_signed_request_object,
+            redirect_uris: request.redirect_uris.clone(),
+            grant_types: request.grant_types.clone(),
+            scopes: request.scopes.clone(),
+            audiences: request.audiences.clone(),
+            access_ttl: request.access_ttl,
+            refresh_ttl: request.refresh_ttl,
+            jwks: request.jwks.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ClientListResponse {
+    pub data: Vec<OAuthClient>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ClientResponse {
+    pub data: OAuthClient,
+}
+
+/// Returned on registration and rotation. The secret is only ever shown
+/// here; the service keeps a hash.
+#[derive(Deserialize, Serialize)]
+pub struct ClientSecretResponse {
+    pub data: OAuthClient,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DeleteClientResponse {
+    pub message: String,
+}
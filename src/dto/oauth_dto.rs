@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        oauth::{has_scope, SCOPE_BIRTHDATE, SCOPE_EMAIL, SCOPE_PROFILE},
+        user::User,
+    },
+    util::{
+        dpop::DPOP_SCHEME,
+        token::{Actor, Confirmation, IssuedToken},
+    },
+};
+
+/// Parameters of an authorization request. They arrive in the query string,
+/// in a signed request object or through a pushed request, and travel on
+/// as hidden fields of the sign-in form.
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct AuthorizeRequest {
+    pub response_type: Option<String>,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    /// OpenID Connect: echoed back in the ID token.
+    pub nonce: Option<String>,
+    /// OpenID Connect: `consent` asks the user again even if they agreed
+    /// before.
+    pub prompt: Option<String>,
+    /// RFC 9101: the parameters as a JWT signed with a registered key.
+    pub request: Option<String>,
+    /// RFC 9126: names a request pushed to the PAR endpoint.
+    pub request_uri: Option<String>,
+}
+
+/// The sign-in form posted back to the authorization endpoint.
+#[derive(Deserialize)]
+pub struct AuthorizeForm {
+    #[serde(flatten)]
+    pub request: AuthorizeRequest,
+    pub username: String,
+    pub password: String,
+}
+
+/// The user's answer on the consent screen.
+#[derive(Deserialize)]
+pub struct ConsentForm {
+    #[serde(flatten)]
+    pub request: AuthorizeRequest,
+    /// Issued with the consent screen for the user who signed in.
+    pub consent_ticket: String,
+    /// `approve` or `deny`.
+    pub action: String,
+}
+
+/// Client authentication sent in the body of the token and device
+/// authorization endpoints.
+#[derive(Deserialize, Serialize, Default)]
+pub struct ClientCredentials {
+    pub client_id: Option<String>,
+    /// `client_secret_post` authentication.
+    pub client_secret: Option<String>,
+    /// `private_key_jwt` authentication.
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
+}
+
+/// Form body of the token endpoint. Which fields are needed depends on
+/// `grant_type`.
+#[derive(Deserialize, Serialize, Default)]
+pub struct TokenRequest {
+    pub grant_type: Option<String>,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub device_code: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    /// Token exchange: the token being traded in and, for delegation, the
+    /// token of whoever acts on its subject's behalf.
+    pub subject_token: Option<String>,
+    pub subject_token_type: Option<String>,
+    pub actor_token: Option<String>,
+    pub actor_token_type: Option<String>,
+    pub requested_token_type: Option<String>,
+    /// Token exchange: the resource server the new token is for.
+    pub audience: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientCredentials,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Issued to clients registered for the refresh token grant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Issued when the `openid` scope was granted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    /// Token exchange: what kind of token `access_token` holds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_token_type: Option<String>,
+}
+
+impl From<IssuedToken> for TokenResponse {
+    fn from(issued: IssuedToken) -> Self {
+        Self {
+            access_token: issued.token,
+            token_type: match issued.claims.cnf {
+                Some(_) => DPOP_SCHEME.to_string(),
+                None => "Bearer".to_string(),
+            },
+            expires_in: issued.expires_in,
+            scope: issued.claims.scope,
+            refresh_token: None,
+            id_token: None,
+            issued_token_type: None,
+        }
+    }
+}
+
+/// Form body of the pushed authorization request endpoint: an ordinary
+/// authorization request plus client authentication.
+#[derive(Deserialize, Serialize, Default)]
+pub struct PushedAuthorizationRequest {
+    #[serde(flatten)]
+    pub request: AuthorizeRequest,
+    pub client_secret: Option<String>,
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
+}
+
+impl PushedAuthorizationRequest {
+    /// The client authentication fields. `client_id` belongs to the
+    /// authorization request as well, so it is shared.
+    pub fn client(&self) -> ClientCredentials {
+        ClientCredentials {
+            client_id: self.request.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            client_assertion_type: self.client_assertion_type.clone(),
+            client_assertion: self.client_assertion.clone(),
+        }
+    }
+}
+
+/// RFC 9126, section 2.2.
+#[derive(Deserialize, Serialize)]
+pub struct PushedAuthorizationResponse {
+    pub request_uri: String,
+    pub expires_in: i64,
+}
+
+/// Form body of the device authorization endpoint.
+#[derive(Deserialize, Serialize, Default)]
+pub struct DeviceAuthorizationRequest {
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientCredentials,
+}
+
+/// RFC 8628, section 3.2.
+#[derive(Deserialize, Serialize)]
+pub struct DeviceAuthorizationResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: String,
+    pub expires_in: i64,
+    pub interval: i32,
+}
+
+/// `?user_code=` on the verification page, filled in when the user
+/// followed `verification_uri_complete`.
+#[derive(Deserialize, Serialize)]
+pub struct DeviceQuery {
+    pub user_code: Option<String>,
+}
+
+/// The verification form. `action` is `approve` or `deny`.
+#[derive(Deserialize, Serialize)]
+pub struct DeviceForm {
+    pub user_code: String,
+    pub username: String,
+    pub password: String,
+    pub action: Option<String>,
+}
+
+/// Approval by a user who is already signed in to a first-party app.
+#[derive(Deserialize, Serialize)]
+pub struct DeviceVerifyRequest {
+    pub user_code: String,
+    pub approve: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DeviceVerifyResponse {
+    pub message: String,
+}
+
+/// Form body of the introspection endpoint (RFC 7662).
+#[derive(Deserialize, Serialize, Default)]
+pub struct IntrospectionRequest {
+    pub token: Option<String>,
+    pub token_type_hint: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientCredentials,
+}
+
+/// Form body of the revocation endpoint (RFC 7009).
+#[derive(Deserialize, Serialize, Default)]
+pub struct RevocationRequest {
+    pub token: Option<String>,
+    pub token_type_hint: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientCredentials,
+}
+
+/// What the introspection endpoint knows about a token. Inactive tokens
+/// reveal nothing beyond `active`.
+#[derive(Deserialize, Serialize, Default)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<Uuid>,
+    /// `access_token` or `refresh_token`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<Uuid>,
+    /// The resource server an exchanged token is addressed to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Who acts on the subject's behalf, for delegated tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    /// The DPoP key a bound token has to be presented with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<Confirmation>,
+}
+
+/// Standard claims about the user, released by scope: `profile` for the
+/// names, `email` and `birthdate` for themselves.
+#[derive(Deserialize, Serialize)]
+pub struct UserInfoResponse {
+    pub sub: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// `YYYY-MM-DD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birthdate: Option<String>,
+}
+
+impl UserInfoResponse {
+    pub fn new(user: User, scope: &str) -> Self {
+        let profile = has_scope(scope, SCOPE_PROFILE);
+        let name = match (&user.first_name, &user.last_name) {
+            (Some(first), Some(last)) => Some(format!("{} {}", first, last)),
+            (first, last) => first.clone().or_else(|| last.clone()),
+        };
+
+        Self {
+            sub: user.id,
+            preferred_username: Some(user.username).filter(|_| profile),
+            name: name.filter(|_| profile),
+            given_name: user.first_name.filter(|_| profile),
+            family_name: user.last_name.filter(|_| profile),
+            email: Some(user.email).filter(|_| has_scope(scope, SCOPE_EMAIL)),
+            birthdate: user
+                .date_of_birth
+                .filter(|_| has_scope(scope, SCOPE_BIRTHDATE))
+                .map(|date| date.format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+/// OpenID Provider metadata served at `/.well-known/openid-configuration`.
+#[derive(Deserialize, Serialize)]
+pub struct OpenIdConfiguration {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub device_authorization_endpoint: String,
+    pub introspection_endpoint: String,
+    pub revocation_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub response_types_supported: Vec<String>,
+    pub grant_types_supported: Vec<String>,
+    pub subject_types_supported: Vec<String>,
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    pub scopes_supported: Vec<String>,
+    pub claims_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    pub code_challenge_methods_supported: Vec<String>,
+    pub dpop_signing_alg_values_supported: Vec<String>,
+    pub pushed_authorization_request_endpoint: String,
+    pub require_pushed_authorization_requests: bool,
+    pub request_parameter_supported: bool,
+    pub request_uri_parameter_supported: bool,
+    pub request_object_signing_alg_values_supported: Vec<String>,
+}
+
+/// Error body defined by RFC 6749, section 5.2.
+#[derive(Deserialize, Serialize)]
+pub struct OAuthErrorResponse {
+    pub error: String,
+    pub error_description: String,
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::oauth::OAuthGrant;
+
+#[derive(Deserialize, Serialize)]
+pub struct GrantListResponse {
+    pub data: Vec<OAuthGrant>,
+}
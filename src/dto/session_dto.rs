@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::session::Session;
+
+/// What a successful sign-in hands back to the client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoginMode {
+    /// A bearer access token in the response body.
+    #[default]
+    Token,
+    /// An HttpOnly session cookie, with the CSRF token in the body.
+    Session,
+}
+
+/// `?mode=session` on the sign-in endpoints.
+#[derive(Deserialize, Serialize)]
+pub struct LoginQuery {
+    #[serde(default)]
+    pub mode: LoginMode,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SessionResponse {
+    pub user_id: Uuid,
+    /// Has to be sent back as `X-CSRF-Token` on state-changing requests.
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A session as listed to its owner or an admin.
+#[derive(Deserialize, Serialize)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
+
+impl SessionSummary {
+    pub fn new(session: Session, current: bool) -> Self {
+        Self {
+            id: session.id,
+            browser: session.browser,
+            os: session.os,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            current,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SessionListResponse {
+    pub data: Vec<SessionSummary>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RevokeSessionsResponse {
+    pub revoked: u64,
+}
@@ -14,6 +14,31 @@ pub struct CreateRequest {
     pub date_of_birth: Option<NaiveDate>,
 }
 
+/// A user migrated from another system, carrying that system's password hash
+/// instead of a plaintext password.
+#[derive(Deserialize, Serialize)]
+pub struct ImportRequest {
+    pub username: String,
+    pub email: String,
+    pub password_hash: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+}
+
+impl From<ImportRequest> for CreateRequest {
+    fn from(user: ImportRequest) -> Self {
+        Self {
+            username: user.username,
+            email: user.email,
+            password: user.password_hash,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            date_of_birth: user.date_of_birth,
+        }
+    }
+}
+
 #[derive(FromRow, Deserialize, Serialize)]
 pub struct UpdateRequest {
     pub username: String,
@@ -42,3 +67,29 @@ pub struct FindByIdResponse {
 pub struct DeleteResponse {
     pub message: String,
 }
+
+#[derive(Deserialize, Serialize)]
+pub struct UnlockResponse {
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PhoneNumberRequest {
+    pub phone_number: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PhoneNumberResponse {
+    pub phone_number: String,
+    pub expires_in: i64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PhoneVerifyRequest {
+    pub code: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PhoneResponse {
+    pub message: String,
+}
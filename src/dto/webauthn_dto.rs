@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    domain::webauthn::{WebauthnAssertion, WebauthnCredential},
+    util::webauthn::{decode_base64url, encode_base64url, WebauthnError},
+};
+
+pub const PUBLIC_KEY_TYPE: &str = "public-key";
+
+#[derive(Deserialize, Serialize)]
+pub struct RelyingPartyEntity {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CredentialParameter {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub alg: i64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: String,
+}
+
+impl From<&WebauthnCredential> for CredentialDescriptor {
+    fn from(credential: &WebauthnCredential) -> Self {
+        Self {
+            kind: PUBLIC_KEY_TYPE.to_string(),
+            id: encode_base64url(&credential.credential_id),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub user_verification: String,
+}
+
+/// `PublicKeyCredentialCreationOptions` in its JSON form.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCreationOptions {
+    pub rp: RelyingPartyEntity,
+    pub user: UserEntity,
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<CredentialParameter>,
+    pub timeout: u64,
+    pub attestation: String,
+    pub authenticator_selection: AuthenticatorSelection,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+}
+
+/// `PublicKeyCredentialRequestOptions` in its JSON form.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyRequestOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    pub timeout: u64,
+    pub user_verification: String,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CreationOptionsResponse {
+    pub challenge_id: Uuid,
+    #[serde(rename = "publicKey")]
+    pub public_key: PublicKeyCreationOptions,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RequestOptionsResponse {
+    pub challenge_id: Uuid,
+    #[serde(rename = "publicKey")]
+    pub public_key: PublicKeyRequestOptions,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "attestationObject")]
+    pub attestation_object: String,
+}
+
+/// The `PublicKeyCredential` returned by `navigator.credentials.create()`.
+#[derive(Deserialize, Serialize)]
+pub struct AttestationCredential {
+    pub id: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RegisterCredentialRequest {
+    pub challenge_id: Uuid,
+    pub name: Option<String>,
+    pub credential: AttestationCredential,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "authenticatorData")]
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(rename = "userHandle")]
+    pub user_handle: Option<String>,
+}
+
+/// The `PublicKeyCredential` returned by `navigator.credentials.get()`.
+#[derive(Deserialize, Serialize)]
+pub struct AssertionCredential {
+    pub id: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AssertionRequest {
+    pub challenge_id: Uuid,
+    pub credential: AssertionCredential,
+}
+
+impl TryFrom<AssertionRequest> for WebauthnAssertion {
+    type Error = WebauthnError;
+
+    fn try_from(request: AssertionRequest) -> Result<Self, Self::Error> {
+        let response = request.credential.response;
+        Ok(Self {
+            challenge_id: request.challenge_id,
+            credential_id: decode_base64url(&request.credential.id)?,
+            client_data_json: decode_base64url(&response.client_data_json)?,
+            authenticator_data: decode_base64url(&response.authenticator_data)?,
+            signature: decode_base64url(&response.signature)?,
+            user_handle: response
+                .user_handle
+                .filter(|handle| !handle.is_empty())
+                .map(|handle| decode_base64url(&handle))
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PasskeyOptionsRequest {
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MfaWebauthnOptionsRequest {
+    pub mfa_token: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CredentialResponse {
+    pub id: Uuid,
+    pub credential_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<WebauthnCredential> for CredentialResponse {
+    fn from(credential: WebauthnCredential) -> Self {
+        Self {
+            id: credential.id,
+            credential_id: encode_base64url(&credential.credential_id),
+            name: credential.name,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct FindCredentialsResponse {
+    pub data: Vec<CredentialResponse>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct FindCredentialResponse {
+    pub data: CredentialResponse,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RenameCredentialRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DeleteCredentialResponse {
+    pub message: String,
+}
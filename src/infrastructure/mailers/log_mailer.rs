@@ -0,0 +1,29 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::application::services::mailer::{EmailMessage, Mailer};
+
+/// Writes messages to the log instead of delivering them. Meant for local
+/// development, where the link or code can be copied from the output.
+#[derive(Default)]
+pub struct LogMailer;
+
+impl LogMailer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) -> Result<(), Box<dyn Error>> {
+        tracing::info!(
+            to = message.to,
+            subject = message.subject,
+            body = message.body,
+            "email not sent, log mailer in use"
+        );
+        Ok(())
+    }
+}
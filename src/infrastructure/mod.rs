@@ -1,2 +1,5 @@
+pub mod mailers;
+pub mod notifiers;
 pub mod repositories;
 pub mod postgres_database;
+pub mod sms_senders;
@@ -0,0 +1,71 @@
+use std::{error::Error, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{
+    application::services::{
+        mailer::{EmailMessage, Mailer},
+        notifier::{LoginNotification, Notifier},
+    },
+    util::user_agent::parse_user_agent,
+};
+
+/// Emails the account owner through the configured mailer.
+pub struct EmailNotifier {
+    mailer: Arc<dyn Mailer + Send + Sync>,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: Arc<dyn Mailer + Send + Sync>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    async fn notify(&self, notification: LoginNotification) -> Result<(), Box<dyn Error>> {
+        let info = notification
+            .user_agent
+            .as_deref()
+            .map(parse_user_agent)
+            .unwrap_or_default();
+        let unknown = || "Unknown".to_string();
+
+        let mut reasons = Vec::new();
+        if notification.risk.new_device {
+            reasons.push("from a device you have not used before".to_string());
+        }
+        if notification.risk.new_country {
+            reasons.push("from a country you have not signed in from before".to_string());
+        }
+        if notification.risk.recent_failures > 0 {
+            reasons.push(format!(
+                "after {} failed attempts",
+                notification.risk.recent_failures
+            ));
+        }
+
+        let body = format!(
+            "Hi {},\n\n\
+             Your account was just signed in to {}.\n\n\
+             Time: {}\nBrowser: {}\nOperating system: {}\nIP address: {}\nCountry: {}\n\n\
+             If this was you, there is nothing to do. If not, change your password \
+             and sign out of your other sessions.",
+            notification.username,
+            reasons.join(" and "),
+            notification.occurred_at.to_rfc2822(),
+            info.browser.unwrap_or_else(unknown),
+            info.os.unwrap_or_else(unknown),
+            notification.ip_address.unwrap_or_else(unknown),
+            notification.country.unwrap_or_else(unknown),
+        );
+
+        self.mailer
+            .send(EmailMessage {
+                to: notification.email,
+                subject: "New sign-in to your account".to_string(),
+                body,
+            })
+            .await
+    }
+}
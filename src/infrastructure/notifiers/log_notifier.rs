@@ -0,0 +1,31 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::application::services::notifier::{LoginNotification, Notifier};
+
+/// Writes notifications to the log instead of telling the user.
+#[derive(Default)]
+pub struct LogNotifier;
+
+impl LogNotifier {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, notification: LoginNotification) -> Result<(), Box<dyn Error>> {
+        tracing::info!(
+            user_id = %notification.user_id,
+            score = notification.risk.score,
+            new_device = notification.risk.new_device,
+            new_country = notification.risk.new_country,
+            ip_address = notification.ip_address,
+            country = notification.country,
+            "unusual sign-in, log notifier in use"
+        );
+        Ok(())
+    }
+}
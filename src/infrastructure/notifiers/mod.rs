@@ -0,0 +1,2 @@
+pub mod email_notifier;
+pub mod log_notifier;
@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::application::repositories::rate_limit_store::RateLimitStore;
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision};
+use async_trait::async_trait;
+
+/// Upper bound on tracked buckets before full ones are swept.
+const SWEEP_THRESHOLD: usize = 100_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    /// When the bucket is back at capacity under its own rule and can be
+    /// forgotten.
+    full_at: Instant,
+}
+
+/// Token buckets kept in process memory. Limits are per instance.
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops buckets that have refilled, as a fresh one would be identical.
+    fn sweep(buckets: &mut HashMap<String, Bucket>, now: Instant) {
+        buckets.retain(|_, bucket| bucket.full_at > now);
+    }
+
+    fn take_at(&self, key: &str, limit: RateLimit, now: Instant) -> RateLimitDecision {
+        let capacity = limit.capacity as f64;
+        let rate = limit.refill_per_second();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+
+        if buckets.len() > SWEEP_THRESHOLD {
+            Self::sweep(&mut buckets, now);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+            full_at: now,
+        });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        bucket.full_at = now
+            + Duration::try_from_secs_f64((capacity - bucket.tokens) / rate)
+                .unwrap_or(Duration::ZERO);
+
+        RateLimitDecision {
+            allowed,
+            limit,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((capacity - bucket.tokens) / rate).ceil() as u64,
+            retry_after: if allowed {
+                0
+            } else {
+                ((1.0 - bucket.tokens) / rate).ceil() as u64
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(&self, key: &str, limit: RateLimit) -> Result<RateLimitDecision, Box<dyn Error>> {
+        Ok(self.take_at(key, limit, Instant::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_drains_and_refills() {
+        let store = MemoryRateLimitStore::new();
+        let limit = RateLimit {
+            capacity: 2,
+            period: 10,
+        };
+        let start = Instant::now();
+
+        let first = store.take_at("ip:127.0.0.1", limit, start);
+        assert!(first.allowed);
+        assert_eq!(1, first.remaining);
+
+        let second = store.take_at("ip:127.0.0.1", limit, start);
+        assert!(second.allowed);
+        assert_eq!(0, second.remaining);
+        assert_eq!(10, second.reset);
+
+        let third = store.take_at("ip:127.0.0.1", limit, start);
+        assert!(!third.allowed);
+        assert_eq!(5, third.retry_after);
+        assert!(store.take_at("ip:10.0.0.1", limit, start).allowed);
+
+        let refilled = store.take_at("ip:127.0.0.1", limit, start + Duration::from_secs(5));
+        assert!(refilled.allowed);
+    }
+
+    #[test]
+    fn test_sweep_keeps_buckets_until_their_own_rule_refills_them() {
+        let store = MemoryRateLimitStore::new();
+        let short = RateLimit {
+            capacity: 10,
+            period: 1,
+        };
+        let long = RateLimit {
+            capacity: 10,
+            period: 3600,
+        };
+        let start = Instant::now();
+        store.take_at("route:short", short, start);
+        store.take_at("route:long", long, start);
+
+        let mut buckets = store.buckets.lock().unwrap();
+        MemoryRateLimitStore::sweep(&mut buckets, start + Duration::from_secs(60));
+        assert!(!buckets.contains_key("route:short"));
+        assert!(buckets.contains_key("route:long"));
+
+        MemoryRateLimitStore::sweep(&mut buckets, start + Duration::from_secs(3600));
+        assert!(buckets.is_empty());
+    }
+}
@@ -1 +1,21 @@
+pub mod memory_rate_limit_store;
+pub mod postgres_authorization_code_repo;
+pub mod postgres_client_assertion_repo;
+pub mod postgres_device_code_repo;
+pub mod postgres_dpop_proof_repo;
+pub mod postgres_lockout_repo;
+pub mod postgres_login_event_repo;
+pub mod postgres_mfa_repo;
+pub mod postgres_oauth_client_repo;
+pub mod postgres_oauth_grant_repo;
+pub mod postgres_one_time_code_repo;
+pub mod postgres_pushed_request_repo;
+pub mod postgres_refresh_token_repo;
+pub mod postgres_revoked_token_repo;
+pub mod postgres_session_repo;
 pub mod postgres_user_repo;
+pub mod postgres_webauthn_repo;
+#[cfg(feature = "redis")]
+pub mod redis_rate_limit_store;
+#[cfg(test)]
+pub mod test_support;
@@ -0,0 +1,203 @@
+use std::error::Error;
+
+use crate::application::repositories::authorization_code_repository::AuthorizationCodeRepository;
+use crate::domain::oauth::{AuthorizationCode, NewAuthorizationCode};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresAuthorizationCodeRepository {
+    pool: PgPool,
+}
+
+impl PostgresAuthorizationCodeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuthorizationCodeRepository for PostgresAuthorizationCodeRepository {
+    async fn create(&self, code: &NewAuthorizationCode) -> Result<PgQueryResult, Box<dyn Error>> {
+        let auth_time = DateTime::from_timestamp(code.auth.auth_time, 0).unwrap_or_else(Utc::now);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM oauth_authorization_codes
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO oauth_authorization_codes (code_hash, client_id, user_id, redirect_uri,
+                scope, code_challenge, nonce, auth_time, amr, acr, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ",
+            code.code_hash,
+            code.client_id,
+            code.user_id,
+            code.redirect_uri,
+            code.scope,
+            code.code_challenge,
+            code.nonce,
+            auth_time,
+            &code.auth.amr,
+            code.auth.acr,
+            code.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_code_hash(
+        &self,
+        code_hash: &str,
+    ) -> Result<Option<AuthorizationCode>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            AuthorizationCode,
+            "
+            SELECT id, code_hash, client_id, user_id, redirect_uri, scope, code_challenge, nonce,
+                auth_time, amr, acr, expires_at, consumed_at, family_id, access_jti,
+                access_expires_at, created_at
+            FROM oauth_authorization_codes
+            WHERE code_hash = $1
+            ",
+            code_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn consume(&self, id: Uuid, family_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_authorization_codes
+            SET consumed_at = CURRENT_TIMESTAMP,
+                family_id = $2
+            WHERE id = $1 AND consumed_at IS NULL
+            ",
+            id,
+            family_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn record_access_token(
+        &self,
+        id: Uuid,
+        jti: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_authorization_codes
+            SET access_jti = $2,
+                access_expires_at = $3
+            WHERE id = $1
+            ",
+            id,
+            jti,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::auth_context::{AuthContext, AMR_PASSWORD};
+    use crate::infrastructure::repositories::test_support::{
+        create_client, create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn code_is_consumed_once() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+        let repo = PostgresAuthorizationCodeRepository::new(pool.clone());
+        let user_id = create_user(&pool, "oauthuser").await;
+        let client_id = create_client(&pool).await;
+
+        repo.create(&NewAuthorizationCode {
+            code_hash: "a".repeat(64),
+            client_id,
+            user_id,
+            redirect_uri: "https://app.example.com/cb".to_string(),
+            scope: "profile".to_string(),
+            code_challenge: "b".repeat(43),
+            nonce: Some("n-0S6_WzA2Mj".to_string()),
+            auth: AuthContext::new(&[AMR_PASSWORD]),
+            expires_at: Utc::now() + chrono::Duration::seconds(60),
+        })
+        .await
+        .unwrap();
+
+        let code = repo
+            .find_by_code_hash(&"a".repeat(64))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user_id, code.user_id);
+        assert_eq!(vec![AMR_PASSWORD.to_string()], code.amr);
+        assert_eq!(Some("n-0S6_WzA2Mj"), code.nonce.as_deref());
+        assert!(!code.is_expired());
+
+        let family_id = Uuid::new_v4();
+        assert_eq!(
+            1,
+            repo.consume(code.id, family_id)
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+        assert_eq!(
+            0,
+            repo.consume(code.id, Uuid::new_v4())
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+        let jti = Uuid::new_v4();
+        repo.record_access_token(code.id, jti, Utc::now())
+            .await
+            .unwrap();
+        let code = repo
+            .find_by_code_hash(&"a".repeat(64))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(code.consumed_at.is_some());
+        assert_eq!(Some(family_id), code.family_id);
+        assert_eq!(Some(jti), code.access_jti);
+
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+    }
+}
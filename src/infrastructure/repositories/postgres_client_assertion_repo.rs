@@ -0,0 +1,87 @@
+use std::error::Error;
+
+use crate::application::repositories::client_assertion_repository::ClientAssertionRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresClientAssertionRepository {
+    pool: PgPool,
+}
+
+impl PostgresClientAssertionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ClientAssertionRepository for PostgresClientAssertionRepository {
+    async fn record(
+        &self,
+        client_id: Uuid,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM oauth_client_assertions
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO oauth_client_assertions (client_id, jti, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (client_id, jti) DO NOTHING
+            ",
+            client_id,
+            jti,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::{
+        create_client, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn assertion_is_accepted_once() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["oauth_clients"]).await;
+        let repo = PostgresClientAssertionRepository::new(pool.clone());
+        let client_id = create_client(&pool).await;
+        let other_client_id = create_client(&pool).await;
+        let expires_at = Utc::now() + chrono::Duration::seconds(60);
+
+        let result = repo.record(client_id, "jti-1", expires_at).await.unwrap();
+        assert_eq!(1, result.rows_affected());
+        let result = repo.record(client_id, "jti-1", expires_at).await.unwrap();
+        assert_eq!(0, result.rows_affected());
+        let result = repo
+            .record(other_client_id, "jti-1", expires_at)
+            .await
+            .unwrap();
+        assert_eq!(1, result.rows_affected());
+
+        reset_tables(&pool, &["oauth_clients"]).await;
+    }
+}
@@ -0,0 +1,302 @@
+use std::error::Error;
+
+use crate::application::repositories::device_code_repository::DeviceCodeRepository;
+use crate::domain::auth_context::AuthContext;
+use crate::domain::oauth::{DeviceCode, NewDeviceCode};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresDeviceCodeRepository {
+    pool: PgPool,
+}
+
+impl PostgresDeviceCodeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DeviceCodeRepository for PostgresDeviceCodeRepository {
+    async fn create(&self, code: &NewDeviceCode) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM oauth_device_codes
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO oauth_device_codes (device_code_hash, user_code_hash, client_id, scope,
+                poll_interval, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ",
+            code.device_code_hash,
+            code.user_code_hash,
+            code.client_id,
+            code.scope,
+            code.poll_interval,
+            code.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_device_code_hash(
+        &self,
+        device_code_hash: &str,
+    ) -> Result<Option<DeviceCode>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            DeviceCode,
+            "
+            SELECT id, device_code_hash, user_code_hash, client_id, scope, status, user_id,
+                auth_time, amr, acr, poll_interval, last_polled_at, expires_at, consumed_at,
+                created_at
+            FROM oauth_device_codes
+            WHERE device_code_hash = $1
+            ",
+            device_code_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_user_code_hash(
+        &self,
+        user_code_hash: &str,
+    ) -> Result<Option<DeviceCode>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            DeviceCode,
+            "
+            SELECT id, device_code_hash, user_code_hash, client_id, scope, status, user_id,
+                auth_time, amr, acr, poll_interval, last_polled_at, expires_at, consumed_at,
+                created_at
+            FROM oauth_device_codes
+            WHERE user_code_hash = $1
+            ",
+            user_code_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn approve(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        auth: &AuthContext,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let auth_time = DateTime::from_timestamp(auth.auth_time, 0).unwrap_or_else(Utc::now);
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_device_codes
+            SET status = 'approved', user_id = $2, auth_time = $3, amr = $4, acr = $5
+            WHERE id = $1 AND status = 'pending' AND expires_at > CURRENT_TIMESTAMP
+            ",
+            id,
+            user_id,
+            auth_time,
+            &auth.amr,
+            auth.acr
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn deny(&self, id: Uuid, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_device_codes
+            SET status = 'denied', user_id = $2
+            WHERE id = $1 AND status = 'pending' AND expires_at > CURRENT_TIMESTAMP
+            ",
+            id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn record_poll(
+        &self,
+        id: Uuid,
+        poll_interval: i32,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_device_codes
+            SET last_polled_at = CURRENT_TIMESTAMP, poll_interval = $2
+            WHERE id = $1
+            ",
+            id,
+            poll_interval
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn consume(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_device_codes
+            SET consumed_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND status = 'approved' AND consumed_at IS NULL
+            ",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::auth_context::AMR_PASSWORD;
+    use crate::domain::oauth::{DEVICE_APPROVED, DEVICE_DENIED};
+    use crate::infrastructure::repositories::test_support::{
+        create_client, create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    async fn create_code(repo: &PostgresDeviceCodeRepository, client_id: Uuid, tag: &str) {
+        repo.create(&NewDeviceCode {
+            device_code_hash: format!("device-{}", tag),
+            user_code_hash: format!("user-{}", tag),
+            client_id,
+            scope: String::new(),
+            poll_interval: 5,
+            expires_at: Utc::now() + chrono::Duration::seconds(600),
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn approved_code_is_consumed_once() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+        let repo = PostgresDeviceCodeRepository::new(pool.clone());
+        let user_id = create_user(&pool, "deviceuser").await;
+        let client_id = create_client(&pool).await;
+        create_code(&repo, client_id, "a").await;
+
+        let code = repo
+            .find_by_user_code_hash("user-a")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(code.is_pending());
+        assert!(!code.polled_too_soon());
+        assert_eq!(0, repo.consume(code.id).await.unwrap().rows_affected());
+
+        repo.record_poll(code.id, 10).await.unwrap();
+        let auth = AuthContext::new(&[AMR_PASSWORD]);
+        assert_eq!(
+            1,
+            repo.approve(code.id, user_id, &auth)
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+        assert_eq!(
+            0,
+            repo.deny(code.id, user_id).await.unwrap().rows_affected()
+        );
+
+        let code = repo
+            .find_by_device_code_hash("device-a")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(DEVICE_APPROVED, code.status);
+        assert_eq!(Some(user_id), code.user_id);
+        assert_eq!(auth.amr, code.auth_context().amr);
+        assert_eq!(10, code.poll_interval);
+        assert!(code.polled_too_soon());
+
+        assert_eq!(1, repo.consume(code.id).await.unwrap().rows_affected());
+        assert_eq!(0, repo.consume(code.id).await.unwrap().rows_affected());
+
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+    }
+
+    #[tokio::test]
+    async fn denied_code_cannot_be_approved() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+        let repo = PostgresDeviceCodeRepository::new(pool.clone());
+        let user_id = create_user(&pool, "deviceuser").await;
+        let client_id = create_client(&pool).await;
+        create_code(&repo, client_id, "b").await;
+
+        let code = repo
+            .find_by_user_code_hash("user-b")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            1,
+            repo.deny(code.id, user_id).await.unwrap().rows_affected()
+        );
+        let auth = AuthContext::new(&[AMR_PASSWORD]);
+        assert_eq!(
+            0,
+            repo.approve(code.id, user_id, &auth)
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+
+        let code = repo
+            .find_by_user_code_hash("user-b")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(DEVICE_DENIED, code.status);
+        assert!(!code.is_pending());
+        assert_eq!(0, repo.consume(code.id).await.unwrap().rows_affected());
+
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+    }
+}
@@ -0,0 +1,81 @@
+use std::error::Error;
+
+use crate::application::repositories::dpop_proof_repository::DpopProofRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+
+pub struct PostgresDpopProofRepository {
+    pool: PgPool,
+}
+
+impl PostgresDpopProofRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DpopProofRepository for PostgresDpopProofRepository {
+    async fn record(
+        &self,
+        jkt: &str,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM dpop_proofs
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO dpop_proofs (jkt, jti, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jkt, jti) DO NOTHING
+            ",
+            jkt,
+            jti,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::{reset_tables, setup_database};
+    use tokio;
+
+    #[tokio::test]
+    async fn proof_is_accepted_once_per_key() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["dpop_proofs"]).await;
+        let repo = PostgresDpopProofRepository::new(pool.clone());
+        let jkt = "a".repeat(43);
+        let other_jkt = "b".repeat(43);
+        let expires_at = Utc::now() + chrono::Duration::seconds(60);
+
+        let result = repo.record(&jkt, "jti-1", expires_at).await.unwrap();
+        assert_eq!(1, result.rows_affected());
+        let result = repo.record(&jkt, "jti-1", expires_at).await.unwrap();
+        assert_eq!(0, result.rows_affected());
+        let result = repo.record(&other_jkt, "jti-1", expires_at).await.unwrap();
+        assert_eq!(1, result.rows_affected());
+
+        reset_tables(&pool, &["dpop_proofs"]).await;
+    }
+}
@@ -0,0 +1,208 @@
+use std::error::Error;
+
+use crate::application::repositories::lockout_repository::LockoutRepository;
+use crate::domain::lockout::{AccountLockout, LockoutEvent};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresLockoutRepository {
+    pool: PgPool,
+}
+
+impl PostgresLockoutRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LockoutRepository for PostgresLockoutRepository {
+    async fn find_by_user_id(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<AccountLockout>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            AccountLockout,
+            "
+            SELECT user_id, failed_attempts, lockouts, locked_until, last_failed_at
+            FROM account_lockouts
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn increment_failed_attempts(
+        &self,
+        user_id: Uuid,
+    ) -> Result<AccountLockout, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            AccountLockout,
+            "
+            INSERT INTO account_lockouts (user_id, failed_attempts, last_failed_at)
+            VALUES ($1, 1, CURRENT_TIMESTAMP)
+            ON CONFLICT (user_id) DO UPDATE
+            SET failed_attempts = account_lockouts.failed_attempts + 1,
+                last_failed_at = CURRENT_TIMESTAMP
+            RETURNING user_id, failed_attempts, lockouts, locked_until, last_failed_at
+            ",
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn lock(
+        &self,
+        user_id: Uuid,
+        locked_until: DateTime<Utc>,
+    ) -> Result<AccountLockout, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            AccountLockout,
+            "
+            UPDATE account_lockouts
+            SET failed_attempts = 0,
+                lockouts = lockouts + 1,
+                locked_until = $1
+            WHERE user_id = $2
+            RETURNING user_id, failed_attempts, lockouts, locked_until, last_failed_at
+            ",
+            locked_until,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn reset(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM account_lockouts
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn record_event(
+        &self,
+        user_id: Uuid,
+        event: &str,
+        ip_address: Option<&str>,
+        actor_id: Option<Uuid>,
+        locked_until: Option<DateTime<Utc>>,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO lockout_events (user_id, event, ip_address, actor_id, locked_until)
+            VALUES ($1, $2, $3, $4, $5)
+            ",
+            user_id,
+            event,
+            ip_address,
+            actor_id,
+            locked_until
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_events(&self, user_id: Uuid) -> Result<Vec<LockoutEvent>, Box<dyn Error>> {
+        let results = sqlx::query_as!(
+            LockoutEvent,
+            "
+            SELECT id, user_id, event, ip_address, actor_id, locked_until, created_at
+            FROM lockout_events
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            ",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::{
+        create_user, reset_tables, setup_database,
+    };
+    use chrono::Duration;
+    use tokio;
+
+    #[tokio::test]
+    async fn lock_and_reset() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresLockoutRepository::new(pool.clone());
+        let user_id = create_user(&pool, "lockeduser").await;
+
+        assert!(repo.find_by_user_id(user_id).await.unwrap().is_none());
+
+        repo.increment_failed_attempts(user_id).await.unwrap();
+        let lockout = repo.increment_failed_attempts(user_id).await.unwrap();
+        assert_eq!(2, lockout.failed_attempts);
+
+        let locked_until = Utc::now() + Duration::seconds(30);
+        let lockout = repo.lock(user_id, locked_until).await.unwrap();
+        assert_eq!(0, lockout.failed_attempts);
+        assert_eq!(1, lockout.lockouts);
+        assert!(lockout.is_locked(Utc::now()));
+
+        repo.record_event(
+            user_id,
+            "locked",
+            Some("127.0.0.1"),
+            None,
+            Some(locked_until),
+        )
+        .await
+        .unwrap();
+        let events = repo.find_events(user_id).await.unwrap();
+        assert_eq!(1, events.len());
+        assert_eq!("locked", events[0].event);
+
+        let result = repo.reset(user_id).await.unwrap();
+        assert_eq!(1, result.rows_affected());
+        assert!(repo.find_by_user_id(user_id).await.unwrap().is_none());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+}
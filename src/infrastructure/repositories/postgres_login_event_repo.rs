@@ -0,0 +1,248 @@
+use std::error::Error;
+
+use crate::application::repositories::login_event_repository::LoginEventRepository;
+use crate::domain::login_event::{LoginEvent, NewLoginEvent};
+use crate::domain::login_risk::LoginHistory;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresLoginEventRepository {
+    pool: PgPool,
+}
+
+impl PostgresLoginEventRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LoginEventRepository for PostgresLoginEventRepository {
+    async fn record(&self, event: &NewLoginEvent) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO login_events (user_id, success, method, mfa_method, failure_reason,
+                ip_address, user_agent, device_hash, country)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ",
+            event.user_id,
+            event.success,
+            event.method,
+            event.mfa_method,
+            event.failure_reason,
+            event.ip_address,
+            event.user_agent,
+            event.device_hash,
+            event.country
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<LoginEvent>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            LoginEvent,
+            "
+            SELECT id, user_id, success, method, mfa_method, failure_reason, ip_address,
+                user_agent, country, created_at
+            FROM login_events
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            ",
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn count_by_user(&self, user_id: Uuid) -> Result<i64, Box<dyn Error>> {
+        let result = sqlx::query!(
+            "
+            SELECT COUNT(*) AS \"count!\"
+            FROM login_events
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result.count)
+    }
+
+    async fn find_history(
+        &self,
+        user_id: Uuid,
+        device_hash: Option<&str>,
+        country: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<LoginHistory, Box<dyn Error>> {
+        let result = sqlx::query!(
+            "
+            SELECT
+                COUNT(*) FILTER (WHERE success AND device_hash IS NOT NULL) AS \"devices!\",
+                COALESCE(BOOL_OR(success AND device_hash = $2), false) AS \"known_device!\",
+                COUNT(*) FILTER (WHERE success AND country IS NOT NULL) AS \"countries!\",
+                COALESCE(BOOL_OR(success AND country = $3), false) AS \"known_country!\",
+                COUNT(*) FILTER (WHERE NOT success AND created_at > $4) AS \"recent_failures!\"
+            FROM login_events
+            WHERE user_id = $1
+            ",
+            user_id,
+            device_hash,
+            country,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(LoginHistory {
+            devices: result.devices,
+            known_device: result.known_device,
+            countries: result.countries,
+            known_country: result.known_country,
+            recent_failures: result.recent_failures,
+        })
+    }
+
+    async fn delete_before(&self, cutoff: DateTime<Utc>) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM login_events
+            WHERE created_at < $1
+            ",
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::auth_context::AMR_PASSWORD;
+    use crate::domain::client::ClientContext;
+    use crate::domain::login_event::{LoginAttempt, INVALID_CREDENTIALS};
+    use crate::infrastructure::repositories::test_support::{
+        create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn record_page_and_prune_events() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresLoginEventRepository::new(pool.clone());
+        let user_id = create_user(&pool, "eventuser").await;
+        let client = ClientContext {
+            ip_address: Some("127.0.0.1".to_string()),
+            ..ClientContext::default()
+        };
+
+        let mut attempt = LoginAttempt::new(AMR_PASSWORD);
+        attempt.user_id = Some(user_id);
+        for reason in [Some(INVALID_CREDENTIALS), Some(INVALID_CREDENTIALS), None] {
+            repo.record(&NewLoginEvent::new(attempt.clone(), reason, &client))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(3, repo.count_by_user(user_id).await.unwrap());
+        let page = repo.find_by_user(user_id, 2, 0).await.unwrap();
+        assert_eq!(2, page.len());
+        let page = repo.find_by_user(user_id, 2, 2).await.unwrap();
+        assert_eq!(1, page.len());
+        assert_eq!(Some("127.0.0.1".to_string()), page[0].ip_address);
+
+        let result = repo
+            .delete_before(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(result.rows_affected() >= 3);
+        assert_eq!(0, repo.count_by_user(user_id).await.unwrap());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn history_only_knows_successful_devices() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresLoginEventRepository::new(pool.clone());
+        let user_id = create_user(&pool, "eventuser").await;
+        let client = |device_id: &str, country: &str| ClientContext {
+            device_id: Some(device_id.to_string()),
+            country: Some(country.to_string()),
+            ..ClientContext::default()
+        };
+
+        let mut attempt = LoginAttempt::new(AMR_PASSWORD);
+        attempt.user_id = Some(user_id);
+        repo.record(&NewLoginEvent::new(
+            attempt.clone(),
+            None,
+            &client("laptop", "DE"),
+        ))
+        .await
+        .unwrap();
+        repo.record(&NewLoginEvent::new(
+            attempt,
+            Some(INVALID_CREDENTIALS),
+            &client("phone", "FR"),
+        ))
+        .await
+        .unwrap();
+
+        let since = Utc::now() - chrono::Duration::days(1);
+        let laptop = client("laptop", "DE");
+        let history = repo
+            .find_history(user_id, laptop.device_hash().as_deref(), Some("DE"), since)
+            .await
+            .unwrap();
+        assert_eq!(1, history.devices);
+        assert!(history.known_device);
+        assert!(history.known_country);
+        assert_eq!(1, history.recent_failures);
+
+        let phone = client("phone", "FR");
+        let history = repo
+            .find_history(user_id, phone.device_hash().as_deref(), Some("FR"), since)
+            .await
+            .unwrap();
+        assert!(!history.known_device);
+        assert!(!history.known_country);
+
+        let page = repo.find_by_user(user_id, 10, 0).await.unwrap();
+        assert!(page
+            .iter()
+            .any(|event| event.country.as_deref() == Some("FR")));
+
+        reset_tables(&pool, &["users"]).await;
+    }
+}
@@ -0,0 +1,282 @@
+use std::error::Error;
+
+use crate::application::repositories::mfa_repository::MfaRepository;
+use crate::domain::mfa::UserTotp;
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresMfaRepository {
+    pool: PgPool,
+}
+
+impl PostgresMfaRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MfaRepository for PostgresMfaRepository {
+    async fn find_totp(&self, user_id: Uuid) -> Result<Option<UserTotp>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            UserTotp,
+            "
+            SELECT user_id, secret_ciphertext, secret_nonce, confirmed_at, last_used_step, created_at
+            FROM user_totp
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn save_pending_totp(
+        &self,
+        user_id: Uuid,
+        secret_ciphertext: &[u8],
+        secret_nonce: &[u8],
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO user_totp (user_id, secret_ciphertext, secret_nonce)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret_ciphertext = EXCLUDED.secret_ciphertext,
+                secret_nonce = EXCLUDED.secret_nonce,
+                last_used_step = NULL,
+                created_at = CURRENT_TIMESTAMP
+            WHERE user_totp.confirmed_at IS NULL
+            ",
+            user_id,
+            secret_ciphertext,
+            secret_nonce
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn confirm_totp(
+        &self,
+        user_id: Uuid,
+        step: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE user_totp
+            SET confirmed_at = CURRENT_TIMESTAMP,
+                last_used_step = $2
+            WHERE user_id = $1 AND confirmed_at IS NULL
+            ",
+            user_id,
+            step
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(result);
+        }
+
+        sqlx::query!(
+            "
+            DELETE FROM mfa_recovery_codes
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+            INSERT INTO mfa_recovery_codes (user_id, code_hash)
+            SELECT $1, UNNEST($2::VARCHAR[])
+            ",
+            user_id,
+            recovery_code_hashes
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn record_totp_step(
+        &self,
+        user_id: Uuid,
+        step: i64,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE user_totp
+            SET last_used_step = $2
+            WHERE user_id = $1
+                AND confirmed_at IS NOT NULL
+                AND (last_used_step IS NULL OR last_used_step < $2)
+            ",
+            user_id,
+            step
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn redeem_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE mfa_recovery_codes
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            ",
+            user_id,
+            code_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM mfa_recovery_codes
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM user_totp
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::{
+        create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn confirm_totp_and_redeem_codes() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresMfaRepository::new(pool.clone());
+        let user_id = create_user(&pool, "mfauser").await;
+
+        let result = repo
+            .save_pending_totp(user_id, b"ciphertext", b"nonce")
+            .await
+            .unwrap();
+        assert_eq!(1, result.rows_affected());
+        assert!(!repo
+            .find_totp(user_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_confirmed());
+
+        let code_hashes = vec!["first".to_string(), "second".to_string()];
+        let result = repo.confirm_totp(user_id, 10, &code_hashes).await.unwrap();
+        assert_eq!(1, result.rows_affected());
+        let totp = repo.find_totp(user_id).await.unwrap().unwrap();
+        assert!(totp.is_confirmed());
+        assert_eq!(Some(10), totp.last_used_step);
+
+        let result = repo
+            .save_pending_totp(user_id, b"other", b"nonce")
+            .await
+            .unwrap();
+        assert_eq!(0, result.rows_affected());
+
+        assert_eq!(
+            0,
+            repo.record_totp_step(user_id, 10)
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+        assert_eq!(
+            1,
+            repo.record_totp_step(user_id, 11)
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+
+        assert_eq!(
+            1,
+            repo.redeem_recovery_code(user_id, "first")
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+        assert_eq!(
+            0,
+            repo.redeem_recovery_code(user_id, "first")
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+
+        assert_eq!(1, repo.delete(user_id).await.unwrap().rows_affected());
+        assert!(repo.find_totp(user_id).await.unwrap().is_none());
+        assert_eq!(
+            0,
+            repo.redeem_recovery_code(user_id, "second")
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+
+        reset_tables(&pool, &["users"]).await;
+    }
+}
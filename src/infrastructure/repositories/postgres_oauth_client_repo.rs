@@ -0,0 +1,305 @@
+use std::error::Error;
+
+use crate::application::repositories::oauth_client_repository::OAuthClientRepository;
+use crate::domain::oauth::{NewOAuthClient, OAuthClient};
+use crate::dto::oauth_client_dto::UpdateClientRequest;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use sqlx::postgres::PgQueryResult;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresOAuthClientRepository {
+    pool: PgPool,
+}
+
+impl PostgresOAuthClientRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OAuthClientRepository for PostgresOAuthClientRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<OAuthClient>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            OAuthClient,
+            "
+            SELECT id, name, client_type, first_party,
+                require_pushed_authorization_requests, require_signed_request_object, secret_hash,
+                previous_secret_hash, previous_secret_expires_at, redirect_uris, grant_types, scopes, audiences,
+                access_ttl, refresh_ttl, jwks AS \"jwks: Json<JwkSet>\", created_at, updated_at
+            FROM oauth_clients
+            WHERE id = $1
+            ",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_all(&self) -> Result<Vec<OAuthClient>, Box<dyn Error>> {
+        let results = sqlx::query_as!(
+            OAuthClient,
+            "
+            SELECT id, name, client_type, first_party,
+                require_pushed_authorization_requests, require_signed_request_object, secret_hash,
+                previous_secret_hash, previous_secret_expires_at, redirect_uris, grant_types, scopes, audiences,
+                access_ttl, refresh_ttl, jwks AS \"jwks: Json<JwkSet>\", created_at, updated_at
+            FROM oauth_clients
+            ORDER BY created_at
+            "
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn create(&self, client: &NewOAuthClient) -> Result<OAuthClient, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            OAuthClient,
+            "
+            INSERT INTO oauth_clients (name, client_type, first_party,
+                require_pushed_authorization_requests, require_signed_request_object, secret_hash,
+                redirect_uris, grant_types, scopes, audiences, access_ttl, refresh_ttl, jwks)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id, name, client_type, first_party,
+                require_pushed_authorization_requests, require_signed_request_object, secret_hash,
+                previous_secret_hash, previous_secret_expires_at, redirect_uris, grant_types, scopes, audiences,
+                access_ttl, refresh_ttl, jwks AS \"jwks: Json<JwkSet>\", created_at, updated_at
+            ",
+            client.name,
+            client.client_type,
+            client.first_party,
+            client.require_pushed_authorization_requests,
+            client.require_signed_request_object,
+            client.secret_hash,
+            &client.redirect_uris,
+            &client.grant_types,
+            &client.scopes,
+            &client.audiences,
+            client.access_ttl,
+            client.refresh_ttl,
+            client.jwks.as_ref().map(Json) as _
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        data: &UpdateClientRequest,
+    ) -> Result<Option<OAuthClient>, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            OAuthClient,
+            "
+            UPDATE oauth_clients
+            SET name = $1,
+                first_party = $2,
+                require_pushed_authorization_requests = $3,
+                require_signed_request_object = $4,
+                redirect_uris = $5,
+                grant_types = $6,
+                scopes = $7,
+                audiences = $8,
+                access_ttl = $9,
+                refresh_ttl = $10,
+                jwks = $11,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $12
+            RETURNING id, name, client_type, first_party,
+                require_pushed_authorization_requests, require_signed_request_object, secret_hash,
+                previous_secret_hash, previous_secret_expires_at, redirect_uris, grant_types, scopes, audiences,
+                access_ttl, refresh_ttl, jwks AS \"jwks: Json<JwkSet>\", created_at, updated_at
+            ",
+            data.name,
+            data.first_party,
+            data.require_pushed_authorization_requests,
+            data.require_signed_request_object,
+            &data.redirect_uris,
+            &data.grant_types,
+            &data.scopes,
+            &data.audiences,
+            data.access_ttl,
+            data.refresh_ttl,
+            data.jwks.as_ref().map(Json) as _,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn rotate_secret(
+        &self,
+        id: Uuid,
+        secret_hash: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> Result<Option<OAuthClient>, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            OAuthClient,
+            "
+            UPDATE oauth_clients
+            SET previous_secret_hash = secret_hash,
+                previous_secret_expires_at = $2,
+                secret_hash = $1,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $3
+            RETURNING id, name, client_type, first_party,
+                require_pushed_authorization_requests, require_signed_request_object, secret_hash,
+                previous_secret_hash, previous_secret_expires_at, redirect_uris, grant_types, scopes, audiences,
+                access_ttl, refresh_ttl, jwks AS \"jwks: Json<JwkSet>\", created_at, updated_at
+            ",
+            secret_hash,
+            previous_expires_at,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM oauth_clients
+            WHERE id = $1
+            ",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::oauth::{hash_client_secret, CLIENT_CONFIDENTIAL, GRANT_AUTHORIZATION_CODE};
+    use crate::infrastructure::repositories::test_support::{reset_tables, setup_database};
+    use tokio;
+
+    fn new_client() -> NewOAuthClient {
+        NewOAuthClient {
+            name: "app".to_string(),
+            client_type: CLIENT_CONFIDENTIAL.to_string(),
+            first_party: false,
+            require_pushed_authorization_requests: false,
+            require_signed_request_object: false,
+            secret_hash: Some(hash_client_secret("first")),
+            redirect_uris: vec!["https://app.example.com/cb".to_string()],
+            grant_types: vec![GRANT_AUTHORIZATION_CODE.to_string()],
+            scopes: vec!["profile".to_string()],
+            audiences: Vec::new(),
+            access_ttl: Some(300),
+            refresh_ttl: None,
+            jwks: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_update_and_delete() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["oauth_clients"]).await;
+        let repo = PostgresOAuthClientRepository::new(pool.clone());
+
+        let client = repo.create(&new_client()).await.unwrap();
+        assert!(client.is_confidential());
+        assert_eq!(300, client.access_ttl_or(900));
+        assert_eq!(1, repo.find_all().await.unwrap().len());
+
+        let updated = repo
+            .update(
+                client.id,
+                &UpdateClientRequest {
+                    name: "renamed".to_string(),
+                    first_party: true,
+                    require_pushed_authorization_requests: true,
+                    require_signed_request_object: false,
+                    redirect_uris: vec!["https://app.example.com/other".to_string()],
+                    grant_types: vec![GRANT_AUTHORIZATION_CODE.to_string()],
+                    scopes: Vec::new(),
+                    audiences: vec!["https://api.example.com".to_string()],
+                    access_ttl: None,
+                    refresh_ttl: Some(86400),
+                    jwks: None,
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("renamed", updated.name);
+        assert!(updated.first_party);
+        assert!(updated.require_pushed_authorization_requests);
+        assert!(updated.allows_audience("https://api.example.com"));
+        assert!(updated.allows_redirect_uri("https://app.example.com/other"));
+        assert!(!updated.allows_redirect_uri("https://app.example.com/cb"));
+        assert_eq!(900, updated.access_ttl_or(900));
+        assert!(updated.verify_secret("first"));
+
+        assert_eq!(1, repo.delete(client.id).await.unwrap().rows_affected());
+        assert!(repo.find_by_id(client.id).await.unwrap().is_none());
+
+        reset_tables(&pool, &["oauth_clients"]).await;
+    }
+
+    #[tokio::test]
+    async fn rotation_keeps_previous_secret() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["oauth_clients"]).await;
+        let repo = PostgresOAuthClientRepository::new(pool.clone());
+
+        let client = repo.create(&new_client()).await.unwrap();
+        let rotated = repo
+            .rotate_secret(
+                client.id,
+                &hash_client_secret("second"),
+                Utc::now() + chrono::Duration::seconds(60),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(rotated.verify_secret("second"));
+        assert!(rotated.verify_secret("first"));
+        assert_eq!(
+            Some(hash_client_secret("first")),
+            rotated.previous_secret_hash
+        );
+
+        assert!(repo
+            .rotate_secret(Uuid::new_v4(), &hash_client_secret("third"), Utc::now())
+            .await
+            .unwrap()
+            .is_none());
+
+        reset_tables(&pool, &["oauth_clients"]).await;
+    }
+}
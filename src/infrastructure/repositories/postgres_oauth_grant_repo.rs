@@ -0,0 +1,166 @@
+use std::error::Error;
+
+use crate::application::repositories::oauth_grant_repository::OAuthGrantRepository;
+use crate::domain::oauth::OAuthGrant;
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresOAuthGrantRepository {
+    pool: PgPool,
+}
+
+impl PostgresOAuthGrantRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OAuthGrantRepository for PostgresOAuthGrantRepository {
+    async fn find(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<Option<OAuthGrant>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            OAuthGrant,
+            "
+            SELECT g.user_id, g.client_id, c.name AS client_name, g.scopes, g.created_at,
+                g.updated_at
+            FROM oauth_grants g
+            JOIN oauth_clients c ON c.id = g.client_id
+            WHERE g.user_id = $1 AND g.client_id = $2
+            ",
+            user_id,
+            client_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> Result<Vec<OAuthGrant>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            OAuthGrant,
+            "
+            SELECT g.user_id, g.client_id, c.name AS client_name, g.scopes, g.created_at,
+                g.updated_at
+            FROM oauth_grants g
+            JOIN oauth_clients c ON c.id = g.client_id
+            WHERE g.user_id = $1
+            ORDER BY g.updated_at DESC
+            ",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn save(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+        scopes: &[String],
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO oauth_grants (user_id, client_id, scopes)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, client_id) DO UPDATE
+            SET scopes = ARRAY(
+                    SELECT DISTINCT unnest(oauth_grants.scopes || EXCLUDED.scopes) ORDER BY 1
+                ),
+                updated_at = CURRENT_TIMESTAMP
+            ",
+            user_id,
+            client_id,
+            scopes
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM oauth_grants
+            WHERE user_id = $1 AND client_id = $2
+            ",
+            user_id,
+            client_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::{
+        create_client, create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn saving_grant_merges_scopes() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+        let repo = PostgresOAuthGrantRepository::new(pool.clone());
+        let user_id = create_user(&pool, "grantuser").await;
+        let client_id = create_client(&pool).await;
+
+        repo.save(
+            user_id,
+            client_id,
+            &["openid".to_string(), "email".to_string()],
+        )
+        .await
+        .unwrap();
+        repo.save(
+            user_id,
+            client_id,
+            &["profile".to_string(), "email".to_string()],
+        )
+        .await
+        .unwrap();
+
+        let grant = repo.find(user_id, client_id).await.unwrap().unwrap();
+        assert_eq!("app", grant.client_name);
+        assert_eq!(vec!["email", "openid", "profile"], grant.scopes);
+        assert!(grant.covers("openid profile"));
+        assert_eq!(1, repo.find_by_user(user_id).await.unwrap().len());
+
+        assert_eq!(
+            1,
+            repo.delete(user_id, client_id)
+                .await
+                .unwrap()
+                .rows_affected()
+        );
+        assert!(repo.find(user_id, client_id).await.unwrap().is_none());
+
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+    }
+}
@@ -0,0 +1,222 @@
+use std::error::Error;
+
+use crate::application::repositories::one_time_code_repository::OneTimeCodeRepository;
+use crate::domain::one_time_code::OneTimeCode;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresOneTimeCodeRepository {
+    pool: PgPool,
+}
+
+impl PostgresOneTimeCodeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OneTimeCodeRepository for PostgresOneTimeCodeRepository {
+    async fn create(
+        &self,
+        user_id: Uuid,
+        purpose: &str,
+        code_hash: &str,
+        binding_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM one_time_codes
+            WHERE (user_id = $1 AND purpose = $2 AND consumed_at IS NULL)
+                OR expires_at < CURRENT_TIMESTAMP
+            ",
+            user_id,
+            purpose
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO one_time_codes (user_id, purpose, code_hash, binding_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            ",
+            user_id,
+            purpose,
+            code_hash,
+            binding_hash,
+            expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.id)
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<OneTimeCode>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            OneTimeCode,
+            "
+            SELECT id, user_id, purpose, code_hash, binding_hash, attempts, expires_at,
+                consumed_at, created_at
+            FROM one_time_codes
+            WHERE id = $1
+            ",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_latest(
+        &self,
+        user_id: Uuid,
+        purpose: &str,
+    ) -> Result<Option<OneTimeCode>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            OneTimeCode,
+            "
+            SELECT id, user_id, purpose, code_hash, binding_hash, attempts, expires_at,
+                consumed_at, created_at
+            FROM one_time_codes
+            WHERE user_id = $1 AND purpose = $2 AND consumed_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT 1
+            ",
+            user_id,
+            purpose
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn record_attempt(&self, id: Uuid) -> Result<i32, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE one_time_codes
+            SET attempts = attempts + 1
+            WHERE id = $1
+            RETURNING attempts
+            ",
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.attempts)
+    }
+
+    async fn consume(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE one_time_codes
+            SET consumed_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND consumed_at IS NULL
+            ",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn purge_expired(&self) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM one_time_codes
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::one_time_code::{EMAIL_CODE_PURPOSE, MAGIC_LINK_PURPOSE};
+    use crate::infrastructure::repositories::test_support::{
+        create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn create_attempt_and_consume() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresOneTimeCodeRepository::new(pool.clone());
+        let user_id = create_user(&pool, "otcuser").await;
+        let expires_at = Utc::now() + chrono::Duration::minutes(10);
+
+        let first = repo
+            .create(user_id, EMAIL_CODE_PURPOSE, "code", "binding", expires_at)
+            .await
+            .unwrap();
+        let link = repo
+            .create(user_id, MAGIC_LINK_PURPOSE, "link", "binding", expires_at)
+            .await
+            .unwrap();
+        let second = repo
+            .create(user_id, EMAIL_CODE_PURPOSE, "other", "binding", expires_at)
+            .await
+            .unwrap();
+        assert!(repo.find(first).await.unwrap().is_none());
+        assert!(repo.find(link).await.unwrap().is_some());
+        assert_eq!(
+            Some(second),
+            repo.find_latest(user_id, EMAIL_CODE_PURPOSE)
+                .await
+                .unwrap()
+                .map(|code| code.id)
+        );
+
+        let code = repo.find(second).await.unwrap().unwrap();
+        assert_eq!(user_id, code.user_id);
+        assert_eq!("other", code.code_hash);
+        assert_eq!(0, code.attempts);
+        assert!(code.is_usable());
+
+        assert_eq!(1, repo.record_attempt(second).await.unwrap());
+        assert_eq!(2, repo.record_attempt(second).await.unwrap());
+
+        assert_eq!(1, repo.consume(second).await.unwrap().rows_affected());
+        assert_eq!(0, repo.consume(second).await.unwrap().rows_affected());
+        assert!(!repo.find(second).await.unwrap().unwrap().is_usable());
+        assert!(repo
+            .find_latest(user_id, EMAIL_CODE_PURPOSE)
+            .await
+            .unwrap()
+            .is_none());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+}
@@ -0,0 +1,155 @@
+use std::error::Error;
+
+use crate::application::repositories::pushed_request_repository::PushedRequestRepository;
+use crate::domain::oauth::{NewPushedRequest, PushedRequest};
+use async_trait::async_trait;
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+
+pub struct PostgresPushedRequestRepository {
+    pool: PgPool,
+}
+
+impl PostgresPushedRequestRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PushedRequestRepository for PostgresPushedRequestRepository {
+    async fn create(&self, request: &NewPushedRequest) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM oauth_pushed_requests
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO oauth_pushed_requests (request_uri_hash, client_id, parameters, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ",
+            request.request_uri_hash,
+            request.client_id,
+            request.parameters,
+            request.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_request_uri_hash(
+        &self,
+        request_uri_hash: &str,
+    ) -> Result<Option<PushedRequest>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            PushedRequest,
+            "
+            SELECT id, request_uri_hash, client_id, parameters, expires_at, consumed_at, created_at
+            FROM oauth_pushed_requests
+            WHERE request_uri_hash = $1
+            ",
+            request_uri_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn consume(&self, request_uri_hash: &str) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_pushed_requests
+            SET consumed_at = CURRENT_TIMESTAMP
+            WHERE request_uri_hash = $1
+                AND consumed_at IS NULL
+                AND expires_at > CURRENT_TIMESTAMP
+            ",
+            request_uri_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::{
+        create_client, reset_tables, setup_database,
+    };
+    use chrono::Utc;
+    use tokio;
+
+    #[tokio::test]
+    async fn pushed_request_is_consumed_once() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["oauth_clients"]).await;
+        let repo = PostgresPushedRequestRepository::new(pool.clone());
+        let client_id = create_client(&pool).await;
+
+        repo.create(&NewPushedRequest {
+            request_uri_hash: "a".repeat(64),
+            client_id,
+            parameters: serde_json::json!({"scope": "openid", "state": "s1"}),
+            expires_at: Utc::now() + chrono::Duration::seconds(60),
+        })
+        .await
+        .unwrap();
+        repo.create(&NewPushedRequest {
+            request_uri_hash: "b".repeat(64),
+            client_id,
+            parameters: serde_json::json!({}),
+            expires_at: Utc::now() - chrono::Duration::seconds(1),
+        })
+        .await
+        .unwrap();
+
+        let pushed = repo
+            .find_by_request_uri_hash(&"a".repeat(64))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(client_id, pushed.client_id);
+        assert_eq!("s1", pushed.parameters["state"]);
+        assert!(pushed.is_active());
+
+        assert_eq!(
+            1,
+            repo.consume(&"a".repeat(64)).await.unwrap().rows_affected()
+        );
+        assert_eq!(
+            0,
+            repo.consume(&"a".repeat(64)).await.unwrap().rows_affected()
+        );
+        assert_eq!(
+            0,
+            repo.consume(&"b".repeat(64)).await.unwrap().rows_affected()
+        );
+        assert!(!repo
+            .find_by_request_uri_hash(&"a".repeat(64))
+            .await
+            .unwrap()
+            .unwrap()
+            .is_active());
+
+        reset_tables(&pool, &["oauth_clients"]).await;
+    }
+}
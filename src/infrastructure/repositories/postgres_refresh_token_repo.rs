@@ -0,0 +1,202 @@
+use std::error::Error;
+
+use crate::application::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::domain::oauth::{NewRefreshToken, RefreshToken};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresRefreshTokenRepository {
+    pool: PgPool,
+}
+
+impl PostgresRefreshTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for PostgresRefreshTokenRepository {
+    async fn create(&self, token: &NewRefreshToken) -> Result<PgQueryResult, Box<dyn Error>> {
+        let auth_time = DateTime::from_timestamp(token.auth.auth_time, 0).unwrap_or_else(Utc::now);
+        let mut tx = self.pool.begin().await?;
+
+        // Revoked tokens are kept until they expire so that a rotated token
+        // presented again is still recognised.
+        sqlx::query!(
+            "
+            DELETE FROM oauth_refresh_tokens
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO oauth_refresh_tokens (token_hash, family_id, client_id, user_id, scope,
+                auth_time, amr, acr, jkt, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ",
+            token.token_hash,
+            token.family_id,
+            token.client_id,
+            token.user_id,
+            token.scope,
+            auth_time,
+            &token.auth.amr,
+            token.auth.acr,
+            token.jkt,
+            token.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            RefreshToken,
+            "
+            SELECT id, token_hash, family_id, client_id, user_id, scope, auth_time, amr, acr,
+                jkt, expires_at, revoked_at, created_at
+            FROM oauth_refresh_tokens
+            WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn revoke(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND revoked_at IS NULL
+            ",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE family_id = $1 AND revoked_at IS NULL
+            ",
+            family_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn revoke_for_client(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE oauth_refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = $1 AND client_id = $2 AND revoked_at IS NULL
+            ",
+            user_id,
+            client_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::auth_context::{AuthContext, AMR_PASSWORD};
+    use crate::infrastructure::repositories::test_support::{
+        create_client, create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    #[tokio::test]
+    async fn revoking_family_revokes_rotated_tokens() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+        let repo = PostgresRefreshTokenRepository::new(pool.clone());
+        let user_id = create_user(&pool, "refreshuser").await;
+        let client_id = create_client(&pool).await;
+        let family_id = Uuid::new_v4();
+
+        for tag in ["a", "b"] {
+            repo.create(&NewRefreshToken {
+                token_hash: tag.repeat(64),
+                family_id,
+                client_id,
+                user_id,
+                scope: "openid".to_string(),
+                auth: AuthContext::new(&[AMR_PASSWORD]),
+                jkt: None,
+                expires_at: Utc::now() + chrono::Duration::seconds(600),
+            })
+            .await
+            .unwrap();
+        }
+
+        let first = repo
+            .find_by_token_hash(&"a".repeat(64))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(first.is_active());
+        assert_eq!(vec![AMR_PASSWORD.to_string()], first.amr);
+        assert_eq!(1, repo.revoke(first.id).await.unwrap().rows_affected());
+        assert_eq!(0, repo.revoke(first.id).await.unwrap().rows_affected());
+
+        assert_eq!(
+            1,
+            repo.revoke_family(family_id).await.unwrap().rows_affected()
+        );
+        let second = repo
+            .find_by_token_hash(&"b".repeat(64))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!second.is_active());
+
+        reset_tables(&pool, &["users", "oauth_clients"]).await;
+    }
+}
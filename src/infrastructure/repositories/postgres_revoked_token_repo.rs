@@ -0,0 +1,120 @@
+use std::error::Error;
+
+use crate::application::repositories::revoked_token_repository::RevokedTokenRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresRevokedTokenRepository {
+    pool: PgPool,
+}
+
+impl PostgresRevokedTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RevokedTokenRepository for PostgresRevokedTokenRepository {
+    async fn revoke(
+        &self,
+        jti: Uuid,
+        user_id: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM revoked_tokens
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            ",
+            jti,
+            user_id,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn is_revoked(&self, jti: Uuid) -> Result<bool, Box<dyn Error>> {
+        let result = sqlx::query!(
+            "
+            SELECT EXISTS (
+                SELECT 1 FROM revoked_tokens
+                WHERE jti = $1 AND expires_at > CURRENT_TIMESTAMP
+            ) AS \"revoked!\"
+            ",
+            jti
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result.revoked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::test_support::setup_database;
+    use tokio;
+
+    #[tokio::test]
+    async fn revoke_and_expire_tokens() {
+        let pool = setup_database().await;
+        let repo = PostgresRevokedTokenRepository::new(pool.clone());
+        let (live, expired) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(!repo.is_revoked(live).await.unwrap());
+        let result = repo
+            .revoke(live, None, Utc::now() + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(1, result.rows_affected());
+        assert!(repo.is_revoked(live).await.unwrap());
+
+        let result = repo
+            .revoke(live, None, Utc::now() + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(0, result.rows_affected());
+
+        repo.revoke(expired, None, Utc::now() - chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(!repo.is_revoked(expired).await.unwrap());
+
+        repo.revoke(
+            Uuid::new_v4(),
+            None,
+            Utc::now() + chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap();
+        let remaining: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM revoked_tokens WHERE jti = $1")
+                .bind(expired)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(0, remaining);
+    }
+}
@@ -0,0 +1,277 @@
+use std::error::Error;
+
+use crate::application::repositories::session_repository::SessionRepository;
+use crate::domain::session::{NewSession, Session};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresSessionRepository {
+    pool: PgPool,
+}
+
+impl PostgresSessionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PostgresSessionRepository {
+    async fn create(&self, session: &NewSession) -> Result<Session, Box<dyn Error>> {
+        let auth_time =
+            DateTime::from_timestamp(session.auth.auth_time, 0).unwrap_or_else(Utc::now);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM sessions
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query_as!(
+            Session,
+            "
+            INSERT INTO sessions (user_id, token_hash, csrf_token, roles, auth_time, amr, acr,
+                user_agent, browser, os, ip_address, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id, user_id, token_hash, csrf_token, roles, auth_time, amr, acr, user_agent,
+                browser, os, ip_address, created_at, last_seen_at, expires_at
+            ",
+            session.user_id,
+            session.token_hash,
+            session.csrf_token,
+            &session.roles,
+            auth_time,
+            &session.auth.amr,
+            session.auth.acr,
+            session.device.user_agent,
+            session.device.browser,
+            session.device.os,
+            session.device.ip_address,
+            session.expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<Session>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            Session,
+            "
+            SELECT id, user_id, token_hash, csrf_token, roles, auth_time, amr, acr, user_agent,
+                browser, os, ip_address, created_at, last_seen_at, expires_at
+            FROM sessions
+            WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> Result<Vec<Session>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            Session,
+            "
+            SELECT id, user_id, token_hash, csrf_token, roles, auth_time, amr, acr, user_agent,
+                browser, os, ip_address, created_at, last_seen_at, expires_at
+            FROM sessions
+            WHERE user_id = $1 AND expires_at > CURRENT_TIMESTAMP
+            ORDER BY last_seen_at DESC
+            ",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn touch(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE sessions
+            SET last_seen_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            ",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM sessions
+            WHERE id = $1
+            ",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete_for_user(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM sessions
+            WHERE id = $1 AND user_id = $2
+            ",
+            id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete_all_for_user(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM sessions
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::auth_context::AuthContext;
+    use crate::domain::auth_context::{AMR_OTP, AMR_PASSWORD};
+    use crate::domain::session::SessionDevice;
+    use crate::infrastructure::repositories::test_support::{
+        create_user, reset_tables, setup_database,
+    };
+    use tokio;
+
+    fn new_session(user_id: Uuid, token_hash: &str) -> NewSession {
+        NewSession {
+            user_id,
+            token_hash: token_hash.to_string(),
+            csrf_token: "csrf".to_string(),
+            roles: Vec::new(),
+            auth: AuthContext::new(&[AMR_PASSWORD]),
+            device: SessionDevice::default(),
+            expires_at: Utc::now() + chrono::Duration::hours(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_find_touch_and_delete() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresSessionRepository::new(pool.clone());
+        let user_id = create_user(&pool, "sessionuser").await;
+        let new = NewSession {
+            roles: vec!["admin".to_string()],
+            auth: AuthContext::new(&[AMR_PASSWORD, AMR_OTP]),
+            device: SessionDevice {
+                browser: Some("Firefox 120.0".to_string()),
+                ip_address: Some("127.0.0.1".to_string()),
+                ..Default::default()
+            },
+            ..new_session(user_id, "token-hash")
+        };
+
+        let session = repo.create(&new).await.unwrap();
+        assert_eq!(user_id, session.user_id);
+        assert_eq!(new.auth, session.auth_context());
+        assert_eq!(new.device.browser, session.browser);
+
+        let found = repo
+            .find_by_token_hash("token-hash")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.id, found.id);
+        assert_eq!(vec!["admin".to_string()], found.roles);
+        assert!(repo.find_by_token_hash("other").await.unwrap().is_none());
+
+        assert_eq!(1, repo.touch(session.id).await.unwrap().rows_affected());
+        assert_eq!(1, repo.delete(session.id).await.unwrap().rows_affected());
+        assert!(repo
+            .find_by_token_hash("token-hash")
+            .await
+            .unwrap()
+            .is_none());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn list_and_revoke_user_sessions() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresSessionRepository::new(pool.clone());
+        let user_id = create_user(&pool, "sessionuser").await;
+        let mut ids = Vec::new();
+        for token_hash in ["first", "second", "third"] {
+            let session = repo
+                .create(&new_session(user_id, token_hash))
+                .await
+                .unwrap();
+            ids.push(session.id);
+        }
+        assert_eq!(3, repo.find_by_user(user_id).await.unwrap().len());
+
+        let result = repo.delete_for_user(Uuid::new_v4(), ids[0]).await.unwrap();
+        assert_eq!(0, result.rows_affected());
+        let result = repo.delete_for_user(user_id, ids[0]).await.unwrap();
+        assert_eq!(1, result.rows_affected());
+        assert_eq!(2, repo.find_by_user(user_id).await.unwrap().len());
+
+        let result = repo.delete_all_for_user(user_id).await.unwrap();
+        assert_eq!(2, result.rows_affected());
+        assert!(repo.find_by_user(user_id).await.unwrap().is_empty());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+}
@@ -1,7 +1,7 @@
 use std::error::Error;
 
 use crate::application::repositories::user_repository::UserRepository;
-use crate::domain::user::{User, UserId};
+use crate::domain::user::{User, UserId, UserWithPassword};
 use crate::dto::user_dto::{CreateRequest, UpdateRequest};
 use async_trait::async_trait;
 use sqlx::postgres::PgQueryResult;
@@ -24,7 +24,9 @@ impl UserRepository for PostgresUserRepository {
         let result = sqlx::query_as!(
             User,
             "
-            SELECT id, username, email, first_name, last_name, date_of_birth FROM users
+            SELECT id, username, email, first_name, last_name, date_of_birth, phone_number,
+                phone_verified_at
+            FROM users
             WHERE id = $1
             ",
             user_id
@@ -39,11 +41,78 @@ impl UserRepository for PostgresUserRepository {
         }
     }
 
+    async fn find_by_id_with_password(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<UserWithPassword>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            UserWithPassword,
+            "
+            SELECT id, username, email, password_hash, first_name, last_name, date_of_birth,
+                phone_number, phone_verified_at, password_reset_required, is_admin
+            FROM users
+            WHERE id = $1
+            ",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        match result {
+            Ok(user) => Ok(Some(user)),
+            Err(sqlx::Error::RowNotFound) => Ok(None),
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+    async fn find_by_username_with_password(
+        &self,
+        username: &str,
+    ) -> Result<Option<UserWithPassword>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            UserWithPassword,
+            "
+            SELECT id, username, email, password_hash, first_name, last_name, date_of_birth,
+                phone_number, phone_verified_at, password_reset_required, is_admin
+            FROM users
+            WHERE username = $1
+            ",
+            username
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        match result {
+            Ok(user) => Ok(Some(user)),
+            Err(sqlx::Error::RowNotFound) => Ok(None),
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            User,
+            "
+            SELECT id, username, email, first_name, last_name, date_of_birth, phone_number,
+                phone_verified_at
+            FROM users
+            WHERE LOWER(email) = LOWER($1)
+            ",
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
     async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>> {
         let results = sqlx::query_as!(
             User,
             "
-            SELECT id, username, email, first_name, last_name, date_of_birth FROM users
+            SELECT id, username, email, first_name, last_name, date_of_birth, phone_number,
+                phone_verified_at
+            FROM users
             ",
         )
         .fetch_all(&self.pool)
@@ -94,7 +163,8 @@ impl UserRepository for PostgresUserRepository {
                 last_name = $4,
                 date_of_birth = $5
             WHERE id = $6
-            RETURNING id, username, email, first_name, last_name, date_of_birth
+            RETURNING id, username, email, first_name, last_name, date_of_birth, phone_number,
+                phone_verified_at
             ",
             data.username,
             data.email,
@@ -115,6 +185,103 @@ impl UserRepository for PostgresUserRepository {
         }
     }
 
+    async fn update_password_hash(
+        &self,
+        user_id: Uuid,
+        password_hash: &str,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE users
+            SET password_hash = $1,
+                password_reset_required = FALSE,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $2
+            ",
+            password_hash,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn update_phone_number(
+        &self,
+        user_id: Uuid,
+        phone_number: Option<&str>,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE users
+            SET phone_number = $1,
+                phone_verified_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $2
+            ",
+            phone_number,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn mark_phone_verified(
+        &self,
+        user_id: Uuid,
+        phone_number: &str,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE users
+            SET phone_verified_at = CURRENT_TIMESTAMP,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND phone_number = $2
+            ",
+            user_id,
+            phone_number
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn flag_password_reset(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE users
+            SET password_reset_required = TRUE,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            ",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
     async fn delete(&self, user_id: Uuid) -> Result<PgQueryResult, Box<dyn Error>> {
         let mut tx = self.pool.begin().await?;
 
@@ -137,35 +304,14 @@ impl UserRepository for PostgresUserRepository {
 
 #[cfg(test)]
 mod tests {
-    use std::env;
-
     use super::*;
-    use crate::config::DatabaseConfig;
-    use crate::infrastructure::postgres_database::PostgresDatabase;
+    use crate::infrastructure::repositories::test_support::{reset_tables, setup_database};
     use tokio;
 
-    async fn setup_database() -> PgPool {
-        let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
-        let config = DatabaseConfig::new(database_url);
-        let db = PostgresDatabase::new(config).await;
-
-        let migrator = sqlx::migrate!("./migrations");
-        migrator.run(&db.pool).await.unwrap();
-
-        db.pool
-    }
-
-    async fn reset_test_db(pool: &PgPool) {
-        sqlx::query("DELETE FROM users")
-            .execute(pool)
-            .await
-            .unwrap();
-    }
-
     #[tokio::test]
     async fn create_new_user() {
         let pool = setup_database().await;
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
         let repo = PostgresUserRepository::new(pool.clone());
 
         let new_user = CreateRequest {
@@ -180,13 +326,13 @@ mod tests {
         let result = repo.create(&new_user).await;
         assert!(result.is_ok());
 
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
     }
 
     #[tokio::test]
     async fn find_all() {
         let pool = setup_database().await;
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
 
         let repo = PostgresUserRepository::new(pool.clone());
 
@@ -207,13 +353,13 @@ mod tests {
         let users = repo.find_all().await.unwrap();
         assert_eq!(users.len(), 1);
 
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
     }
 
     #[tokio::test]
     async fn find_one() {
         let pool = setup_database().await;
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
         let repo = PostgresUserRepository::new(pool.clone());
 
         let new_user = CreateRequest {
@@ -230,13 +376,52 @@ mod tests {
         let result = repo.find_by_id(created_user_id.id).await;
         assert!(result.is_ok());
 
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn find_by_email() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresUserRepository::new(pool.clone());
+
+        let new_user = CreateRequest {
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            password: "hashed_password".to_string(),
+            first_name: None,
+            last_name: None,
+            date_of_birth: None,
+        };
+
+        let created_user_id = repo.create(&new_user).await.unwrap();
+
+        let user = repo
+            .find_by_email("TEST@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(created_user_id.id, user.id);
+        assert!(repo
+            .find_by_email("missing@example.com")
+            .await
+            .unwrap()
+            .is_none());
+
+        let same_email = CreateRequest {
+            username: "otheruser".to_string(),
+            email: "Test@Example.com".to_string(),
+            ..new_user
+        };
+        assert!(repo.create(&same_email).await.is_err());
+
+        reset_tables(&pool, &["users"]).await;
     }
 
     #[tokio::test]
     async fn update() {
         let pool = setup_database().await;
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
         let repo = PostgresUserRepository::new(pool.clone());
 
         let new_user = CreateRequest {
@@ -265,13 +450,132 @@ mod tests {
         assert_eq!(update_user_request.first_name, updated_user.first_name);
         assert_eq!(update_user_request.last_name, updated_user.last_name);
 
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn update_and_verify_phone_number() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresUserRepository::new(pool.clone());
+
+        let new_user = CreateRequest {
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            password: "hashed_password".to_string(),
+            first_name: None,
+            last_name: None,
+            date_of_birth: None,
+        };
+
+        let user_id = repo.create(&new_user).await.unwrap().id;
+
+        let result = repo
+            .update_phone_number(user_id, Some("+14155550123"))
+            .await
+            .unwrap();
+        assert_eq!(1, result.rows_affected());
+        let result = repo
+            .mark_phone_verified(user_id, "+14155550199")
+            .await
+            .unwrap();
+        assert_eq!(0, result.rows_affected());
+        let result = repo
+            .mark_phone_verified(user_id, "+14155550123")
+            .await
+            .unwrap();
+        assert_eq!(1, result.rows_affected());
+
+        let user = repo.find_by_id(user_id).await.unwrap().unwrap();
+        assert_eq!(Some("+14155550123"), user.verified_phone_number());
+
+        repo.update_phone_number(user_id, Some("+14155550199"))
+            .await
+            .unwrap();
+        let user = repo.find_by_id(user_id).await.unwrap().unwrap();
+        assert_eq!(Some("+14155550199"), user.phone_number.as_deref());
+        assert_eq!(None, user.verified_phone_number());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn update_password_hash() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresUserRepository::new(pool.clone());
+
+        let new_user = CreateRequest {
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            password: "$2b$04$legacy_hashed_password".to_string(),
+            first_name: None,
+            last_name: None,
+            date_of_birth: None,
+        };
+
+        let created_user_id = repo.create(&new_user).await.unwrap();
+
+        let result = repo
+            .update_password_hash(created_user_id.id, "$argon2id$new_hashed_password")
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(1, result.unwrap().rows_affected());
+
+        let user = repo
+            .find_by_username_with_password(&new_user.username)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(created_user_id.id, user.id);
+        assert_eq!("$argon2id$new_hashed_password", user.password_hash);
+        assert!(!user.password_reset_required);
+
+        let user = repo
+            .find_by_id_with_password(created_user_id.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(new_user.username, user.username);
+        assert_eq!("$argon2id$new_hashed_password", user.password_hash);
+
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn flag_password_reset() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresUserRepository::new(pool.clone());
+
+        let new_user = CreateRequest {
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            password: "corrupted_hash".to_string(),
+            first_name: None,
+            last_name: None,
+            date_of_birth: None,
+        };
+
+        let created_user_id = repo.create(&new_user).await.unwrap();
+
+        let result = repo.flag_password_reset(created_user_id.id).await;
+        assert!(result.is_ok());
+
+        let user = repo
+            .find_by_username_with_password(&new_user.username)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.password_reset_required);
+
+        reset_tables(&pool, &["users"]).await;
     }
 
     #[tokio::test]
     async fn delete() {
         let pool = setup_database().await;
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
         let repo = PostgresUserRepository::new(pool.clone());
 
         let new_user = CreateRequest {
@@ -291,6 +595,6 @@ mod tests {
         let affected_rows = result.unwrap().rows_affected();
         assert_eq!(1, affected_rows);
 
-        reset_test_db(&pool).await;
+        reset_tables(&pool, &["users"]).await;
     }
 }
@@ -0,0 +1,327 @@
+use std::error::Error;
+
+use crate::application::repositories::webauthn_repository::WebauthnRepository;
+use crate::domain::webauthn::{WebauthnChallenge, WebauthnCredential};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgQueryResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresWebauthnRepository {
+    pool: PgPool,
+}
+
+impl PostgresWebauthnRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebauthnRepository for PostgresWebauthnRepository {
+    async fn create_challenge(
+        &self,
+        user_id: Option<Uuid>,
+        ceremony: &str,
+        challenge: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+            DELETE FROM webauthn_challenges
+            WHERE expires_at < CURRENT_TIMESTAMP
+            "
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+            INSERT INTO webauthn_challenges (user_id, ceremony, challenge, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            ",
+            user_id,
+            ceremony,
+            challenge,
+            expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.id)
+    }
+
+    async fn take_challenge(
+        &self,
+        challenge_id: Uuid,
+        ceremony: &str,
+    ) -> Result<Option<WebauthnChallenge>, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            WebauthnChallenge,
+            "
+            DELETE FROM webauthn_challenges
+            WHERE id = $1 AND ceremony = $2
+            RETURNING id, user_id, ceremony, challenge, expires_at
+            ",
+            challenge_id,
+            ceremony
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn find_credentials(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<WebauthnCredential>, Box<dyn Error>> {
+        let results = sqlx::query_as!(
+            WebauthnCredential,
+            "
+            SELECT id, user_id, credential_id, public_key, sign_count, name, created_at, last_used_at
+            FROM webauthn_credentials
+            WHERE user_id = $1
+            ORDER BY created_at
+            ",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_credential(
+        &self,
+        credential_id: &[u8],
+    ) -> Result<Option<WebauthnCredential>, Box<dyn Error>> {
+        let result = sqlx::query_as!(
+            WebauthnCredential,
+            "
+            SELECT id, user_id, credential_id, public_key, sign_count, name, created_at, last_used_at
+            FROM webauthn_credentials
+            WHERE credential_id = $1
+            ",
+            credential_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn create_credential(
+        &self,
+        user_id: Uuid,
+        credential_id: &[u8],
+        public_key: &[u8],
+        sign_count: i64,
+        name: &str,
+    ) -> Result<WebauthnCredential, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            WebauthnCredential,
+            "
+            INSERT INTO webauthn_credentials (user_id, credential_id, public_key, sign_count, name)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, credential_id, public_key, sign_count, name, created_at, last_used_at
+            ",
+            user_id,
+            credential_id,
+            public_key,
+            sign_count,
+            name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn update_sign_count(
+        &self,
+        id: Uuid,
+        sign_count: i64,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            UPDATE webauthn_credentials
+            SET sign_count = $2,
+                last_used_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            ",
+            id,
+            sign_count
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn rename_credential(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        name: &str,
+    ) -> Result<Option<WebauthnCredential>, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query_as!(
+            WebauthnCredential,
+            "
+            UPDATE webauthn_credentials
+            SET name = $3
+            WHERE id = $2 AND user_id = $1
+            RETURNING id, user_id, credential_id, public_key, sign_count, name, created_at, last_used_at
+            ",
+            user_id,
+            id,
+            name
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn delete_credential(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<PgQueryResult, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+            DELETE FROM webauthn_credentials
+            WHERE id = $2 AND user_id = $1
+            ",
+            user_id,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::webauthn::{AUTHENTICATION_CEREMONY, REGISTRATION_CEREMONY};
+    use crate::infrastructure::repositories::test_support::{
+        create_user, reset_tables, setup_database,
+    };
+    use chrono::Duration;
+    use tokio;
+
+    #[tokio::test]
+    async fn challenge_is_taken_once() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresWebauthnRepository::new(pool.clone());
+        let user_id = create_user(&pool, "passkeyuser").await;
+
+        let expires_at = Utc::now() + Duration::seconds(60);
+        let challenge_id = repo
+            .create_challenge(
+                Some(user_id),
+                REGISTRATION_CEREMONY,
+                b"challenge",
+                expires_at,
+            )
+            .await
+            .unwrap();
+
+        assert!(repo
+            .take_challenge(challenge_id, AUTHENTICATION_CEREMONY)
+            .await
+            .unwrap()
+            .is_none());
+        let challenge = repo
+            .take_challenge(challenge_id, REGISTRATION_CEREMONY)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(user_id), challenge.user_id);
+        assert_eq!(b"challenge".to_vec(), challenge.challenge);
+        assert!(repo
+            .take_challenge(challenge_id, REGISTRATION_CEREMONY)
+            .await
+            .unwrap()
+            .is_none());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+
+    #[tokio::test]
+    async fn credential_lifecycle() {
+        let pool = setup_database().await;
+        reset_tables(&pool, &["users"]).await;
+        let repo = PostgresWebauthnRepository::new(pool.clone());
+        let user_id = create_user(&pool, "passkeyuser").await;
+
+        let credential = repo
+            .create_credential(user_id, b"credential-1", b"public-key", 0, "Laptop")
+            .await
+            .unwrap();
+        assert_eq!("Laptop", credential.name);
+        assert_eq!(
+            credential.id,
+            repo.find_credential(b"credential-1")
+                .await
+                .unwrap()
+                .unwrap()
+                .id
+        );
+
+        repo.update_sign_count(credential.id, 5).await.unwrap();
+        let renamed = repo
+            .rename_credential(user_id, credential.id, "Phone")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("Phone", renamed.name);
+        assert_eq!(5, renamed.sign_count);
+        assert!(renamed.last_used_at.is_some());
+
+        assert!(repo
+            .rename_credential(Uuid::new_v4(), credential.id, "Stolen")
+            .await
+            .unwrap()
+            .is_none());
+
+        let result = repo
+            .delete_credential(user_id, credential.id)
+            .await
+            .unwrap();
+        assert_eq!(1, result.rows_affected());
+        assert!(repo.find_credentials(user_id).await.unwrap().is_empty());
+
+        reset_tables(&pool, &["users"]).await;
+    }
+}
@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use crate::application::repositories::rate_limit_store::RateLimitStore;
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision};
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, Script};
+
+/// Refills and takes from a bucket atomically on the server so every
+/// instance shares the same limits.
+const TAKE_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local rate = capacity / period
+local time = redis.call('TIME')
+local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or capacity
+local updated_at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + (now - updated_at) * rate)
+
+local allowed = 0
+if tokens >= 1 then
+    allowed = 1
+    tokens = tokens - 1
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', tostring(now))
+redis.call('EXPIRE', KEYS[1], period)
+
+local retry_after = 0
+if allowed == 0 then
+    retry_after = math.ceil((1 - tokens) / rate)
+end
+return { allowed, math.floor(tokens), math.ceil((capacity - tokens) / rate), retry_after }
+"#;
+
+/// Token buckets stored on any server speaking the Redis protocol.
+pub struct RedisRateLimitStore {
+    connection: ConnectionManager,
+    script: Script,
+}
+
+impl RedisRateLimitStore {
+    pub async fn new(url: &str) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(url)?;
+        Ok(Self {
+            connection: ConnectionManager::new(client).await?,
+            script: Script::new(TAKE_SCRIPT),
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn take(&self, key: &str, limit: RateLimit) -> Result<RateLimitDecision, Box<dyn Error>> {
+        let mut connection = self.connection.clone();
+        let (allowed, remaining, reset, retry_after): (u8, u32, u64, u64) = self
+            .script
+            .key(format!("ratelimit:{}", key))
+            .arg(limit.capacity)
+            .arg(limit.period.max(1))
+            .invoke_async(&mut connection)
+            .await?;
+
+        Ok(RateLimitDecision {
+            allowed: allowed == 1,
+            limit,
+            remaining,
+            reset,
+            retry_after,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+//! Fixtures for the tests that run against the database named by
+//! `TEST_DATABASE_URL`.
+
+use std::env;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::application::repositories::user_repository::UserRepository;
+use crate::config::DatabaseConfig;
+use crate::dto::user_dto::CreateRequest;
+use crate::infrastructure::postgres_database::PostgresDatabase;
+use crate::infrastructure::repositories::postgres_user_repo::PostgresUserRepository;
+
+/// Connects to the test database and runs the migrations.
+pub async fn setup_database() -> PgPool {
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    let config = DatabaseConfig::new(database_url);
+    let db = PostgresDatabase::new(config).await;
+
+    let migrator = sqlx::migrate!("./migrations");
+    migrator.run(&db.pool).await.unwrap();
+
+    db.pool
+}
+
+/// Deletes every row of `tables`, in the given order.
+pub async fn reset_tables(pool: &PgPool, tables: &[&str]) {
+    for table in tables {
+        let query = format!("DELETE FROM {}", table);
+        sqlx::query(&query).execute(pool).await.unwrap();
+    }
+}
+
+/// Creates a user called `username` with an address at example.com.
+pub async fn create_user(pool: &PgPool, username: &str) -> Uuid {
+    let new_user = CreateRequest {
+        username: username.to_string(),
+        email: format!("{}@example.com", username),
+        password: "hashed_password".to_string(),
+        first_name: None,
+        last_name: None,
+        date_of_birth: None,
+    };
+
+    PostgresUserRepository::new(pool.clone())
+        .create(&new_user)
+        .await
+        .unwrap()
+        .id
+}
+
+/// Creates an OAuth client that redirects to `https://app.example.com/cb`.
+pub async fn create_client(pool: &PgPool) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO oauth_clients (name, redirect_uris) VALUES ('app', '{https://app.example.com/cb}') RETURNING id",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
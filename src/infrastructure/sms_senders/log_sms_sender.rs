@@ -0,0 +1,44 @@
+use std::{error::Error, path::PathBuf};
+
+use async_trait::async_trait;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use crate::application::services::sms_sender::{SmsMessage, SmsSender};
+
+/// Keeps messages local instead of delivering them. With a file configured
+/// each message is appended as a JSON line, otherwise it goes to the log.
+#[derive(Default)]
+pub struct LogSmsSender {
+    file: Option<PathBuf>,
+}
+
+impl LogSmsSender {
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self { file }
+    }
+}
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, message: SmsMessage) -> Result<(), Box<dyn Error>> {
+        let Some(path) = &self.file else {
+            tracing::info!(
+                to = message.to,
+                body = message.body,
+                "SMS not sent, log sender in use"
+            );
+            return Ok(());
+        };
+
+        let mut line = serde_json::to_vec(&message)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(&line).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod log_sms_sender;
+pub mod webhook_sms_sender;
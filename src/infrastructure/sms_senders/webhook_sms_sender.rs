@@ -0,0 +1,132 @@
+use std::{error::Error, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    application::services::sms_sender::{SmsMessage, SmsSender},
+    config::SmsConfig,
+};
+
+/// Posts each message as `{"to": ..., "body": ...}` to an HTTP gateway. Any
+/// status other than 2xx counts as a failed delivery.
+pub struct WebhookSmsSender {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl WebhookSmsSender {
+    pub fn new(config: &SmsConfig) -> Result<Self, Box<dyn Error>> {
+        if config.url.is_empty() {
+            return Err("SMS webhook url is not set".into());
+        }
+
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.timeout))
+                .build()?,
+            url: config.url.clone(),
+            token: Some(config.token.clone()).filter(|token| !token.is_empty()),
+        })
+    }
+}
+
+#[async_trait]
+impl SmsSender for WebhookSmsSender {
+    async fn send(&self, message: SmsMessage) -> Result<(), Box<dyn Error>> {
+        let mut request = self.client.post(&self.url).json(&message);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request.send().await?.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+
+    use super::*;
+
+    type Received = Arc<Mutex<Vec<(Option<String>, serde_json::Value)>>>;
+
+    /// Starts a gateway stub that records requests and answers with `status`.
+    fn start_stub(status: u16) -> (String, Received) {
+        let received: Received = Arc::default();
+        let data = web::Data::new(Arc::clone(&received));
+        let server = HttpServer::new(move || {
+            App::new().app_data(data.clone()).route(
+                "/sms",
+                web::post().to(
+                    move |req: HttpRequest,
+                          body: web::Json<serde_json::Value>,
+                          received: web::Data<Received>| async move {
+                        let authorization = req
+                            .headers()
+                            .get("authorization")
+                            .and_then(|value| value.to_str().ok())
+                            .map(str::to_string);
+                        received
+                            .lock()
+                            .unwrap()
+                            .push((authorization, body.into_inner()));
+                        HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap())
+                            .finish()
+                    },
+                ),
+            )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let url = format!("http://{}/sms", server.addrs()[0]);
+        actix_web::rt::spawn(server.run());
+
+        (url, received)
+    }
+
+    fn sender(url: String, token: &str) -> WebhookSmsSender {
+        WebhookSmsSender::new(&SmsConfig {
+            url,
+            token: token.to_string(),
+            ..SmsConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_posts_message_to_gateway() {
+        let (url, received) = start_stub(200);
+
+        sender(url, "gateway-token")
+            .send(SmsMessage {
+                to: "+14155550123".to_string(),
+                body: "Your code is 123456".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(1, received.len());
+        assert_eq!(Some("Bearer gateway-token"), received[0].0.as_deref());
+        assert_eq!("+14155550123", received[0].1["to"]);
+        assert_eq!("Your code is 123456", received[0].1["body"]);
+    }
+
+    #[actix_web::test]
+    async fn test_gateway_error_fails_delivery() {
+        let (url, _) = start_stub(502);
+
+        let result = sender(url, "")
+            .send(SmsMessage {
+                to: "+14155550123".to_string(),
+                body: "Your code is 123456".to_string(),
+            })
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -1,11 +1,17 @@
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use rust_auth_service::{
-    api::api_v1_cfg,
+    api::{
+        api_v1_cfg,
+        middleware::rate_limit::{RateLimitMiddleware, RateLimiter},
+        AppState,
+    },
     config::get_config_from_env,
     infrastructure::postgres_database::PostgresDatabase,
     util::{logging::custom_status_info, tracing::setup_tracing},
 };
-use std::{error::Error, sync::Arc};
+use std::{error::Error, sync::Arc, time::Duration};
+
+const LOGIN_EVENT_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -13,11 +19,32 @@ async fn main() -> Result<(), Box<dyn Error>> {
     setup_tracing(&config.loki.get_url(), config.env)?;
 
     let db = PostgresDatabase::new(config.database.clone()).await;
+    let state = AppState::new(Arc::clone(&config), db.pool)?;
+    let rate_limiter = Arc::new(
+        RateLimiter::from_config(
+            &config.ratelimit,
+            Arc::clone(&state.tokens),
+            Arc::clone(&state.trusted_proxies),
+        )
+        .await?,
+    );
+
+    let login_events = Arc::clone(&state.login_events);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(LOGIN_EVENT_PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = login_events.prune().await {
+                tracing::error!(error = %err, "failed to prune login events");
+            }
+        }
+    });
 
     let app_data_config = web::Data::new(Arc::clone(&config));
 
     let _ = HttpServer::new(move || {
         App::new()
+            .wrap(RateLimitMiddleware::new(Arc::clone(&rate_limiter)))
             .wrap(
                 Logger::new(
                     "%{STATUS_INFO}xo %a \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T",
@@ -25,7 +52,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .custom_response_replace("STATUS_INFO", |res| custom_status_info(res).to_string()),
             )
             .app_data(app_data_config.clone())
-            .service(web::scope("/api/v1").configure(|cfg| api_v1_cfg(cfg, db.pool.clone())))
+            .service(web::scope("/api/v1").configure(|cfg| api_v1_cfg(cfg, state.clone())))
     })
     .bind((config.host.clone(), config.port))?
     .run()
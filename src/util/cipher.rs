@@ -0,0 +1,92 @@
+use std::{error::Error, fmt};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug)]
+pub struct CipherError;
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to encrypt or decrypt secret")
+    }
+}
+
+impl Error for CipherError {}
+
+/// Encrypts small secrets such as TOTP keys before they are stored.
+///
+/// The AES-256-GCM key is the SHA-256 digest of the configured secret. The
+/// associated data binds a ciphertext to its owner so rows cannot be swapped.
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    pub fn new(secret: &str) -> Self {
+        let key = Sha256::digest(secret.as_bytes());
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    /// Returns the ciphertext and the nonce it was encrypted with.
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CipherError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| CipherError)?;
+        Ok((ciphertext, nonce.to_vec()))
+    }
+
+    pub fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CipherError> {
+        if nonce.len() != 12 {
+            return Err(CipherError);
+        }
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| CipherError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let cipher = SecretCipher::new("SuperDuperMfaKey");
+        let (ciphertext, nonce) = cipher.encrypt(b"totp secret", b"user-1").unwrap();
+
+        assert_ne!(b"totp secret".to_vec(), ciphertext);
+        assert_eq!(
+            b"totp secret".to_vec(),
+            cipher.decrypt(&ciphertext, &nonce, b"user-1").unwrap()
+        );
+        assert!(cipher.decrypt(&ciphertext, &nonce, b"user-2").is_err());
+        assert!(SecretCipher::new("AnotherKey")
+            .decrypt(&ciphertext, &nonce, b"user-1")
+            .is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use std::net::{IpAddr, SocketAddr};
+
+use actix_web::http::header::HeaderMap;
+
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// One trusted address or CIDR range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Network {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl Network {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid trusted proxy: {}", spec);
+        let (address, prefix) = match spec.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (spec, None),
+        };
+        let address = address.parse::<IpAddr>().map_err(|_| invalid())?;
+        let max = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| invalid())?,
+            None => max,
+        };
+        if prefix > max {
+            return Err(invalid());
+        }
+
+        Ok(Self { address, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.address, canonical(ip)) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// IPv4-mapped IPv6 addresses, as dual-stack sockets report IPv4 peers,
+/// compare as IPv4.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    }
+}
+
+/// Reverse proxies whose `X-Forwarded-For` is believed. Requests from any
+/// other peer are attributed to the peer itself, so clients cannot pick
+/// the address that rate limits and risk scoring see.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<Network>,
+}
+
+impl TrustedProxies {
+    /// Parses a `,` separated list of addresses and CIDR ranges.
+    pub fn parse(specs: &str) -> Result<Self, String> {
+        let networks = specs
+            .split(',')
+            .map(str::trim)
+            .filter(|spec| !spec.is_empty())
+            .map(Network::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { networks })
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    /// Address of the client behind `peer`. When the peer is a trusted
+    /// proxy, `X-Forwarded-For` is walked from the right and the first
+    /// address that is not itself a trusted proxy wins.
+    pub fn client_ip(&self, peer: Option<SocketAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let peer = canonical(peer?.ip());
+        if !self.is_trusted(peer) {
+            return Some(peer);
+        }
+
+        let mut forwarded = headers
+            .get_all(FORWARDED_FOR_HEADER)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        forwarded.reverse();
+
+        let mut client = peer;
+        for hop in forwarded {
+            match hop.parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = canonical(ip);
+                    if !self.is_trusted(client) {
+                        break;
+                    }
+                }
+                // Whatever sits left of an unparsable hop is not vouched for.
+                Err(_) => break,
+            }
+        }
+
+        Some(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn headers(forwarded_for: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(FORWARDED_FOR_HEADER),
+            HeaderValue::from_str(forwarded_for).unwrap(),
+        );
+        headers
+    }
+
+    fn peer(address: &str) -> Option<SocketAddr> {
+        Some(SocketAddr::new(address.parse().unwrap(), 443))
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_forwarded_for() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8").unwrap();
+
+        let ip = proxies.client_ip(peer("203.0.113.7"), &headers("198.51.100.1"));
+
+        assert_eq!(ip, Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(
+            TrustedProxies::default().client_ip(peer("10.0.0.1"), &headers("198.51.100.1")),
+            Some("10.0.0.1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_trusted_peer_uses_rightmost_untrusted_hop() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8, 2001:db8::1").unwrap();
+
+        // The client prepended a spoofed hop; only the one added by the
+        // proxy counts.
+        let ip = proxies.client_ip(
+            peer("10.1.2.3"),
+            &headers("1.2.3.4, 198.51.100.1, 10.0.0.2"),
+        );
+        assert_eq!(ip, Some("198.51.100.1".parse().unwrap()));
+
+        let ip = proxies.client_ip(peer("2001:db8::1"), &headers("198.51.100.1"));
+        assert_eq!(ip, Some("198.51.100.1".parse().unwrap()));
+
+        let ip = proxies.client_ip(peer("10.1.2.3"), &HeaderMap::new());
+        assert_eq!(ip, Some("10.1.2.3".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_invalid_trusted_proxies_are_rejected() {
+        assert!(TrustedProxies::parse("10.0.0.0/33").is_err());
+        assert!(TrustedProxies::parse("proxy.internal").is_err());
+        assert!(TrustedProxies::parse("").is_ok());
+    }
+}
@@ -0,0 +1,214 @@
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, Jwk},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Request header carrying the proof.
+pub const DPOP_HEADER: &str = "DPoP";
+/// Response header carrying a nonce the next proof has to include.
+pub const DPOP_NONCE_HEADER: &str = "DPoP-Nonce";
+/// Authorization scheme of DPoP-bound access tokens.
+pub const DPOP_SCHEME: &str = "DPoP";
+/// The `typ` every proof has to declare.
+pub const DPOP_PROOF_TYPE: &str = "dpop+jwt";
+/// Proof algorithms accepted, advertised in the discovery document.
+pub const DPOP_SIGNING_ALGS: &[&str] = &["ES256", "ES384", "RS256", "PS256", "EdDSA"];
+
+/// Claims of a DPoP proof (RFC 9449, section 4.2).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DpopClaims {
+    pub jti: String,
+    /// HTTP method of the request the proof was made for.
+    pub htm: String,
+    /// URL of that request, without query and fragment.
+    pub htu: String,
+    pub iat: i64,
+    /// Hash of the access token, when one is presented with the proof.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ath: Option<String>,
+    /// The last nonce the server handed out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+/// A proof whose signature checks out against the key in its header.
+pub struct DpopProof {
+    pub claims: DpopClaims,
+    /// JWK SHA-256 thumbprint of that key, which tokens are bound to.
+    pub jkt: String,
+}
+
+/// Checks the form and signature of a proof. Whether it fits the request
+/// is up to the caller.
+pub fn decode_proof(proof: &str) -> Option<DpopProof> {
+    let header = jsonwebtoken::decode_header(proof).ok()?;
+    if header.typ.as_deref() != Some(DPOP_PROOF_TYPE)
+        || !DPOP_SIGNING_ALGS.contains(&algorithm_name(header.alg)?)
+        || has_private_key(proof)
+    {
+        return None;
+    }
+    let jwk = header.jwk?;
+    let key = DecodingKey::from_jwk(&jwk).ok()?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_required_spec_claims::<&str>(&[]);
+    validation.validate_exp = false;
+    let claims = jsonwebtoken::decode::<DpopClaims>(proof, &key, &validation)
+        .ok()?
+        .claims;
+
+    Some(DpopProof {
+        claims,
+        jkt: jwk_thumbprint(&jwk)?,
+    })
+}
+
+fn algorithm_name(alg: Algorithm) -> Option<&'static str> {
+    match alg {
+        Algorithm::ES256 => Some("ES256"),
+        Algorithm::ES384 => Some("ES384"),
+        Algorithm::RS256 => Some("RS256"),
+        Algorithm::PS256 => Some("PS256"),
+        Algorithm::EdDSA => Some("EdDSA"),
+        _ => None,
+    }
+}
+
+/// The key parser drops members it does not know, so the private part of
+/// a key sent by mistake has to be looked for in the raw header.
+fn has_private_key(proof: &str) -> bool {
+    let header = proof
+        .split('.')
+        .next()
+        .and_then(|header| BASE64URL_NOPAD.decode(header.as_bytes()).ok())
+        .and_then(|header| serde_json::from_slice::<serde_json::Value>(&header).ok());
+    header
+        .as_ref()
+        .and_then(|header| header.get("jwk"))
+        .is_none_or(|jwk| jwk.get("d").is_some())
+}
+
+/// RFC 7638 thumbprint: SHA-256 of the required members in lexicographic
+/// order. Symmetric keys cannot prove possession and have none.
+pub fn jwk_thumbprint(jwk: &Jwk) -> Option<String> {
+    let canonical = match &jwk.algorithm {
+        AlgorithmParameters::RSA(params) => {
+            format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, params.e, params.n)
+        }
+        AlgorithmParameters::EllipticCurve(params) => format!(
+            r#"{{"crv":{},"kty":"EC","x":"{}","y":"{}"}}"#,
+            serde_json::to_string(&params.curve).ok()?,
+            params.x,
+            params.y
+        ),
+        AlgorithmParameters::OctetKeyPair(params) => format!(
+            r#"{{"crv":{},"kty":"OKP","x":"{}"}}"#,
+            serde_json::to_string(&params.curve).ok()?,
+            params.x
+        ),
+        AlgorithmParameters::OctetKey(_) => return None,
+    };
+    Some(BASE64URL_NOPAD.encode(&Sha256::digest(canonical.as_bytes())))
+}
+
+/// The `ath` a proof sent with the access token has to carry.
+pub fn access_token_hash(access_token: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(access_token.as_bytes()))
+}
+
+/// Whether the proof's `htu` names the request URL. Query and fragment
+/// are not part of the comparison.
+pub fn htu_matches(htu: &str, url: &str) -> bool {
+    let strip = |url: &str| url.split(['?', '#']).next().unwrap_or_default().to_string();
+    bool::from(strip(htu).as_bytes().ct_eq(strip(url).as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{EncodingKey, Header};
+    use p256::{ecdsa::SigningKey, pkcs8::EncodePrivateKey};
+    use rsa::rand_core::OsRng;
+
+    use super::*;
+
+    fn proof(key: &SigningKey, mut header: serde_json::Value, claims: &DpopClaims) -> String {
+        let point = key.verifying_key().to_encoded_point(false);
+        header["jwk"] = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": BASE64URL_NOPAD.encode(point.x().unwrap()),
+            "y": BASE64URL_NOPAD.encode(point.y().unwrap()),
+        });
+        let header: Header = serde_json::from_value(header).unwrap();
+        let der = key.to_pkcs8_der().unwrap();
+        jsonwebtoken::encode(&header, claims, &EncodingKey::from_ec_der(der.as_bytes())).unwrap()
+    }
+
+    fn claims() -> DpopClaims {
+        DpopClaims {
+            jti: "proof-1".to_string(),
+            htm: "POST".to_string(),
+            htu: "https://auth.example.com/token".to_string(),
+            iat: 0,
+            ath: None,
+            nonce: None,
+        }
+    }
+
+    #[test]
+    fn test_rfc7638_example_thumbprint() {
+        let jwk: Jwk = serde_json::from_value(serde_json::json!({
+            "kty": "RSA",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29",
+        }))
+        .unwrap();
+        assert_eq!(
+            Some("NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs".to_string()),
+            jwk_thumbprint(&jwk)
+        );
+    }
+
+    #[test]
+    fn test_proof_is_bound_to_its_key() {
+        let key = SigningKey::random(&mut OsRng);
+        let header = serde_json::json!({"alg": "ES256", "typ": DPOP_PROOF_TYPE});
+        let decoded = decode_proof(&proof(&key, header.clone(), &claims())).unwrap();
+        assert_eq!("proof-1", decoded.claims.jti);
+        assert_eq!(43, decoded.jkt.len());
+
+        let again = decode_proof(&proof(&key, header.clone(), &claims())).unwrap();
+        assert_eq!(decoded.jkt, again.jkt);
+        let other_key = SigningKey::random(&mut OsRng);
+        let other = decode_proof(&proof(&other_key, header.clone(), &claims())).unwrap();
+        assert_ne!(decoded.jkt, other.jkt);
+
+        let untyped = serde_json::json!({"alg": "ES256", "typ": "JWT"});
+        assert!(decode_proof(&proof(&key, untyped, &claims())).is_none());
+
+        let original = proof(&key, header.clone(), &claims());
+        let other = proof(&SigningKey::random(&mut OsRng), header, &claims());
+        let (signed, _) = original.rsplit_once('.').unwrap();
+        let (_, signature) = other.rsplit_once('.').unwrap();
+        assert!(decode_proof(&format!("{}.{}", signed, signature)).is_none());
+    }
+
+    #[test]
+    fn test_htu_ignores_query_and_fragment() {
+        assert!(htu_matches(
+            "https://api.example.com/users/1",
+            "https://api.example.com/users/1?expand=all"
+        ));
+        assert!(!htu_matches(
+            "https://api.example.com/users/1",
+            "https://api.example.com/users/2"
+        ));
+    }
+}
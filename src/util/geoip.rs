@@ -0,0 +1,27 @@
+use std::{error::Error, net::IpAddr, path::Path};
+
+use maxminddb::{geoip2, Reader};
+
+/// Country lookups against a local MaxMind-format database, such as
+/// GeoLite2-Country. The file is read into memory once at startup.
+pub struct GeoIp {
+    reader: Reader<Vec<u8>>,
+}
+
+impl GeoIp {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            reader: Reader::open_readfile(path)?,
+        })
+    }
+
+    /// ISO 3166-1 alpha-2 code for an address. Private and unknown
+    /// addresses have none.
+    pub fn country(&self, ip: IpAddr) -> Option<String> {
+        let record: geoip2::Country = self.reader.lookup(ip).ok()?;
+        record
+            .country?
+            .iso_code
+            .map(|code| code.to_ascii_uppercase())
+    }
+}
@@ -1,3 +1,17 @@
 pub mod tracing;
 pub mod logging;
+pub mod cipher;
+pub mod client_ip;
+pub mod dpop;
+pub mod geoip;
+pub mod id_token;
+pub mod otp;
+pub mod pkce;
+pub mod phone;
 pub mod pwd;
+pub mod pwd_pool;
+pub mod throttle;
+pub mod token;
+pub mod totp;
+pub mod user_agent;
+pub mod webauthn;
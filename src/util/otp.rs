@@ -0,0 +1,151 @@
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Random decimal code of `digits` length, zero padded.
+pub fn generate_numeric_code(digits: u32) -> String {
+    let modulus = 10_u64.pow(digits);
+    // Rejection sampling keeps every code equally likely.
+    let limit = u64::MAX - u64::MAX % modulus;
+    loop {
+        let value = OsRng.next_u64();
+        if value < limit {
+            return format!("{:0width$}", value % modulus, width = digits as usize);
+        }
+    }
+}
+
+/// Consonants only, so user codes cannot spell words, and without letters
+/// that are easy to confuse when read off a screen (RFC 8628, section 6.1).
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+/// Eight letters shown as `XXXX-XXXX`, for users to type in on a second
+/// device.
+pub fn generate_user_code() -> String {
+    // Rejection sampling keeps every letter equally likely.
+    let limit = u8::MAX - u8::MAX % USER_CODE_ALPHABET.len() as u8;
+    let mut letters = Vec::with_capacity(8);
+    while letters.len() < 8 {
+        let mut byte = [0_u8; 1];
+        OsRng.fill_bytes(&mut byte);
+        if byte[0] < limit {
+            letters.push(USER_CODE_ALPHABET[usize::from(byte[0]) % USER_CODE_ALPHABET.len()]);
+        }
+    }
+    let code = String::from_utf8(letters).expect("alphabet is ASCII");
+    format!("{}-{}", &code[..4], &code[4..])
+}
+
+/// Drops separators and case, so `bcdf ghjk` matches `BCDF-GHJK`.
+pub fn normalize_user_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// 256 random bits encoded as base64url, used for link secrets and browser
+/// binding nonces.
+pub fn generate_secret() -> String {
+    let mut bytes = [0_u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+/// Hashes one-time codes for storage and signs magic links with a server
+/// side key, so a leaked table does not allow the short codes to be brute
+/// forced offline.
+pub struct OneTimeCodeSigner {
+    key: Vec<u8>,
+}
+
+impl OneTimeCodeSigner {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.as_bytes().to_vec(),
+        }
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key length")
+    }
+
+    /// Keyed SHA-256 of `value` as lowercase hex.
+    pub fn hash(&self, value: &str) -> String {
+        let mut mac = self.mac();
+        mac.update(value.as_bytes());
+        HEXLOWER.encode(&mac.finalize().into_bytes())
+    }
+
+    /// Builds a `<flow id>.<secret>.<signature>` link token.
+    pub fn sign_link(&self, flow_id: Uuid, secret: &str) -> String {
+        let payload = format!("{}.{}", flow_id.simple(), secret);
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        let signature = BASE64URL_NOPAD.encode(&mac.finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    /// Returns the flow id and secret of a link token with a valid
+    /// signature.
+    pub fn verify_link(&self, token: &str) -> Option<(Uuid, String)> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        let signature = BASE64URL_NOPAD.decode(signature.as_bytes()).ok()?;
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).ok()?;
+
+        let (flow_id, secret) = payload.split_once('.')?;
+        Some((Uuid::parse_str(flow_id).ok()?, secret.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_code_has_requested_length() {
+        for _ in 0..100 {
+            let code = generate_numeric_code(6);
+            assert_eq!(6, code.len());
+            assert!(code.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_user_code_format() {
+        for _ in 0..100 {
+            let code = generate_user_code();
+            assert_eq!(9, code.len());
+            assert_eq!(Some(4), code.find('-'));
+            assert!(normalize_user_code(&code)
+                .bytes()
+                .all(|byte| USER_CODE_ALPHABET.contains(&byte)));
+        }
+        assert_eq!("BCDFGHJK", normalize_user_code(" bcdf ghjk\n"));
+    }
+
+    #[test]
+    fn test_link_round_trip_and_tampering() {
+        let signer = OneTimeCodeSigner::new("key");
+        let flow_id = Uuid::new_v4();
+        let secret = generate_secret();
+        let token = signer.sign_link(flow_id, &secret);
+
+        assert_eq!(Some((flow_id, secret.clone())), signer.verify_link(&token));
+        assert_eq!(None, OneTimeCodeSigner::new("other").verify_link(&token));
+
+        let tampered = token.replacen(&secret, &generate_secret(), 1);
+        assert_eq!(None, signer.verify_link(&tampered));
+        assert_eq!(None, signer.verify_link("not-a-token"));
+
+        assert_eq!(signer.hash("123456"), signer.hash("123456"));
+        assert_ne!(
+            signer.hash("123456"),
+            OneTimeCodeSigner::new("other").hash("123456")
+        );
+    }
+}
@@ -0,0 +1,52 @@
+/// Normalizes a phone number to E.164 (`+` followed by up to 15 digits, no
+/// leading zero). Spaces, dashes, dots and parentheses are dropped first.
+/// Returns `None` when the result is not a valid E.164 number.
+pub fn normalize_e164(input: &str) -> Option<String> {
+    let number: String = input
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+    let digits = number.strip_prefix('+')?;
+
+    let valid = (2..=15).contains(&digits.len())
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && !digits.starts_with('0');
+
+    valid.then_some(number)
+}
+
+/// Shows only the last two digits, e.g. `+*********23`.
+pub fn mask_phone_number(number: &str) -> String {
+    let visible = number.len().saturating_sub(2);
+    number
+        .char_indices()
+        .map(|(i, c)| if i == 0 || i >= visible { c } else { '*' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_e164() {
+        assert_eq!(
+            Some("+14155550123".to_string()),
+            normalize_e164("+1 (415) 555-0123")
+        );
+        assert_eq!(
+            Some("+6281234567890".to_string()),
+            normalize_e164("+62 812.3456.7890")
+        );
+        assert_eq!(None, normalize_e164("4155550123"));
+        assert_eq!(None, normalize_e164("+04155550123"));
+        assert_eq!(None, normalize_e164("+1415555012345678"));
+        assert_eq!(None, normalize_e164("+1415555O123"));
+        assert_eq!(None, normalize_e164("+"));
+    }
+
+    #[test]
+    fn test_mask_phone_number() {
+        assert_eq!("+*********23", mask_phone_number("+14155550123"));
+    }
+}
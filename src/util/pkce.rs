@@ -0,0 +1,43 @@
+use data_encoding::BASE64URL_NOPAD;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Whether a code verifier or S256 challenge has the shape RFC 7636 asks
+/// for: 43 to 128 unreserved characters.
+pub fn is_valid_pkce_value(value: &str) -> bool {
+    (43..=128).contains(&value.len())
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~'))
+}
+
+/// The S256 challenge for a verifier.
+pub fn s256_challenge(verifier: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(verifier.as_bytes()))
+}
+
+/// Checks a verifier against the challenge sent with the authorization
+/// request.
+pub fn verify_s256(verifier: &str, challenge: &str) -> bool {
+    is_valid_pkce_value(verifier)
+        && bool::from(
+            s256_challenge(verifier)
+                .as_bytes()
+                .ct_eq(challenge.as_bytes()),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc7636_example() {
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+        assert_eq!(challenge, s256_challenge(verifier));
+        assert!(verify_s256(verifier, challenge));
+        assert!(!verify_s256(&verifier.replace('d', "e"), challenge));
+        assert!(!verify_s256("short", &s256_challenge("short")));
+    }
+}
@@ -1,35 +1,116 @@
-use std::error::Error;
+use std::{error::Error, fmt};
 
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
+    password_hash::{self, rand_core::OsRng, PasswordHasher, SaltString},
     Argon2, Params, PasswordHash, PasswordVerifier,
 };
+use pbkdf2::Pbkdf2;
+use pwhash::sha512_crypt;
+use scrypt::Scrypt;
+use subtle::ConstantTimeEq;
 
 use crate::config::PwdConfig;
 
+/// Password hash formats understood by [`Pwd::verify_password_hash`].
+///
+/// New hashes are always Argon2id; the other schemes only exist so users
+/// imported from older systems can still sign in until they are rehashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashScheme {
+    Argon2id,
+    Bcrypt,
+    Scrypt,
+    Pbkdf2Sha256,
+    Sha512Crypt,
+}
+
+impl HashScheme {
+    pub fn identify(password_hash: &str) -> Option<Self> {
+        match password_hash {
+            hash if hash.starts_with("$argon2id$") => Some(HashScheme::Argon2id),
+            hash if hash.starts_with("$2a$")
+                || hash.starts_with("$2b$")
+                || hash.starts_with("$2y$") =>
+            {
+                Some(HashScheme::Bcrypt)
+            }
+            hash if hash.starts_with("$scrypt$") => Some(HashScheme::Scrypt),
+            hash if hash.starts_with("$pbkdf2-sha256$") => Some(HashScheme::Pbkdf2Sha256),
+            hash if hash.starts_with("$6$") => Some(HashScheme::Sha512Crypt),
+            _ => None,
+        }
+    }
+}
+
+impl AsRef<str> for HashScheme {
+    fn as_ref(&self) -> &str {
+        match self {
+            HashScheme::Argon2id => "argon2id",
+            HashScheme::Bcrypt => "bcrypt",
+            HashScheme::Scrypt => "scrypt",
+            HashScheme::Pbkdf2Sha256 => "pbkdf2-sha256",
+            HashScheme::Sha512Crypt => "sha512-crypt",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PwdError {
+    /// The configured secret or parameters were rejected by Argon2.
+    InvalidConfig(String),
+    /// A new hash could not be computed.
+    Hashing(String),
+    /// The stored hash is malformed or uses an unknown scheme.
+    CorruptHash(String),
+}
+
+impl fmt::Display for PwdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PwdError::InvalidConfig(reason) => write!(f, "invalid password config: {}", reason),
+            PwdError::Hashing(reason) => write!(f, "failed to hash password: {}", reason),
+            PwdError::CorruptHash(reason) => write!(f, "corrupt password hash: {}", reason),
+        }
+    }
+}
+
+impl Error for PwdError {}
+
+fn password_matches(result: Result<(), password_hash::Error>) -> Result<bool, PwdError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(err) => Err(PwdError::CorruptHash(err.to_string())),
+    }
+}
+
+fn parse_phc_hash(password_hash: &str) -> Result<PasswordHash<'_>, PwdError> {
+    PasswordHash::new(password_hash).map_err(|err| PwdError::CorruptHash(err.to_string()))
+}
+
 pub struct Pwd<'a> {
     argon2: Argon2<'a>,
 }
 
 impl<'a> Pwd<'a> {
-    pub fn new(pwd_config: &'a PwdConfig) -> Self {
-        Self {
-            argon2: Argon2::new_with_secret(
-                pwd_config.secret.as_bytes(),
-                argon2::Algorithm::Argon2id,
-                argon2::Version::V0x13,
-                Params::default(),
-            )
-            .unwrap(),
-        }
+    pub fn new(pwd_config: &'a PwdConfig) -> Result<Self, PwdError> {
+        let argon2 = Argon2::new_with_secret(
+            pwd_config.secret.as_bytes(),
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            Params::default(),
+        )
+        .map_err(|err| PwdError::InvalidConfig(err.to_string()))?;
+
+        Ok(Self { argon2 })
     }
 
-    pub fn generate_password_hash(&self, password: &str) -> Result<String, Box<dyn Error>> {
+    pub fn generate_password_hash(&self, password: &str) -> Result<String, PwdError> {
         let salt = SaltString::generate(&mut OsRng);
         let password_hash = self
             .argon2
             .hash_password(password.as_bytes(), &salt)
-            .unwrap()
+            .map_err(|err| PwdError::Hashing(err.to_string()))?
             .to_string();
         Ok(password_hash)
     }
@@ -38,12 +119,43 @@ impl<'a> Pwd<'a> {
         &self,
         password: &str,
         password_hash: &str,
-    ) -> Result<bool, Box<dyn Error>> {
-        let parsed_hash = PasswordHash::new(password_hash).unwrap();
-        Ok(self
-            .argon2
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .is_ok())
+    ) -> Result<bool, PwdError> {
+        match HashScheme::identify(password_hash) {
+            Some(HashScheme::Argon2id) => {
+                let parsed_hash = parse_phc_hash(password_hash)?;
+                password_matches(
+                    self.argon2
+                        .verify_password(password.as_bytes(), &parsed_hash),
+                )
+            }
+            Some(HashScheme::Bcrypt) => bcrypt::verify(password, password_hash)
+                .map_err(|err| PwdError::CorruptHash(err.to_string())),
+            Some(HashScheme::Scrypt) => {
+                let parsed_hash = parse_phc_hash(password_hash)?;
+                password_matches(Scrypt.verify_password(password.as_bytes(), &parsed_hash))
+            }
+            Some(HashScheme::Pbkdf2Sha256) => {
+                let parsed_hash = parse_phc_hash(password_hash)?;
+                password_matches(Pbkdf2.verify_password(password.as_bytes(), &parsed_hash))
+            }
+            Some(HashScheme::Sha512Crypt) => {
+                let computed_hash = sha512_crypt::hash_with(password_hash, password)
+                    .map_err(|err| PwdError::CorruptHash(err.to_string()))?;
+                Ok(computed_hash
+                    .as_bytes()
+                    .ct_eq(password_hash.as_bytes())
+                    .into())
+            }
+            None => Err(PwdError::CorruptHash(
+                "unrecognised hash scheme".to_string(),
+            )),
+        }
+    }
+
+    /// Whether a stored hash should be replaced with a fresh Argon2id hash
+    /// after the next successful verification.
+    pub fn needs_rehash(&self, password_hash: &str) -> bool {
+        HashScheme::identify(password_hash) != Some(HashScheme::Argon2id)
     }
 }
 
@@ -55,7 +167,7 @@ mod tests {
     #[test]
     fn test_generate_and_verify_password() {
         let pwd_config = PwdConfig::default();
-        let pwd = Pwd::new(&pwd_config);
+        let pwd = Pwd::new(&pwd_config).unwrap();
         let password = "SuperDuperSecretPassword";
 
         let result_hashed_password = pwd.generate_password_hash(password);
@@ -75,5 +187,83 @@ mod tests {
 
         let verify_status = result_verify_password.ok().unwrap();
         assert!(!verify_status);
+        assert!(!pwd.needs_rehash(hashed_password.as_str()));
+    }
+
+    #[test]
+    fn test_verify_legacy_password_hashes() {
+        let pwd_config = PwdConfig::default();
+        let pwd = Pwd::new(&pwd_config).unwrap();
+        let password = "SuperDuperSecretPassword";
+        let salt = SaltString::generate(&mut OsRng);
+
+        let bcrypt_hash = bcrypt::hash(password, 4).unwrap();
+        let scrypt_hash = Scrypt
+            .hash_password_customized(
+                password.as_bytes(),
+                None,
+                None,
+                scrypt::Params::new(4, 8, 1, 32).unwrap(),
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        let pbkdf2_hash = Pbkdf2
+            .hash_password_customized(
+                password.as_bytes(),
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                pbkdf2::Params {
+                    rounds: 1_000,
+                    output_length: 32,
+                },
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        let sha512_crypt_hash = sha512_crypt::hash(password).unwrap();
+
+        for (scheme, hashed_password) in [
+            (HashScheme::Bcrypt, bcrypt_hash),
+            (HashScheme::Scrypt, scrypt_hash),
+            (HashScheme::Pbkdf2Sha256, pbkdf2_hash),
+            (HashScheme::Sha512Crypt, sha512_crypt_hash),
+        ] {
+            assert_eq!(Some(scheme), HashScheme::identify(&hashed_password));
+            assert!(pwd.needs_rehash(&hashed_password));
+            assert!(pwd
+                .verify_password_hash(password, &hashed_password)
+                .unwrap());
+            assert!(!pwd
+                .verify_password_hash("SuperDuperWrongPassword", &hashed_password)
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_verify_sha512_crypt_reference_hash() {
+        let pwd_config = PwdConfig::default();
+        let pwd = Pwd::new(&pwd_config).unwrap();
+        let hashed_password = "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+
+        assert!(pwd
+            .verify_password_hash("Hello world!", hashed_password)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_verify_corrupt_password_hash() {
+        let pwd_config = PwdConfig::default();
+        let pwd = Pwd::new(&pwd_config).unwrap();
+
+        for hashed_password in [
+            "hashed_password",
+            "$argon2id$v=19$m=19456,t=2,p=1$not-base64!$",
+            "$2b$04$truncated",
+            "$scrypt$ln=abc$",
+        ] {
+            let result = pwd.verify_password_hash("SuperDuperSecretPassword", hashed_password);
+            assert!(matches!(result, Err(PwdError::CorruptHash(_))));
+        }
     }
 }
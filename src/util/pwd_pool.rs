@@ -0,0 +1,395 @@
+use std::{
+    error::Error,
+    fmt,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    sync::{OnceCell, Semaphore},
+    task, time,
+};
+
+use crate::{
+    config::PwdConfig,
+    util::pwd::{Pwd, PwdError},
+};
+
+const DUMMY_PASSWORD: &str = "dummy-password-for-unknown-users";
+
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+#[derive(Debug)]
+pub enum PwdPoolError {
+    QueueTimeout,
+    Cancelled,
+    /// The hashing task panicked.
+    Panicked,
+    Pwd(PwdError),
+}
+
+impl fmt::Display for PwdPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PwdPoolError::QueueTimeout => write!(f, "password hashing queue timed out"),
+            PwdPoolError::Cancelled => write!(f, "password hashing task was cancelled"),
+            PwdPoolError::Panicked => write!(f, "password hashing task panicked"),
+            PwdPoolError::Pwd(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for PwdPoolError {}
+
+impl From<PwdError> for PwdPoolError {
+    fn from(err: PwdError) -> Self {
+        PwdPoolError::Pwd(err)
+    }
+}
+
+/// Runs Argon2 (and legacy scheme) work on tokio's blocking threads so the
+/// async workers keep serving requests. At most `concurrency` operations run
+/// at once; callers that cannot get a slot within `timeout` are rejected.
+pub struct PwdPool {
+    config: Arc<PwdConfig>,
+    permits: Arc<Semaphore>,
+    timeout: Duration,
+    metrics: PwdMetrics,
+    dummy_hash: OnceCell<String>,
+}
+
+impl PwdPool {
+    pub fn new(config: PwdConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.concurrency.max(1))),
+            timeout: Duration::from_millis(config.timeout),
+            config: Arc::new(config),
+            metrics: PwdMetrics::default(),
+            dummy_hash: OnceCell::new(),
+        }
+    }
+
+    pub async fn generate_password_hash(&self, password: &str) -> Result<String, PwdPoolError> {
+        let password = password.to_string();
+        self.run(PwdOperation::Hash, move |pwd| {
+            pwd.generate_password_hash(&password)
+        })
+        .await
+    }
+
+    pub async fn verify_password_hash(
+        &self,
+        password: &str,
+        password_hash: &str,
+    ) -> Result<bool, PwdPoolError> {
+        let password = password.to_string();
+        let password_hash = password_hash.to_string();
+        self.run(PwdOperation::Verify, move |pwd| {
+            pwd.verify_password_hash(&password, &password_hash)
+        })
+        .await
+    }
+
+    /// Verifies `password` against a throwaway Argon2id hash, so a sign-in
+    /// for an unknown user takes as long as one with a wrong password.
+    pub async fn verify_dummy_password(&self, password: &str) -> Result<(), PwdPoolError> {
+        let dummy_hash = self
+            .dummy_hash
+            .get_or_try_init(|| self.generate_password_hash(DUMMY_PASSWORD))
+            .await?;
+        self.verify_password_hash(password, dummy_hash).await?;
+        Ok(())
+    }
+
+    pub fn needs_rehash(&self, password_hash: &str) -> bool {
+        Pwd::new(&self.config)
+            .map(|pwd| pwd.needs_rehash(password_hash))
+            .unwrap_or(true)
+    }
+
+    pub fn metrics(&self) -> &PwdMetrics {
+        &self.metrics
+    }
+
+    async fn run<T, F>(&self, operation: PwdOperation, f: F) -> Result<T, PwdPoolError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Pwd) -> Result<T, PwdError> + Send + 'static,
+    {
+        let queued = Gauge::enter(&self.metrics.queue_depth);
+        let permit = time::timeout(self.timeout, Arc::clone(&self.permits).acquire_owned()).await;
+        drop(queued);
+
+        let permit = match permit {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) => return Err(PwdPoolError::Cancelled),
+            Err(_) => {
+                self.metrics.queue_timeouts.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    operation = operation.as_ref(),
+                    "password hashing queue timed out"
+                );
+                return Err(PwdPoolError::QueueTimeout);
+            }
+        };
+
+        let config = Arc::clone(&self.config);
+        let started_at = Instant::now();
+        let in_flight = Gauge::enter(&self.metrics.in_flight);
+        let result = task::spawn_blocking(move || {
+            let _permit = permit;
+            Pwd::new(&config).and_then(|pwd| f(&pwd))
+        })
+        .await;
+        drop(in_flight);
+
+        let elapsed = started_at.elapsed();
+        self.metrics.latency(operation).observe(elapsed);
+        tracing::debug!(
+            operation = operation.as_ref(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            "password hashing finished"
+        );
+
+        match result {
+            Ok(result) => result.map_err(PwdPoolError::from),
+            Err(err) if err.is_panic() => {
+                tracing::error!(
+                    operation = operation.as_ref(),
+                    error = %err,
+                    "password hashing task panicked"
+                );
+                Err(PwdPoolError::Panicked)
+            }
+            Err(_) => Err(PwdPoolError::Cancelled),
+        }
+    }
+}
+
+/// Counts itself into a gauge until it is dropped, so the gauge stays
+/// right when the caller's future is dropped mid-await.
+struct Gauge<'a>(&'a AtomicU64);
+
+impl<'a> Gauge<'a> {
+    fn enter(gauge: &'a AtomicU64) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge)
+    }
+}
+
+impl Drop for Gauge<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PwdOperation {
+    Hash,
+    Verify,
+}
+
+impl AsRef<str> for PwdOperation {
+    fn as_ref(&self) -> &str {
+        match self {
+            PwdOperation::Hash => "hash",
+            PwdOperation::Verify => "verify",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PwdMetrics {
+    queue_depth: AtomicU64,
+    in_flight: AtomicU64,
+    queue_timeouts: AtomicU64,
+    hash_latency: LatencyHistogram,
+    verify_latency: LatencyHistogram,
+}
+
+impl PwdMetrics {
+    fn latency(&self, operation: PwdOperation) -> &LatencyHistogram {
+        match operation {
+            PwdOperation::Hash => &self.hash_latency,
+            PwdOperation::Verify => &self.verify_latency,
+        }
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# TYPE pwd_queue_depth gauge");
+        let _ = writeln!(
+            out,
+            "pwd_queue_depth {}",
+            self.queue_depth.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE pwd_in_flight gauge");
+        let _ = writeln!(
+            out,
+            "pwd_in_flight {}",
+            self.in_flight.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE pwd_queue_timeouts_total counter");
+        let _ = writeln!(
+            out,
+            "pwd_queue_timeouts_total {}",
+            self.queue_timeouts.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(out, "# TYPE pwd_duration_seconds histogram");
+        for operation in [PwdOperation::Hash, PwdOperation::Verify] {
+            self.latency(operation).render(&mut out, operation.as_ref());
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn observe(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        for (bucket, le) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= le {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, operation: &str) {
+        for (bucket, le) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(
+                out,
+                "pwd_duration_seconds_bucket{{operation=\"{}\",le=\"{}\"}} {}",
+                operation,
+                le,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "pwd_duration_seconds_bucket{{operation=\"{}\",le=\"+Inf\"}} {}",
+            operation, count
+        );
+        let _ = writeln!(
+            out,
+            "pwd_duration_seconds_sum{{operation=\"{}\"}} {}",
+            operation,
+            self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(
+            out,
+            "pwd_duration_seconds_count{{operation=\"{}\"}} {}",
+            operation, count
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(concurrency: usize, timeout: u64) -> PwdConfig {
+        PwdConfig {
+            concurrency,
+            timeout,
+            ..PwdConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_and_verify_on_pool() {
+        let pool = PwdPool::new(test_config(2, 5_000));
+
+        let hashed_password = pool
+            .generate_password_hash("SuperDuperSecretPassword")
+            .await
+            .unwrap();
+        assert!(pool
+            .verify_password_hash("SuperDuperSecretPassword", &hashed_password)
+            .await
+            .unwrap());
+        assert!(!pool
+            .verify_password_hash("SuperDuperWrongPassword", &hashed_password)
+            .await
+            .unwrap());
+
+        let rendered = pool.metrics().render();
+        assert!(rendered.contains("pwd_duration_seconds_count{operation=\"hash\"} 1"));
+        assert!(rendered.contains("pwd_duration_seconds_count{operation=\"verify\"} 2"));
+    }
+
+    #[tokio::test]
+    async fn test_dummy_password_is_verified_with_argon2() {
+        let pool = PwdPool::new(test_config(2, 5_000));
+
+        pool.verify_dummy_password("SuperDuperSecretPassword")
+            .await
+            .unwrap();
+        pool.verify_dummy_password("SuperDuperSecretPassword")
+            .await
+            .unwrap();
+
+        let rendered = pool.metrics().render();
+        assert!(rendered.contains("pwd_duration_seconds_count{operation=\"hash\"} 1"));
+        assert!(rendered.contains("pwd_duration_seconds_count{operation=\"verify\"} 2"));
+    }
+
+    #[tokio::test]
+    async fn test_gauges_recover_when_the_caller_gives_up() {
+        let pool = PwdPool::new(test_config(1, 5_000));
+        let held = Arc::clone(&pool.permits).acquire_owned().await.unwrap();
+
+        let result = time::timeout(
+            Duration::from_millis(10),
+            pool.generate_password_hash("SuperDuperSecretPassword"),
+        )
+        .await;
+        assert!(result.is_err());
+        drop(held);
+
+        assert!(pool.metrics().render().contains("pwd_queue_depth 0"));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_is_an_internal_error() {
+        let pool = PwdPool::new(test_config(1, 5_000));
+
+        let result = pool
+            .run(PwdOperation::Hash, |_| -> Result<(), PwdError> {
+                panic!("hashing blew up")
+            })
+            .await;
+
+        assert!(matches!(result, Err(PwdPoolError::Panicked)));
+        assert!(pool.metrics().render().contains("pwd_in_flight 0"));
+    }
+
+    #[tokio::test]
+    async fn test_queue_timeout_when_pool_is_saturated() {
+        let pool = PwdPool::new(test_config(1, 10));
+        let _held = Arc::clone(&pool.permits).acquire_owned().await.unwrap();
+
+        let result = pool
+            .generate_password_hash("SuperDuperSecretPassword")
+            .await;
+        assert!(matches!(result, Err(PwdPoolError::QueueTimeout)));
+        assert!(pool
+            .metrics()
+            .render()
+            .contains("pwd_queue_timeouts_total 1"));
+    }
+}
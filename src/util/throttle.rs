@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::config::ThrottleConfig;
+
+/// Upper bound on tracked keys before expired windows are swept.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct Window {
+    started_at: Instant,
+    count: u32,
+}
+
+/// Fixed-window attempt counter keyed by an arbitrary string.
+pub struct Throttle {
+    limit: u32,
+    window: Duration,
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl Throttle {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts an attempt for `key`. Returns how long the caller has to wait
+    /// when the key has used up its attempts for the current window.
+    pub fn attempt(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|err| err.into_inner());
+
+        if windows.len() > SWEEP_THRESHOLD {
+            windows.retain(|_, window| now.duration_since(window.started_at) < self.window);
+        }
+
+        let window = windows.entry(key.to_string()).or_insert(Window {
+            started_at: now,
+            count: 0,
+        });
+        if now.duration_since(window.started_at) >= self.window {
+            window.started_at = now;
+            window.count = 0;
+        }
+
+        if window.count >= self.limit {
+            return Err(self.window - now.duration_since(window.started_at));
+        }
+        window.count += 1;
+
+        Ok(())
+    }
+}
+
+/// Limits credential checks per client IP and per username.
+pub struct LoginThrottle {
+    ip: Throttle,
+    username: Throttle,
+}
+
+impl LoginThrottle {
+    pub fn new(config: &ThrottleConfig) -> Self {
+        let window = Duration::from_secs(config.window);
+        Self {
+            ip: Throttle::new(config.ip, window),
+            username: Throttle::new(config.username, window),
+        }
+    }
+
+    pub fn attempt(&self, ip_address: Option<&str>, username: &str) -> Result<(), Duration> {
+        if let Some(ip_address) = ip_address {
+            self.ip.attempt(ip_address)?;
+        }
+        self.username.attempt(&username.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_rejects_after_limit() {
+        let throttle = Throttle::new(2, Duration::from_secs(60));
+
+        assert!(throttle.attempt("127.0.0.1").is_ok());
+        assert!(throttle.attempt("127.0.0.1").is_ok());
+
+        let retry_after = throttle.attempt("127.0.0.1").unwrap_err();
+        assert!(retry_after <= Duration::from_secs(60));
+        assert!(throttle.attempt("10.0.0.1").is_ok());
+    }
+
+    #[test]
+    fn test_throttle_resets_after_window() {
+        let throttle = Throttle::new(1, Duration::from_millis(10));
+
+        assert!(throttle.attempt("alice").is_ok());
+        assert!(throttle.attempt("alice").is_err());
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(throttle.attempt("alice").is_ok());
+    }
+}
@@ -0,0 +1,469 @@
+use chrono::Utc;
+use jsonwebtoken::{
+    errors::{Error, ErrorKind},
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::TokenConfig,
+    domain::{auth_context::AuthContext, principal::Principal},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessClaims {
+    pub sub: Uuid,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: Uuid,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    #[serde(flatten)]
+    pub auth: AuthContext,
+    /// The OAuth client the token was issued to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<Uuid>,
+    /// Space-separated scopes granted to that client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// The resource server a token obtained through token exchange is
+    /// addressed to. Such tokens are not accepted by this service's API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Who is acting on the subject's behalf, after a delegating token
+    /// exchange.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    /// The key a DPoP-bound token has to be presented with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<Confirmation>,
+}
+
+impl AccessClaims {
+    /// Thumbprint of the key the token is bound to, if any.
+    pub fn jkt(&self) -> Option<&str> {
+        self.cnf.as_ref().map(|cnf| cnf.jkt.as_str())
+    }
+}
+
+/// The `act` claim of RFC 8693. A nested `act` names the actor before this
+/// one, so the claim records the whole delegation chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: Uuid,
+    /// The client the actor's token was issued to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<Actor>>,
+}
+
+/// The `cnf` claim of RFC 9449: the JWK SHA-256 thumbprint of the proof
+/// key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confirmation {
+    pub jkt: String,
+}
+
+impl From<AccessClaims> for Principal {
+    fn from(claims: AccessClaims) -> Self {
+        Self {
+            user_id: claims.sub,
+            roles: claims.roles,
+            token_id: claims.jti,
+            expires_at: claims.exp,
+            auth: claims.auth,
+            session: false,
+            client_id: claims.client_id,
+            scope: claims.scope,
+        }
+    }
+}
+
+/// Audience of the short-lived token handed out between the password and
+/// the second factor. Access token validation rejects it.
+pub const MFA_AUDIENCE: &str = "mfa";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaChallengeClaims {
+    pub sub: Uuid,
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: Uuid,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Methods used for the first factor, carried over into the access token.
+    #[serde(default)]
+    pub amr: Vec<String>,
+    /// Set when the sign-in risk checks raised the challenge for an account
+    /// without MFA. Such a challenge is answered with an emailed code.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub verify_email: bool,
+}
+
+/// Audiences of tokens that must never pass for an access token, even
+/// where access tokens addressed to other audiences are accepted.
+const INTERNAL_AUDIENCES: &[&str] = &[MFA_AUDIENCE, CONSENT_AUDIENCE];
+
+/// Audience of the token carried by the OAuth consent screen. It proves
+/// the user signed in before being asked, so the answer can be posted
+/// without a session.
+pub const CONSENT_AUDIENCE: &str = "consent";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentTicketClaims {
+    pub sub: Uuid,
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: Uuid,
+    /// The client asking for access.
+    pub client_id: Uuid,
+    /// The scopes the user is asked to agree to.
+    #[serde(default)]
+    pub scope: String,
+    /// How the user signed in, carried over into the authorization code.
+    #[serde(flatten)]
+    pub auth: AuthContext,
+}
+
+pub struct IssuedToken {
+    pub token: String,
+    pub claims: AccessClaims,
+    pub expires_in: i64,
+}
+
+/// Signs and validates the HS256 access tokens handed out on login.
+pub struct TokenService {
+    config: TokenConfig,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl TokenService {
+    pub fn new(config: TokenConfig) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(config.secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.secret.as_bytes()),
+            config,
+        }
+    }
+
+    /// Default lifetime of access tokens, in seconds.
+    pub fn ttl(&self) -> i64 {
+        self.config.ttl
+    }
+
+    pub fn issue_access_token(
+        &self,
+        user_id: Uuid,
+        roles: Vec<String>,
+        auth: AuthContext,
+    ) -> Result<IssuedToken, Error> {
+        let now = Utc::now().timestamp();
+        let claims = AccessClaims {
+            sub: user_id,
+            iss: self.config.issuer.clone(),
+            iat: now,
+            exp: now + self.config.ttl,
+            jti: Uuid::new_v4(),
+            roles,
+            auth,
+            client_id: None,
+            scope: None,
+            aud: None,
+            act: None,
+            cnf: None,
+        };
+        self.sign_access_token(claims)
+    }
+
+    /// Issues a token on behalf of an OAuth client. The user's roles are
+    /// not delegated and the first-party API refuses the token; it only
+    /// reaches resources that check the scopes the client was granted, for
+    /// the lifetime registered for it. Under the client credentials grant
+    /// the subject is the client itself. A `jkt` binds the token to a
+    /// DPoP key.
+    pub fn issue_oauth_access_token(
+        &self,
+        subject: Uuid,
+        auth: AuthContext,
+        client_id: Uuid,
+        scope: Option<String>,
+        ttl: i64,
+        jkt: Option<String>,
+    ) -> Result<IssuedToken, Error> {
+        let now = Utc::now().timestamp();
+        let claims = AccessClaims {
+            sub: subject,
+            iss: self.config.issuer.clone(),
+            iat: now,
+            exp: now + ttl,
+            jti: Uuid::new_v4(),
+            roles: Vec::new(),
+            auth,
+            client_id: Some(client_id),
+            scope,
+            aud: None,
+            act: None,
+            cnf: jkt.map(|jkt| Confirmation { jkt }),
+        };
+        self.sign_access_token(claims)
+    }
+
+    /// Issues a token in exchange for another one (RFC 8693). It keeps the
+    /// subject and how they signed in, is addressed to a single audience and
+    /// never outlives the token it was exchanged for.
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_exchanged_access_token(
+        &self,
+        subject: &AccessClaims,
+        client_id: Uuid,
+        scope: Option<String>,
+        audience: String,
+        act: Option<Actor>,
+        ttl: i64,
+        jkt: Option<String>,
+    ) -> Result<IssuedToken, Error> {
+        let now = Utc::now().timestamp();
+        let claims = AccessClaims {
+            sub: subject.sub,
+            iss: self.config.issuer.clone(),
+            iat: now,
+            exp: (now + ttl).min(subject.exp),
+            jti: Uuid::new_v4(),
+            roles: Vec::new(),
+            auth: subject.auth.clone(),
+            client_id: Some(client_id),
+            scope,
+            aud: Some(audience),
+            act,
+            cnf: jkt.map(|jkt| Confirmation { jkt }),
+        };
+        self.sign_access_token(claims)
+    }
+
+    fn sign_access_token(&self, claims: AccessClaims) -> Result<IssuedToken, Error> {
+        let token =
+            jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)?;
+
+        Ok(IssuedToken {
+            token,
+            expires_in: claims.exp - claims.iat,
+            claims,
+        })
+    }
+
+    /// Verifies a token for this service's own API. Tokens with an
+    /// audience are addressed elsewhere and rejected.
+    pub fn verify_access_token(&self, token: &str) -> Result<AccessClaims, Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+        let data = jsonwebtoken::decode::<AccessClaims>(token, &self.decoding_key, &validation)?;
+        Ok(data.claims)
+    }
+
+    /// Verifies any access token this service issued, whatever its
+    /// audience. For the OAuth endpoints that take tokens addressed to
+    /// resource servers: introspection, revocation and token exchange.
+    pub fn verify_issued_access_token(&self, token: &str) -> Result<AccessClaims, Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+        validation.validate_aud = false;
+
+        let data = jsonwebtoken::decode::<AccessClaims>(token, &self.decoding_key, &validation)?;
+        if data
+            .claims
+            .aud
+            .as_deref()
+            .is_some_and(|aud| INTERNAL_AUDIENCES.contains(&aud))
+        {
+            return Err(ErrorKind::InvalidAudience.into());
+        }
+        Ok(data.claims)
+    }
+
+    pub fn issue_mfa_challenge(
+        &self,
+        user_id: Uuid,
+        roles: Vec<String>,
+        amr: Vec<String>,
+        verify_email: bool,
+        ttl: i64,
+    ) -> Result<String, Error> {
+        let now = Utc::now().timestamp();
+        let claims = MfaChallengeClaims {
+            sub: user_id,
+            iss: self.config.issuer.clone(),
+            aud: MFA_AUDIENCE.to_string(),
+            iat: now,
+            exp: now + ttl,
+            jti: Uuid::new_v4(),
+            roles,
+            amr,
+            verify_email,
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+    }
+
+    pub fn verify_mfa_challenge(&self, token: &str) -> Result<MfaChallengeClaims, Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[MFA_AUDIENCE]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+
+        let data =
+            jsonwebtoken::decode::<MfaChallengeClaims>(token, &self.decoding_key, &validation)?;
+        Ok(data.claims)
+    }
+
+    pub fn issue_consent_ticket(
+        &self,
+        user_id: Uuid,
+        client_id: Uuid,
+        scope: String,
+        auth: AuthContext,
+        ttl: i64,
+    ) -> Result<String, Error> {
+        let now = Utc::now().timestamp();
+        let claims = ConsentTicketClaims {
+            sub: user_id,
+            iss: self.config.issuer.clone(),
+            aud: CONSENT_AUDIENCE.to_string(),
+            iat: now,
+            exp: now + ttl,
+            jti: Uuid::new_v4(),
+            client_id,
+            scope,
+            auth,
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+    }
+
+    pub fn verify_consent_ticket(&self, token: &str) -> Result<ConsentTicketClaims, Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[CONSENT_AUDIENCE]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+
+        let data =
+            jsonwebtoken::decode::<ConsentTicketClaims>(token, &self.decoding_key, &validation)?;
+        Ok(data.claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_and_verify_access_token() {
+        let tokens = TokenService::new(TokenConfig::default());
+        let user_id = Uuid::new_v4();
+
+        let auth = AuthContext::new(&["pwd", "otp"]);
+        let issued = tokens
+            .issue_access_token(user_id, vec!["admin".to_string()], auth.clone())
+            .unwrap();
+        let claims = tokens.verify_access_token(&issued.token).unwrap();
+        assert_eq!(user_id, claims.sub);
+        assert_eq!(issued.claims.jti, claims.jti);
+        assert_eq!(vec!["admin".to_string()], claims.roles);
+        assert_eq!(auth, claims.auth);
+
+        let other = TokenService::new(TokenConfig {
+            secret: "AnotherSecret".to_string(),
+            ..TokenConfig::default()
+        });
+        assert!(other.verify_access_token(&issued.token).is_err());
+    }
+
+    #[test]
+    fn test_mfa_challenge_is_not_an_access_token() {
+        let tokens = TokenService::new(TokenConfig::default());
+        let user_id = Uuid::new_v4();
+
+        let challenge = tokens
+            .issue_mfa_challenge(user_id, Vec::new(), vec!["pwd".to_string()], false, 300)
+            .unwrap();
+        assert_eq!(
+            user_id,
+            tokens.verify_mfa_challenge(&challenge).unwrap().sub
+        );
+        assert!(tokens.verify_access_token(&challenge).is_err());
+
+        let issued = tokens
+            .issue_access_token(user_id, Vec::new(), AuthContext::default())
+            .unwrap();
+        assert!(tokens.verify_mfa_challenge(&issued.token).is_err());
+    }
+
+    #[test]
+    fn test_exchanged_token_is_only_accepted_where_audiences_are() {
+        let tokens = TokenService::new(TokenConfig::default());
+        let subject = tokens
+            .issue_access_token(Uuid::new_v4(), Vec::new(), AuthContext::default())
+            .unwrap()
+            .claims;
+        let act = Actor {
+            sub: Uuid::new_v4(),
+            client_id: None,
+            act: None,
+        };
+
+        let exchanged = tokens
+            .issue_exchanged_access_token(
+                &subject,
+                Uuid::new_v4(),
+                None,
+                "https://api.example.com".to_string(),
+                Some(act.clone()),
+                i64::MAX / 2,
+                None,
+            )
+            .unwrap();
+        assert_eq!(subject.exp, exchanged.claims.exp);
+        assert!(tokens.verify_access_token(&exchanged.token).is_err());
+        let claims = tokens.verify_issued_access_token(&exchanged.token).unwrap();
+        assert_eq!(subject.sub, claims.sub);
+        assert_eq!(Some(act), claims.act);
+
+        let challenge = tokens
+            .issue_mfa_challenge(subject.sub, Vec::new(), Vec::new(), false, 300)
+            .unwrap();
+        assert!(tokens.verify_issued_access_token(&challenge).is_err());
+    }
+
+    #[test]
+    fn test_consent_ticket_is_not_an_access_token() {
+        let tokens = TokenService::new(TokenConfig::default());
+        let user_id = Uuid::new_v4();
+        let client_id = Uuid::new_v4();
+
+        let ticket = tokens
+            .issue_consent_ticket(
+                user_id,
+                client_id,
+                "openid".to_string(),
+                AuthContext::new(&["pwd"]),
+                300,
+            )
+            .unwrap();
+        let claims = tokens.verify_consent_ticket(&ticket).unwrap();
+        assert_eq!(user_id, claims.sub);
+        assert_eq!(client_id, claims.client_id);
+        assert_eq!(vec!["pwd".to_string()], claims.auth.amr);
+        assert!(tokens.verify_access_token(&ticket).is_err());
+        assert!(tokens.verify_mfa_challenge(&ticket).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use woothee::{parser::Parser, woothee::VALUE_UNKNOWN};
+
+/// Browser and operating system as shown to users reviewing their sessions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UserAgentInfo {
+    pub browser: Option<String>,
+    pub os: Option<String>,
+}
+
+/// Best-effort parse of a `User-Agent` header. Parts that cannot be
+/// recognised are left empty rather than guessed.
+pub fn parse_user_agent(user_agent: &str) -> UserAgentInfo {
+    let Some(result) = Parser::new().parse(user_agent) else {
+        return UserAgentInfo::default();
+    };
+
+    let known = |value: &str| !value.is_empty() && value != VALUE_UNKNOWN;
+    let browser = known(result.name).then(|| {
+        if known(result.version) {
+            format!("{} {}", result.name, result.version)
+        } else {
+            result.name.to_string()
+        }
+    });
+    let os = known(result.os).then(|| result.os.to_string());
+
+    UserAgentInfo { browser, os }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_user_agent() {
+        let info = parse_user_agent(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert_eq!(Some("Chrome 120.0.0.0".to_string()), info.browser);
+        assert_eq!(Some("Windows 10".to_string()), info.os);
+
+        assert_eq!(UserAgentInfo::default(), parse_user_agent("curl"));
+    }
+}
@@ -0,0 +1,537 @@
+use std::{error::Error, fmt, io::Cursor};
+
+use ciborium::Value;
+use data_encoding::BASE64URL_NOPAD;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::domain::webauthn::WebauthnAssertion;
+
+/// COSE algorithm identifiers accepted for new credentials.
+pub const ES256: i64 = -7;
+pub const EDDSA: i64 = -8;
+pub const RS256: i64 = -257;
+pub const SUPPORTED_ALGORITHMS: [i64; 3] = [ES256, EDDSA, RS256];
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+#[derive(Debug)]
+pub enum WebauthnError {
+    UnknownChallenge,
+    AlreadyRegistered,
+    Malformed(&'static str),
+    Mismatch(&'static str),
+    UserNotPresent,
+    UserNotVerified,
+    /// The signature counter did not increase, which points to a cloned
+    /// authenticator.
+    SignCount,
+    UnsupportedAlgorithm(i64),
+    UnsupportedAttestation(String),
+    InvalidSignature,
+}
+
+impl fmt::Display for WebauthnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebauthnError::UnknownChallenge => write!(f, "unknown or expired challenge"),
+            WebauthnError::AlreadyRegistered => write!(f, "credential is already registered"),
+            WebauthnError::Malformed(what) => write!(f, "malformed {}", what),
+            WebauthnError::Mismatch(what) => write!(f, "{} does not match", what),
+            WebauthnError::UserNotPresent => write!(f, "user presence was not confirmed"),
+            WebauthnError::UserNotVerified => write!(f, "user verification is required"),
+            WebauthnError::SignCount => write!(f, "signature counter did not increase"),
+            WebauthnError::UnsupportedAlgorithm(alg) => {
+                write!(f, "unsupported COSE algorithm {}", alg)
+            }
+            WebauthnError::UnsupportedAttestation(fmt) => {
+                write!(f, "unsupported attestation format {}", fmt)
+            }
+            WebauthnError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl Error for WebauthnError {}
+
+/// Decodes base64url with or without padding, as browsers differ.
+pub fn decode_base64url(value: &str) -> Result<Vec<u8>, WebauthnError> {
+    BASE64URL_NOPAD
+        .decode(value.trim_end_matches('=').as_bytes())
+        .map_err(|_| WebauthnError::Malformed("base64url value"))
+}
+
+pub fn encode_base64url(value: &[u8]) -> String {
+    BASE64URL_NOPAD.encode(value)
+}
+
+fn map_get(map: &[(Value, Value)], label: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(key, _)| {
+            key.as_integer()
+                .is_some_and(|key| i128::from(key) == i128::from(label))
+        })
+        .map(|(_, value)| value)
+}
+
+fn map_get_text<'a>(map: &'a [(Value, Value)], label: &str) -> Option<&'a Value> {
+    map.iter()
+        .find(|(key, _)| key.as_text() == Some(label))
+        .map(|(_, value)| value)
+}
+
+fn as_i64(value: Option<&Value>) -> Option<i64> {
+    value
+        .and_then(Value::as_integer)
+        .and_then(|value| i64::try_from(value).ok())
+}
+
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    /// COSE_Key encoded public key, stored as-is.
+    pub public_key: Vec<u8>,
+}
+
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(data: &[u8]) -> Result<Self, WebauthnError> {
+        if data.len() < 37 {
+            return Err(WebauthnError::Malformed("authenticator data"));
+        }
+
+        let mut rp_id_hash = [0_u8; 32];
+        rp_id_hash.copy_from_slice(&data[..32]);
+        let flags = data[32];
+        let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+            // aaguid (16 bytes), credential ID length (2 bytes), credential ID, COSE key
+            let rest = &data[37..];
+            if rest.len() < 18 {
+                return Err(WebauthnError::Malformed("attested credential data"));
+            }
+            let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            let credential_id = rest
+                .get(18..18 + id_len)
+                .ok_or(WebauthnError::Malformed("credential ID"))?
+                .to_vec();
+
+            // The key is followed by optional extensions, so its length is
+            // only known after decoding it.
+            let key_bytes = &rest[18 + id_len..];
+            let mut cursor = Cursor::new(key_bytes);
+            let _: Value = ciborium::from_reader(&mut cursor)
+                .map_err(|_| WebauthnError::Malformed("credential public key"))?;
+            let public_key = key_bytes[..cursor.position() as usize].to_vec();
+
+            Some(AttestedCredential {
+                credential_id,
+                public_key,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rp_id_hash,
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+}
+
+pub enum CosePublicKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    EdDsa(ed25519_dalek::VerifyingKey),
+    Rs256(rsa::pkcs1v15::VerifyingKey<Sha256>),
+}
+
+impl CosePublicKey {
+    pub fn parse(bytes: &[u8]) -> Result<Self, WebauthnError> {
+        let value: Value =
+            ciborium::from_reader(bytes).map_err(|_| WebauthnError::Malformed("COSE key"))?;
+        let map = value
+            .into_map()
+            .map_err(|_| WebauthnError::Malformed("COSE key"))?;
+        let bytes_at = |label| {
+            map_get(&map, label)
+                .and_then(Value::as_bytes)
+                .ok_or(WebauthnError::Malformed("COSE key"))
+        };
+
+        match as_i64(map_get(&map, 3)) {
+            Some(ES256) => {
+                let (x, y) = (bytes_at(-2)?, bytes_at(-3)?);
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(WebauthnError::Malformed("P-256 key"));
+                }
+                let mut point = vec![0x04];
+                point.extend_from_slice(x);
+                point.extend_from_slice(y);
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                    .map(CosePublicKey::Es256)
+                    .map_err(|_| WebauthnError::Malformed("P-256 key"))
+            }
+            Some(EDDSA) => {
+                let x: [u8; 32] = bytes_at(-2)?
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| WebauthnError::Malformed("Ed25519 key"))?;
+                ed25519_dalek::VerifyingKey::from_bytes(&x)
+                    .map(CosePublicKey::EdDsa)
+                    .map_err(|_| WebauthnError::Malformed("Ed25519 key"))
+            }
+            Some(RS256) => {
+                let key = rsa::RsaPublicKey::new(
+                    rsa::BigUint::from_bytes_be(bytes_at(-1)?),
+                    rsa::BigUint::from_bytes_be(bytes_at(-2)?),
+                )
+                .map_err(|_| WebauthnError::Malformed("RSA key"))?;
+                Ok(CosePublicKey::Rs256(rsa::pkcs1v15::VerifyingKey::new(key)))
+            }
+            Some(alg) => Err(WebauthnError::UnsupportedAlgorithm(alg)),
+            None => Err(WebauthnError::Malformed("COSE key")),
+        }
+    }
+
+    pub fn algorithm(&self) -> i64 {
+        match self {
+            CosePublicKey::Es256(_) => ES256,
+            CosePublicKey::EdDsa(_) => EDDSA,
+            CosePublicKey::Rs256(_) => RS256,
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), WebauthnError> {
+        use p256::ecdsa::signature::Verifier;
+
+        let verified = match self {
+            CosePublicKey::Es256(key) => p256::ecdsa::Signature::from_der(signature)
+                .map(|signature| key.verify(message, &signature).is_ok()),
+            CosePublicKey::EdDsa(key) => ed25519_dalek::Signature::from_slice(signature)
+                .map(|signature| key.verify(message, &signature).is_ok()),
+            CosePublicKey::Rs256(key) => rsa::pkcs1v15::Signature::try_from(signature)
+                .map(|signature| key.verify(message, &signature).is_ok()),
+        };
+
+        match verified {
+            Ok(true) => Ok(()),
+            _ => Err(WebauthnError::InvalidSignature),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+/// A credential that passed registration.
+pub struct RegisteredCredential {
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+/// Verifies registration and authentication ceremonies for one relying party.
+///
+/// Only `none` and self `packed` attestation are accepted; registration
+/// options ask browsers for `none`, so authenticator certificates are never
+/// needed.
+pub struct RelyingParty {
+    id: String,
+    origin: String,
+}
+
+impl RelyingParty {
+    pub fn new(id: &str, origin: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            origin: origin.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn verify_client_data(
+        &self,
+        client_data_json: &[u8],
+        kind: &str,
+        challenge: &[u8],
+    ) -> Result<(), WebauthnError> {
+        let client_data: CollectedClientData = serde_json::from_slice(client_data_json)
+            .map_err(|_| WebauthnError::Malformed("client data"))?;
+
+        if client_data.kind != kind {
+            return Err(WebauthnError::Mismatch("ceremony type"));
+        }
+        if decode_base64url(&client_data.challenge)? != challenge {
+            return Err(WebauthnError::Mismatch("challenge"));
+        }
+        if client_data.origin != self.origin {
+            return Err(WebauthnError::Mismatch("origin"));
+        }
+
+        Ok(())
+    }
+
+    fn verify_authenticator_data(
+        &self,
+        data: &AuthenticatorData,
+        require_user_verification: bool,
+    ) -> Result<(), WebauthnError> {
+        if data.rp_id_hash[..] != Sha256::digest(self.id.as_bytes())[..] {
+            return Err(WebauthnError::Mismatch("relying party ID"));
+        }
+        if data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(WebauthnError::UserNotPresent);
+        }
+        if require_user_verification && data.flags & FLAG_USER_VERIFIED == 0 {
+            return Err(WebauthnError::UserNotVerified);
+        }
+
+        Ok(())
+    }
+
+    pub fn verify_registration(
+        &self,
+        challenge: &[u8],
+        client_data_json: &[u8],
+        attestation_object: &[u8],
+        require_user_verification: bool,
+    ) -> Result<RegisteredCredential, WebauthnError> {
+        self.verify_client_data(client_data_json, "webauthn.create", challenge)?;
+
+        let attestation: Value = ciborium::from_reader(attestation_object)
+            .map_err(|_| WebauthnError::Malformed("attestation object"))?;
+        let attestation = attestation
+            .into_map()
+            .map_err(|_| WebauthnError::Malformed("attestation object"))?;
+        let format = map_get_text(&attestation, "fmt")
+            .and_then(Value::as_text)
+            .ok_or(WebauthnError::Malformed("attestation format"))?;
+        let statement = map_get_text(&attestation, "attStmt")
+            .and_then(Value::as_map)
+            .ok_or(WebauthnError::Malformed("attestation statement"))?;
+        let raw_auth_data = map_get_text(&attestation, "authData")
+            .and_then(Value::as_bytes)
+            .ok_or(WebauthnError::Malformed("authenticator data"))?;
+
+        let auth_data = AuthenticatorData::parse(raw_auth_data)?;
+        self.verify_authenticator_data(&auth_data, require_user_verification)?;
+        let credential = auth_data
+            .attested_credential
+            .ok_or(WebauthnError::Malformed("attested credential data"))?;
+        let public_key = CosePublicKey::parse(&credential.public_key)?;
+
+        match format {
+            "none" => {}
+            "packed" if map_get_text(statement, "x5c").is_none() => {
+                let alg = as_i64(map_get_text(statement, "alg"))
+                    .ok_or(WebauthnError::Malformed("attestation statement"))?;
+                if alg != public_key.algorithm() {
+                    return Err(WebauthnError::Mismatch("attestation algorithm"));
+                }
+                let signature = map_get_text(statement, "sig")
+                    .and_then(Value::as_bytes)
+                    .ok_or(WebauthnError::Malformed("attestation statement"))?;
+                public_key.verify(&signed_data(raw_auth_data, client_data_json), signature)?;
+            }
+            format => return Err(WebauthnError::UnsupportedAttestation(format.to_string())),
+        }
+
+        Ok(RegisteredCredential {
+            credential_id: credential.credential_id,
+            public_key: credential.public_key,
+            sign_count: auth_data.sign_count,
+        })
+    }
+
+    /// Verifies an assertion against a stored credential and returns the
+    /// new signature counter.
+    pub fn verify_assertion(
+        &self,
+        challenge: &[u8],
+        assertion: &WebauthnAssertion,
+        public_key: &[u8],
+        stored_sign_count: u32,
+        require_user_verification: bool,
+    ) -> Result<u32, WebauthnError> {
+        self.verify_client_data(&assertion.client_data_json, "webauthn.get", challenge)?;
+
+        let auth_data = AuthenticatorData::parse(&assertion.authenticator_data)?;
+        self.verify_authenticator_data(&auth_data, require_user_verification)?;
+
+        CosePublicKey::parse(public_key)?.verify(
+            &signed_data(&assertion.authenticator_data, &assertion.client_data_json),
+            &assertion.signature,
+        )?;
+
+        // Authenticators without a counter always report zero.
+        if (auth_data.sign_count != 0 || stored_sign_count != 0)
+            && auth_data.sign_count <= stored_sign_count
+        {
+            return Err(WebauthnError::SignCount);
+        }
+
+        Ok(auth_data.sign_count)
+    }
+}
+
+fn signed_data(auth_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    let mut data = auth_data.to_vec();
+    data.extend_from_slice(&Sha256::digest(client_data_json));
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes_gcm::aead::OsRng;
+    use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+    use uuid::Uuid;
+
+    const RP_ID: &str = "localhost";
+    const ORIGIN: &str = "http://localhost:8080";
+
+    fn cose_key(key: &SigningKey) -> Vec<u8> {
+        let point = key.verifying_key().to_encoded_point(false);
+        let value = Value::Map(vec![
+            (Value::from(1), Value::from(2)),
+            (Value::from(3), Value::from(ES256)),
+            (Value::from(-1), Value::from(1)),
+            (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+            (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn auth_data(flags: u8, sign_count: u32, attested: Option<(&[u8], &[u8])>) -> Vec<u8> {
+        let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        if let Some((credential_id, public_key)) = attested {
+            data.extend_from_slice(&[0_u8; 16]);
+            data.extend_from_slice(&(credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(credential_id);
+            data.extend_from_slice(public_key);
+        }
+        data
+    }
+
+    fn client_data(kind: &str, challenge: &[u8], origin: &str) -> Vec<u8> {
+        format!(
+            r#"{{"type":"{}","challenge":"{}","origin":"{}","crossOrigin":false}}"#,
+            kind,
+            encode_base64url(challenge),
+            origin
+        )
+        .into_bytes()
+    }
+
+    fn attestation_object(
+        format: &str,
+        statement: Vec<(Value, Value)>,
+        auth_data: &[u8],
+    ) -> Vec<u8> {
+        let value = Value::Map(vec![
+            (Value::from("fmt"), Value::from(format)),
+            (Value::from("attStmt"), Value::Map(statement)),
+            (Value::from("authData"), Value::Bytes(auth_data.to_vec())),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_register_and_authenticate_es256() {
+        let rp = RelyingParty::new(RP_ID, ORIGIN);
+        let key = SigningKey::random(&mut OsRng);
+        let credential_id = b"credential-1".to_vec();
+        let challenge = b"registration-challenge".to_vec();
+
+        let registration_data = auth_data(0x45, 0, Some((&credential_id, &cose_key(&key))));
+        let client_data_json = client_data("webauthn.create", &challenge, ORIGIN);
+        let signature: Signature = key.sign(&signed_data(&registration_data, &client_data_json));
+        let statement = vec![
+            (Value::from("alg"), Value::from(ES256)),
+            (
+                Value::from("sig"),
+                Value::Bytes(signature.to_der().as_bytes().to_vec()),
+            ),
+        ];
+        let registered = rp
+            .verify_registration(
+                &challenge,
+                &client_data_json,
+                &attestation_object("packed", statement, &registration_data),
+                true,
+            )
+            .unwrap();
+        assert_eq!(credential_id, registered.credential_id);
+
+        let challenge = b"authentication-challenge".to_vec();
+        let authenticator_data = auth_data(0x05, 1, None);
+        let client_data_json = client_data("webauthn.get", &challenge, ORIGIN);
+        let signature: Signature = key.sign(&signed_data(&authenticator_data, &client_data_json));
+        let assertion = WebauthnAssertion {
+            challenge_id: Uuid::new_v4(),
+            credential_id,
+            client_data_json,
+            authenticator_data,
+            signature: signature.to_der().as_bytes().to_vec(),
+            user_handle: None,
+        };
+
+        assert_eq!(
+            1,
+            rp.verify_assertion(&challenge, &assertion, &registered.public_key, 0, true)
+                .unwrap()
+        );
+        assert!(matches!(
+            rp.verify_assertion(&challenge, &assertion, &registered.public_key, 1, true),
+            Err(WebauthnError::SignCount)
+        ));
+        assert!(matches!(
+            rp.verify_assertion(b"other", &assertion, &registered.public_key, 0, true),
+            Err(WebauthnError::Mismatch("challenge"))
+        ));
+    }
+
+    #[test]
+    fn test_registration_checks_origin_and_user_verification() {
+        let rp = RelyingParty::new(RP_ID, ORIGIN);
+        let key = SigningKey::random(&mut OsRng);
+        let challenge = b"registration-challenge".to_vec();
+        let registration_data = auth_data(0x41, 0, Some((b"credential-1", &cose_key(&key))));
+        let attestation = attestation_object("none", Vec::new(), &registration_data);
+
+        let phishing = client_data("webauthn.create", &challenge, "https://evil.example");
+        assert!(matches!(
+            rp.verify_registration(&challenge, &phishing, &attestation, false),
+            Err(WebauthnError::Mismatch("origin"))
+        ));
+
+        let client_data_json = client_data("webauthn.create", &challenge, ORIGIN);
+        assert!(matches!(
+            rp.verify_registration(&challenge, &client_data_json, &attestation, true),
+            Err(WebauthnError::UserNotVerified)
+        ));
+        assert!(rp
+            .verify_registration(&challenge, &client_data_json, &attestation, false)
+            .is_ok());
+    }
+}